@@ -0,0 +1,183 @@
+use actix_web::{HttpResponse, web};
+use sea_orm::DbConn;
+use sea_orm::prelude::DateTime;
+use serde::Deserialize;
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+use super::pagination::{Page, resolve_limit};
+use crate::audit::verify::{self, VerifyReport};
+use crate::audit::{AuditAction, checkpoint};
+use crate::auth::{RequirePermission, permissions};
+use crate::config::AppConfig;
+use crate::db::models::UserAuditModel;
+use crate::db::repositories::{UserAuditQuery, UserAuditRepository};
+use crate::error::{AppError, ErrorResponse};
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("/audit").route(
+            web::get()
+                .to(get_audit)
+                .wrap(RequirePermission(permissions::AUDIT_READ)),
+        ),
+    )
+    .service(
+        web::resource("/audit/verify").route(
+            web::get()
+                .to(verify_audit)
+                .wrap(RequirePermission(permissions::AUDIT_READ)),
+        ),
+    );
+}
+
+/// Audit routes, nested in the `/users` scope.
+pub fn configure_user_audit(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("/{id}/audit").route(
+            web::get()
+                .to(get_user_audit)
+                .wrap(RequirePermission(permissions::AUDIT_READ)),
+        ),
+    );
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AuditParams {
+    user_id: Option<i32>,
+    /// User who made the change.
+    actor_id: Option<i32>,
+    action: Option<AuditAction>,
+    request_id: Option<String>,
+    /// Entries at or after this time, e.g. `2026-10-18T00:00:00`.
+    #[param(value_type = Option<String>, format = DateTime)]
+    from: Option<DateTime>,
+    /// Entries before this time.
+    #[param(value_type = Option<String>, format = DateTime)]
+    to: Option<DateTime>,
+    limit: Option<u64>,
+    offset: Option<u64>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct UserAuditParams {
+    limit: Option<u64>,
+    offset: Option<u64>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/audit",
+    tag = "audit",
+    params(AuditParams),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Matching entries, newest first", body = Page<UserAuditModel>),
+        (status = 400, description = "Invalid query", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 403, description = "Missing `audit:read`", body = ErrorResponse, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_audit(
+    db: web::Data<DbConn>,
+    config: web::Data<AppConfig>,
+    query: web::Query<AuditParams>,
+) -> Result<HttpResponse, AppError> {
+    let query = query.into_inner();
+    if let (Some(from), Some(to)) = (query.from, query.to)
+        && from >= to
+    {
+        return Err(AppError::Validation("from must be earlier than to".into()));
+    }
+
+    find_page(
+        &db,
+        UserAuditQuery {
+            user_id: query.user_id,
+            actor_id: query.actor_id,
+            action: query.action,
+            request_id: query.request_id,
+            from: query.from,
+            to: query.to,
+            limit: resolve_limit(query.limit, &config.pagination)?,
+            offset: query.offset.unwrap_or(0),
+        },
+    )
+    .await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/users/{id}/audit",
+    tag = "audit",
+    params(("id" = i32, Path, description = "User id"), UserAuditParams),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Changes to the user, newest first", body = Page<UserAuditModel>),
+        (status = 400, description = "Invalid query", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 403, description = "Missing `audit:read`", body = ErrorResponse, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_user_audit(
+    db: web::Data<DbConn>,
+    config: web::Data<AppConfig>,
+    path: web::Path<i32>,
+    query: web::Query<UserAuditParams>,
+) -> Result<HttpResponse, AppError> {
+    // Entries outlive the user, so a purged user still has a history.
+    find_page(
+        &db,
+        UserAuditQuery {
+            user_id: Some(path.into_inner()),
+            limit: resolve_limit(query.limit, &config.pagination)?,
+            offset: query.offset.unwrap_or(0),
+            ..Default::default()
+        },
+    )
+    .await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/audit/verify",
+    tag = "audit",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Result of walking the hash chain; `intact` is false when a link is broken", body = VerifyReport),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 403, description = "Missing `audit:read`", body = ErrorResponse, content_type = "application/problem+json"),
+    )
+)]
+pub async fn verify_audit(
+    db: web::Data<DbConn>,
+    config: web::Data<AppConfig>,
+) -> Result<HttpResponse, AppError> {
+    let key = checkpoint::verifying_key(&config.audit);
+    let report = verify::verify(&db, key.as_ref()).await?;
+
+    if let Some(broken) = &report.first_broken {
+        log::warn!(
+            "Audit chain broken at entry {}: {:?}",
+            broken.entry_id,
+            broken.reason
+        );
+    }
+    Ok(HttpResponse::Ok().json(report))
+}
+
+async fn find_page(db: &DbConn, query: UserAuditQuery) -> Result<HttpResponse, AppError> {
+    let page = UserAuditRepository::new(Arc::new(db.clone()))
+        .find_page(&query)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(Page {
+        data: page.entries,
+        total: page.total,
+        limit: query.limit,
+        offset: Some(query.offset),
+        next_cursor: None,
+    }))
+}
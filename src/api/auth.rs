@@ -0,0 +1,161 @@
+use actix_web::{HttpResponse, web};
+use log::{info, warn};
+use sea_orm::DbConn;
+use sea_orm::prelude::Uuid;
+use sea_orm::sqlx::types::chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::auth::{JwtKeys, password, refresh_token};
+use crate::config::AppConfig;
+use crate::db::models::UserModel;
+use crate::db::repositories::{CredentialRepository, RefreshTokenRepository, UserRepository};
+use crate::error::AppError;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/auth")
+            .service(web::resource("/login").post(login))
+            .service(web::resource("/refresh").post(refresh))
+            .service(web::resource("/logout").post(logout)),
+    );
+}
+
+#[derive(Deserialize)]
+pub struct LoginRequest {
+    /// Username or email address.
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Deserialize)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
+#[derive(Serialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub token_type: &'static str,
+    pub expires_in: u64,
+    pub refresh_token: String,
+    pub refresh_expires_in: u64,
+}
+
+pub async fn login(
+    db: web::Data<DbConn>,
+    keys: web::Data<JwtKeys>,
+    config: web::Data<AppConfig>,
+    item: web::Json<LoginRequest>,
+) -> Result<HttpResponse, AppError> {
+    let db = Arc::new(db.get_ref().clone());
+    let users = UserRepository::new(db.clone());
+    let credentials = CredentialRepository::new(db.clone());
+
+    let user = if item.username.contains('@') {
+        users.find_by_email(&item.username).await?
+    } else {
+        users.find_by_username(&item.username).await?
+    };
+
+    let credential = match &user {
+        Some(user) if user.deleted_on.is_none() => credentials.find_by_user_id(user.id).await?,
+        _ => None,
+    };
+
+    let verified = match &credential {
+        Some(credential) => {
+            password::verify_password(&item.password, &credential.password_hash).await?
+        }
+        None => false,
+    };
+
+    let user = match (user, credential) {
+        (Some(user), Some(_)) if verified => user,
+        (_, None) => {
+            password::verify_dummy_password(&item.password).await?;
+            warn!("Failed login attempt for unknown or password-less account");
+            return Err(invalid_credentials());
+        }
+        _ => {
+            warn!("Failed login attempt for an existing account");
+            return Err(invalid_credentials());
+        }
+    };
+
+    let (raw_refresh_token, refresh_model) = refresh_token::new_token(
+        user.id,
+        Uuid::new_v4(),
+        config.auth.refresh_token_ttl_secs,
+        Local::now().naive_local(),
+    );
+    RefreshTokenRepository::new(db)
+        .create(refresh_model)
+        .await?;
+
+    info!("User with ID {} logged in", user.id);
+    Ok(HttpResponse::Ok().json(token_response(&keys, &config, &user, raw_refresh_token)?))
+}
+
+pub async fn refresh(
+    db: web::Data<DbConn>,
+    keys: web::Data<JwtKeys>,
+    config: web::Data<AppConfig>,
+    item: web::Json<RefreshRequest>,
+) -> Result<HttpResponse, AppError> {
+    let db = Arc::new(db.get_ref().clone());
+    let tokens = RefreshTokenRepository::new(db.clone());
+    let users = UserRepository::new(db);
+    let now = Local::now().naive_local();
+
+    let current = refresh_token::redeem(&tokens, &item.refresh_token, now).await?;
+
+    let user = users
+        .find_by_id(current.user_id)
+        .await?
+        .filter(|user| user.deleted_on.is_none())
+        .ok_or_else(|| AppError::Unauthorized("Invalid refresh token".into()))?;
+
+    let raw_refresh_token =
+        refresh_token::rotate(&tokens, &current, config.auth.refresh_token_ttl_secs, now).await?;
+
+    Ok(HttpResponse::Ok().json(token_response(&keys, &config, &user, raw_refresh_token)?))
+}
+
+pub async fn logout(
+    db: web::Data<DbConn>,
+    item: web::Json<RefreshRequest>,
+) -> Result<HttpResponse, AppError> {
+    let tokens = RefreshTokenRepository::new(Arc::new(db.get_ref().clone()));
+
+    if let Some(current) = tokens
+        .find_by_hash(&refresh_token::hash(&item.refresh_token))
+        .await?
+    {
+        tokens
+            .revoke_family(current.family_id, Local::now().naive_local())
+            .await?;
+        info!("User with ID {} logged out", current.user_id);
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+fn token_response(
+    keys: &JwtKeys,
+    config: &AppConfig,
+    user: &UserModel,
+    refresh_token: String,
+) -> Result<TokenResponse, AppError> {
+    Ok(TokenResponse {
+        access_token: keys.issue_access_token(user.id, &user.username)?,
+        token_type: "Bearer",
+        expires_in: keys.access_token_ttl_secs,
+        refresh_token,
+        refresh_expires_in: config.auth.refresh_token_ttl_secs,
+    })
+}
+
+fn invalid_credentials() -> AppError {
+    AppError::Unauthorized("Invalid username or password".into())
+}
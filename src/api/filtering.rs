@@ -0,0 +1,284 @@
+use sea_orm::Order;
+use sea_orm::sqlx::types::chrono::{NaiveDate, NaiveDateTime};
+
+use crate::db::repositories::{
+    FieldKind, FilterOp, FilterValue, UserField, UserFilter, UserSort, user_field,
+};
+use crate::error::AppError;
+
+/// Query parameters of the list endpoint that are not column filters.
+const RESERVED_PARAMS: &[&str] = &[
+    "include_deleted",
+    "limit",
+    "offset",
+    "cursor",
+    "order_by",
+    "sort",
+];
+
+/// Parses `field=value` and `field[op]=value` pairs into repository filters.
+///
+/// Supported operators are `eq` (the default), `prefix` and `ilike` on text
+/// columns, `gt`/`gte`/`lt`/`lte` on numeric and timestamp columns, and
+/// `null=true|false` on nullable columns.
+pub fn parse_filters(params: &[(String, String)]) -> Result<Vec<UserFilter>, AppError> {
+    params
+        .iter()
+        .filter(|(key, _)| !RESERVED_PARAMS.contains(&key.as_str()))
+        .map(|(key, value)| parse_filter(key, value))
+        .collect()
+}
+
+fn parse_filter(key: &str, value: &str) -> Result<UserFilter, AppError> {
+    let (name, op) = match key.split_once('[') {
+        Some((name, rest)) => match rest.strip_suffix(']') {
+            Some(op) => (name, op),
+            None => {
+                return Err(AppError::Validation(format!(
+                    "Malformed filter parameter '{}'",
+                    key
+                )));
+            }
+        },
+        None => (key, "eq"),
+    };
+
+    let field = user_field(name)
+        .ok_or_else(|| AppError::Validation(format!("Unknown filter field '{}'", name)))?;
+
+    let unsupported = || {
+        AppError::Validation(format!(
+            "Operator '{}' is not supported on field '{}'",
+            op, field.name
+        ))
+    };
+
+    let op = match op {
+        "eq" => FilterOp::Eq(parse_value(field, value)?),
+        "prefix" if field.kind == FieldKind::Text => FilterOp::Prefix(value.to_string()),
+        "ilike" if field.kind == FieldKind::Text => FilterOp::ILike(value.to_string()),
+        "gt" | "gte" | "lt" | "lte" if field.kind != FieldKind::Text => {
+            let value = parse_value(field, value)?;
+            match op {
+                "gt" => FilterOp::Gt(value),
+                "gte" => FilterOp::Gte(value),
+                "lt" => FilterOp::Lt(value),
+                _ => FilterOp::Lte(value),
+            }
+        }
+        "null" if field.nullable => match value {
+            "true" => FilterOp::IsNull(true),
+            "false" => FilterOp::IsNull(false),
+            _ => return Err(invalid_value(field)),
+        },
+        "prefix" | "ilike" | "gt" | "gte" | "lt" | "lte" | "null" => {
+            return Err(unsupported());
+        }
+        _ => {
+            return Err(AppError::Validation(format!(
+                "Unknown filter operator '{}' on field '{}'",
+                op, field.name
+            )));
+        }
+    };
+
+    Ok(UserFilter {
+        column: field.column,
+        op,
+    })
+}
+
+fn parse_value(field: &UserField, raw: &str) -> Result<FilterValue, AppError> {
+    match field.kind {
+        FieldKind::Integer => raw
+            .parse()
+            .map(FilterValue::Integer)
+            .map_err(|_| invalid_value(field)),
+        FieldKind::Text => Ok(FilterValue::Text(raw.to_string())),
+        FieldKind::Timestamp => parse_timestamp(raw)
+            .map(FilterValue::Timestamp)
+            .ok_or_else(|| invalid_value(field)),
+    }
+}
+
+/// Accepts `YYYY-MM-DDTHH:MM:SS[.fff]` or a bare `YYYY-MM-DD` (midnight).
+fn parse_timestamp(raw: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(raw, "%Y-%m-%dT%H:%M:%S%.f")
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })
+}
+
+fn invalid_value(field: &UserField) -> AppError {
+    AppError::Validation(format!("Invalid value for field '{}'", field.name))
+}
+
+/// Parses `sort=-created_on,username`; a leading `-` sorts descending.
+pub fn parse_sort(raw: &str) -> Result<Vec<UserSort>, AppError> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let (name, order) = match part.strip_prefix('-') {
+                Some(name) => (name, Order::Desc),
+                None => (part.strip_prefix('+').unwrap_or(part), Order::Asc),
+            };
+
+            user_field(name)
+                .map(|field| UserSort {
+                    column: field.column,
+                    order,
+                })
+                .ok_or_else(|| AppError::Validation(format!("Unknown sort field '{}'", name)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::models::UserColumn;
+
+    fn params(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn rejected(pairs: &[(&str, &str)]) -> String {
+        match parse_filters(&params(pairs)) {
+            Err(AppError::Validation(message)) => message,
+            other => panic!("expected a validation error, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn reserved_params_are_not_filters() {
+        let filters = parse_filters(&params(&[
+            ("limit", "5"),
+            ("cursor", "abc"),
+            ("sort", "-id"),
+            ("include_deleted", "true"),
+        ]))
+        .unwrap();
+
+        assert!(filters.is_empty());
+    }
+
+    #[test]
+    fn parses_operators() {
+        let filters = parse_filters(&params(&[
+            ("username", "jdoe"),
+            ("email[prefix]", "j"),
+            ("last_name[ilike]", "do"),
+            ("id[gte]", "10"),
+            ("created_on[lt]", "2026-01-01"),
+            ("phone[null]", "true"),
+        ]))
+        .unwrap();
+
+        assert!(matches!(
+            &filters[0],
+            UserFilter { column: UserColumn::Username, op: FilterOp::Eq(FilterValue::Text(v)) } if v == "jdoe"
+        ));
+        assert!(matches!(&filters[1].op, FilterOp::Prefix(v) if v == "j"));
+        assert!(matches!(&filters[2].op, FilterOp::ILike(v) if v == "do"));
+        assert!(matches!(
+            filters[3].op,
+            FilterOp::Gte(FilterValue::Integer(10))
+        ));
+        assert!(matches!(
+            &filters[4].op,
+            FilterOp::Lt(FilterValue::Timestamp(at)) if at.to_string() == "2026-01-01 00:00:00"
+        ));
+        assert!(matches!(filters[5].op, FilterOp::IsNull(true)));
+    }
+
+    #[test]
+    fn accepts_timestamps_with_fractions() {
+        let filters =
+            parse_filters(&params(&[("updated_on[gt]", "2026-10-18T12:30:05.25")])).unwrap();
+
+        assert!(matches!(
+            &filters[0].op,
+            FilterOp::Gt(FilterValue::Timestamp(at)) if at.to_string() == "2026-10-18 12:30:05.250"
+        ));
+    }
+
+    #[test]
+    fn rejects_unknown_fields() {
+        assert_eq!(
+            rejected(&[("password", "x")]),
+            "Unknown filter field 'password'"
+        );
+    }
+
+    #[test]
+    fn rejects_bad_operators_and_values() {
+        assert_eq!(
+            rejected(&[("id[prefix]", "1")]),
+            "Operator 'prefix' is not supported on field 'id'"
+        );
+        assert_eq!(
+            rejected(&[("username[null]", "true")]),
+            "Operator 'null' is not supported on field 'username'"
+        );
+        assert_eq!(
+            rejected(&[("id[near]", "1")]),
+            "Unknown filter operator 'near' on field 'id'"
+        );
+        assert_eq!(
+            rejected(&[("id[gt", "1")]),
+            "Malformed filter parameter 'id[gt'"
+        );
+        assert_eq!(rejected(&[("id", "abc")]), "Invalid value for field 'id'");
+        assert_eq!(
+            rejected(&[("phone[null]", "yes")]),
+            "Invalid value for field 'phone'"
+        );
+        assert_eq!(
+            rejected(&[("created_on", "18/10/2026")]),
+            "Invalid value for field 'created_on'"
+        );
+    }
+
+    #[test]
+    fn parses_sort() {
+        let sort = parse_sort("-created_on, +username,,id").unwrap();
+
+        assert_eq!(sort.len(), 3);
+        assert!(matches!(
+            sort[0],
+            UserSort {
+                column: UserColumn::CreatedOn,
+                order: Order::Desc
+            }
+        ));
+        assert!(matches!(
+            sort[1],
+            UserSort {
+                column: UserColumn::Username,
+                order: Order::Asc
+            }
+        ));
+        assert!(matches!(
+            sort[2],
+            UserSort {
+                column: UserColumn::Id,
+                order: Order::Asc
+            }
+        ));
+    }
+
+    #[test]
+    fn rejects_unknown_sort_fields() {
+        assert!(matches!(
+            parse_sort("id,-secret"),
+            Err(AppError::Validation(message)) if message == "Unknown sort field 'secret'"
+        ));
+    }
+}
@@ -0,0 +1,192 @@
+use actix_web::{HttpResponse, web};
+use sea_orm::{DbConn, DbErr};
+use sea_orm_migration::MigratorTrait;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use crate::config::AppConfig;
+use crate::db::migrations::Migrator;
+use crate::shutdown::Shutdown;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/health")
+            .route("", web::get().to(live))
+            .route("/live", web::get().to(live))
+            .route("/ready", web::get().to(ready)),
+    );
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Status {
+    Up,
+    Degraded,
+    Down,
+}
+
+#[derive(Serialize)]
+pub struct ComponentHealth {
+    pub status: Status,
+    pub latency_ms: f64,
+    #[serde(flatten)]
+    pub details: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Serialize)]
+pub struct Readiness {
+    pub status: Status,
+    pub checks: BTreeMap<&'static str, ComponentHealth>,
+}
+
+/// The process is up and serving requests; never touches dependencies.
+async fn live() -> HttpResponse {
+    HttpResponse::Ok().json(serde_json::json!({
+        "status": Status::Up,
+        "message": "Service is running"
+    }))
+}
+
+/// Ready to receive traffic: the database answers, the schema is current
+/// and the pool has spare connections. Responds 503 when any check is down,
+/// and immediately once shutdown has begun.
+async fn ready(
+    db: web::Data<DbConn>,
+    config: web::Data<AppConfig>,
+    shutdown: web::Data<Shutdown>,
+) -> HttpResponse {
+    let mut checks = BTreeMap::new();
+
+    if shutdown.is_shutting_down() {
+        checks.insert(
+            "shutdown",
+            ComponentHealth {
+                status: Status::Down,
+                latency_ms: 0.0,
+                details: details([("reason", "shutting down".into())]),
+            },
+        );
+        return HttpResponse::ServiceUnavailable().json(Readiness {
+            status: Status::Down,
+            checks,
+        });
+    }
+
+    let timeout = Duration::from_millis(config.health.check_timeout_ms);
+
+    checks.insert(
+        "database",
+        check("database", timeout, async {
+            db.ping().await?;
+            Ok((Status::Up, serde_json::Map::new()))
+        })
+        .await,
+    );
+
+    checks.insert(
+        "migrations",
+        check("migrations", timeout, async {
+            let pending: Vec<String> = Migrator::get_pending_migrations(db.get_ref())
+                .await?
+                .iter()
+                .map(|migration| migration.name().to_string())
+                .collect();
+            let status = if pending.is_empty() {
+                Status::Up
+            } else {
+                Status::Down
+            };
+            Ok((status, details([("pending", serde_json::json!(pending))])))
+        })
+        .await,
+    );
+
+    checks.insert("pool", pool_health(&db, &config));
+
+    let status = checks
+        .values()
+        .map(|component| component.status)
+        .max_by_key(|status| match status {
+            Status::Up => 0,
+            Status::Degraded => 1,
+            Status::Down => 2,
+        })
+        .unwrap_or(Status::Up);
+
+    let readiness = Readiness { status, checks };
+    if status == Status::Down {
+        HttpResponse::ServiceUnavailable().json(readiness)
+    } else {
+        HttpResponse::Ok().json(readiness)
+    }
+}
+
+/// Runs one check under `timeout`, timing it and turning errors into `DOWN`.
+/// The endpoint is unauthenticated, so errors are logged rather than
+/// returned; their text can name the database host and user.
+async fn check<F>(name: &str, timeout: Duration, probe: F) -> ComponentHealth
+where
+    F: Future<Output = Result<(Status, serde_json::Map<String, serde_json::Value>), DbErr>>,
+{
+    let started = Instant::now();
+    let result = tokio::time::timeout(timeout, probe).await;
+    let latency_ms = (started.elapsed().as_secs_f64() * 1_000_000.0).round() / 1000.0;
+
+    let (status, details) = match result {
+        Ok(Ok((status, details))) => (status, details),
+        Ok(Err(err)) => {
+            log::warn!("Readiness check '{}' failed: {}", name, err);
+            (Status::Down, details([("error", "check failed".into())]))
+        }
+        Err(_) => (
+            Status::Down,
+            details([(
+                "error",
+                format!("timed out after {} ms", timeout.as_millis()).into(),
+            )]),
+        ),
+    };
+
+    ComponentHealth {
+        status,
+        latency_ms,
+        details,
+    }
+}
+
+/// Reports `DEGRADED` when every pooled connection is in use.
+fn pool_health(db: &DbConn, config: &AppConfig) -> ComponentHealth {
+    let pool = db.get_postgres_connection_pool();
+    let max = config.database.pool.max_connections;
+    let size = pool.size();
+    let idle = pool.num_idle() as u32;
+    let in_use = size.saturating_sub(idle);
+    let saturation = f64::from(in_use) / f64::from(max.max(1));
+
+    ComponentHealth {
+        status: if in_use >= max {
+            Status::Degraded
+        } else {
+            Status::Up
+        },
+        latency_ms: 0.0,
+        details: details([
+            ("size", size.into()),
+            ("idle", idle.into()),
+            ("in_use", in_use.into()),
+            ("max", max.into()),
+            ("saturation", saturation.into()),
+        ]),
+    }
+}
+
+fn details<const N: usize>(
+    entries: [(&str, serde_json::Value); N],
+) -> serde_json::Map<String, serde_json::Value> {
+    entries
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value))
+        .collect()
+}
@@ -0,0 +1,15 @@
+use actix_web::{HttpResponse, web};
+use sea_orm::DbConn;
+
+use crate::config::AppConfig;
+use crate::metrics::metrics;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.route("/metrics", web::get().to(export));
+}
+
+async fn export(db: web::Data<DbConn>, config: web::Data<AppConfig>) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(metrics().render(&db, config.database.pool.max_connections))
+}
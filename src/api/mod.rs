@@ -1,19 +1,50 @@
 use actix_web::web::ServiceConfig;
 use actix_web::{HttpResponse, web};
 use sea_orm::DbConn;
+
+use crate::error::AppError;
+mod audit;
+mod auth;
+mod filtering;
+mod health;
+mod metrics;
+mod openapi;
+mod pagination;
+mod patch;
+mod preconditions;
+mod roles;
+mod search;
 mod users;
+mod webhooks;
 
 pub fn configure_routes(cfg: &mut ServiceConfig, db: DbConn) {
     let db_data = web::Data::new(db);
 
     cfg.app_data(db_data.clone())
-        .service(web::scope("/api").configure(|c| users::configure(c)))
-        .route("/health", web::get().to(health_check));
+        .app_data(web::JsonConfig::default().error_handler(|err, _| invalid_request(err)))
+        .app_data(web::QueryConfig::default().error_handler(|err, _| invalid_request(err)))
+        .app_data(web::PathConfig::default().error_handler(|err, _| invalid_request(err)))
+        .service(
+            web::scope("/api")
+                .configure(openapi::configure)
+                .configure(audit::configure)
+                .configure(auth::configure)
+                .configure(roles::configure)
+                .configure(users::configure)
+                .configure(webhooks::configure),
+        )
+        .configure(health::configure)
+        .configure(metrics::configure)
+        .default_service(web::to(route_not_found));
+}
+
+/// Maps body, query and path extractor failures onto `INVALID_REQUEST`.
+fn invalid_request(err: impl std::fmt::Display) -> actix_web::Error {
+    AppError::Validation(err.to_string()).into()
 }
 
-async fn health_check() -> HttpResponse {
-    HttpResponse::Ok().json(serde_json::json!({
-        "status": "UP",
-        "message": "Service is running"
-    }))
+async fn route_not_found() -> Result<HttpResponse, AppError> {
+    Err(AppError::NotFound(
+        "No route matches the requested path".into(),
+    ))
 }
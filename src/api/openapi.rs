@@ -0,0 +1,154 @@
+use actix_web::http::header::CONTENT_SECURITY_POLICY;
+use actix_web::{HttpResponse, web};
+use std::sync::LazyLock;
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+
+use super::pagination::CursorKey;
+use super::{audit, users, webhooks};
+use crate::audit::AuditAction;
+use crate::webhooks::DeliveryStatus;
+
+/// Generated once; the document only changes with the code.
+static SPEC: LazyLock<String> = LazyLock::new(spec_json);
+
+const DOCS_HTML: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="utf-8">
+  <title>rust-actix-seaorm API</title>
+  <link rel="stylesheet" href="https://cdn.jsdelivr.net/npm/swagger-ui-dist@5/swagger-ui.css">
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="https://cdn.jsdelivr.net/npm/swagger-ui-dist@5/swagger-ui-bundle.js"></script>
+  <script src="/api/docs/init.js"></script>
+</body>
+</html>
+"##;
+
+/// Kept out of the page so its policy needs no `'unsafe-inline'` scripts.
+const DOCS_INIT_JS: &str = r##"SwaggerUIBundle({ url: "/api/openapi.json", dom_id: "#swagger-ui" });
+"##;
+
+/// Lets the page load Swagger UI from the CDN; `frame-ancestors` is added
+/// by the security headers middleware.
+const DOCS_CSP: &str = "default-src 'none'; script-src 'self' https://cdn.jsdelivr.net; \
+    style-src 'unsafe-inline' https://cdn.jsdelivr.net; \
+    img-src 'self' data: https://cdn.jsdelivr.net; connect-src 'self'";
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        users::get_users,
+        users::search_users,
+        users::create_user,
+        users::get_user,
+        users::replace_user,
+        users::patch_user,
+        users::delete_user_physical,
+        users::delete_user_logical,
+        users::restore_user,
+        audit::get_audit,
+        audit::get_user_audit,
+        audit::verify_audit,
+        webhooks::get_webhooks,
+        webhooks::create_webhook,
+        webhooks::get_webhook,
+        webhooks::replace_webhook,
+        webhooks::delete_webhook,
+        webhooks::get_deliveries,
+        webhooks::get_delivery,
+        webhooks::redeliver,
+    ),
+    // Enums used only as query parameters are not collected automatically.
+    components(schemas(AuditAction, CursorKey, DeliveryStatus)),
+    info(description = "Users, authentication and roles over actix-web and SeaORM."),
+    modifiers(&BearerAuth),
+    tags(
+        (name = "users", description = "User management"),
+        (name = "audit", description = "History of user changes"),
+        (name = "webhooks", description = "HTTP callbacks on user events")
+    )
+)]
+pub struct ApiDoc;
+
+/// Declares the JWT bearer scheme the secured paths refer to.
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+    }
+}
+
+/// The document as committed in `openapi.json`.
+pub fn spec_json() -> String {
+    let mut openapi = ApiDoc::openapi();
+    // The crate declares no license; omit the empty object.
+    openapi.info.license = None;
+
+    let mut json = openapi
+        .to_pretty_json()
+        .expect("the OpenAPI document always serializes");
+    json.push('\n');
+    json
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.route("/openapi.json", web::get().to(openapi_json))
+        .route("/docs", web::get().to(docs))
+        .route("/docs/init.js", web::get().to(docs_init));
+}
+
+async fn openapi_json() -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("application/json")
+        .body(SPEC.as_str())
+}
+
+/// Swagger UI for the generated document.
+async fn docs() -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .insert_header((CONTENT_SECURITY_POLICY, DOCS_CSP))
+        .body(DOCS_HTML)
+}
+
+async fn docs_init() -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/javascript; charset=utf-8")
+        .body(DOCS_INIT_JS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::spec_json;
+
+    const SPEC_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/openapi.json");
+
+    #[test]
+    fn committed_spec_matches_generated() {
+        let generated = spec_json();
+
+        if std::env::var_os("UPDATE_OPENAPI").is_some() {
+            std::fs::write(SPEC_PATH, &generated).expect("failed to write openapi.json");
+            return;
+        }
+
+        let committed = std::fs::read_to_string(SPEC_PATH).unwrap_or_default();
+        assert!(
+            committed == generated,
+            "openapi.json is out of date; regenerate it with `UPDATE_OPENAPI=1 cargo test openapi`"
+        );
+    }
+}
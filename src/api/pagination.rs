@@ -0,0 +1,152 @@
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use sea_orm::prelude::DateTime;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::config::PaginationConfig;
+use crate::db::repositories::{UserKeyset, UserSortKey};
+use crate::error::AppError;
+
+/// A page of results; `offset` is omitted for cursor-based pages.
+#[derive(Serialize, ToSchema)]
+pub struct Page<T> {
+    pub data: Vec<T>,
+    pub total: u64,
+    pub limit: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u64>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CursorKey {
+    Id,
+    CreatedOn,
+}
+
+impl From<CursorKey> for UserSortKey {
+    fn from(key: CursorKey) -> Self {
+        match key {
+            CursorKey::Id => UserSortKey::Id,
+            CursorKey::CreatedOn => UserSortKey::CreatedOn,
+        }
+    }
+}
+
+/// Opaque keyset cursor handed to clients as URL-safe base64 JSON.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Cursor {
+    pub key: CursorKey,
+    pub id: i32,
+    pub created_on: DateTime,
+}
+
+impl Cursor {
+    pub fn encode(&self) -> String {
+        let json = serde_json::to_vec(self).expect("cursor serialization cannot fail");
+        URL_SAFE_NO_PAD.encode(json)
+    }
+
+    pub fn decode(raw: &str) -> Result<Self, AppError> {
+        URL_SAFE_NO_PAD
+            .decode(raw)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .ok_or_else(|| AppError::Validation("Invalid cursor".into()))
+    }
+
+    pub fn keyset(&self) -> UserKeyset {
+        UserKeyset {
+            id: self.id,
+            created_on: self.created_on,
+        }
+    }
+}
+
+pub fn resolve_limit(requested: Option<u64>, config: &PaginationConfig) -> Result<u64, AppError> {
+    match requested {
+        None => Ok(config.default_limit),
+        Some(0) => Err(AppError::Validation("limit must be greater than 0".into())),
+        Some(limit) if limit > config.max_limit => Err(AppError::Validation(format!(
+            "limit must not exceed {}",
+            config.max_limit
+        ))),
+        Some(limit) => Ok(limit),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::sqlx::types::chrono::NaiveDate;
+
+    fn cursor() -> Cursor {
+        Cursor {
+            key: CursorKey::CreatedOn,
+            id: 42,
+            created_on: NaiveDate::from_ymd_opt(2026, 10, 18)
+                .unwrap()
+                .and_hms_micro_opt(12, 30, 5, 123456)
+                .unwrap(),
+        }
+    }
+
+    #[test]
+    fn cursor_round_trips() {
+        let decoded = Cursor::decode(&cursor().encode()).unwrap();
+
+        assert_eq!(decoded.key, CursorKey::CreatedOn);
+        assert_eq!(decoded.id, 42);
+        assert_eq!(decoded.created_on, cursor().created_on);
+    }
+
+    #[test]
+    fn cursor_is_url_safe() {
+        let encoded = cursor().encode();
+
+        assert!(
+            encoded
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        );
+    }
+
+    #[test]
+    fn tampered_cursors_are_rejected() {
+        let encoded = cursor().encode();
+        let truncated = &encoded[..encoded.len() - 4];
+        let unknown_key =
+            URL_SAFE_NO_PAD.encode(r#"{"key":"email","id":1,"created_on":"2026-10-18T12:00:00"}"#);
+        let wrong_type =
+            URL_SAFE_NO_PAD.encode(r#"{"key":"id","id":"1","created_on":"2026-10-18T12:00:00"}"#);
+
+        for raw in ["", "not base64!", truncated, &unknown_key, &wrong_type] {
+            assert!(
+                matches!(Cursor::decode(raw), Err(AppError::Validation(_))),
+                "accepted {:?}",
+                raw
+            );
+        }
+    }
+
+    #[test]
+    fn limit_defaults_and_bounds() {
+        let config = PaginationConfig {
+            default_limit: 20,
+            max_limit: 100,
+        };
+
+        assert_eq!(resolve_limit(None, &config).unwrap(), 20);
+        assert_eq!(resolve_limit(Some(100), &config).unwrap(), 100);
+        assert!(matches!(
+            resolve_limit(Some(0), &config),
+            Err(AppError::Validation(_))
+        ));
+        assert!(matches!(
+            resolve_limit(Some(101), &config),
+            Err(AppError::Validation(_))
+        ));
+    }
+}
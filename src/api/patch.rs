@@ -0,0 +1,189 @@
+use actix_web::HttpRequest;
+use json_patch::Patch;
+use serde_json::Value;
+
+use crate::error::{AppError, FieldError};
+
+pub const MERGE_PATCH: &str = "application/merge-patch+json";
+pub const JSON_PATCH: &str = "application/json-patch+json";
+
+/// Applies a PATCH body to `document`, choosing RFC 7396 merge patch or
+/// RFC 6902 JSON patch by the request's content type.
+pub fn apply(req: &HttpRequest, body: &[u8], document: &mut Value) -> Result<(), AppError> {
+    let content_type = req
+        .headers()
+        .get(actix_web::http::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .map(|value| value.trim().to_ascii_lowercase())
+        .unwrap_or_default();
+
+    match content_type.as_str() {
+        MERGE_PATCH => {
+            let patch: Value = serde_json::from_slice(body)
+                .map_err(|err| AppError::Validation(format!("Invalid merge patch: {}", err)))?;
+            if !patch.is_object() {
+                return Err(AppError::Validation(
+                    "A merge patch must be a JSON object".into(),
+                ));
+            }
+            json_patch::merge(document, &patch);
+            Ok(())
+        }
+        JSON_PATCH => {
+            let patch: Patch = serde_json::from_slice(body)
+                .map_err(|err| AppError::Validation(format!("Invalid JSON patch: {}", err)))?;
+            json_patch::patch(document, &patch)
+                .map_err(|err| AppError::Validation(format!("JSON patch failed: {}", err)))
+        }
+        _ => Err(AppError::UnsupportedMediaType(format!(
+            "PATCH requires Content-Type {} or {}",
+            MERGE_PATCH, JSON_PATCH
+        ))),
+    }
+}
+
+/// Rejects patched documents that gained keys outside `allowed`.
+pub fn ensure_fields(document: &Value, allowed: &[&str]) -> Result<(), AppError> {
+    let Some(object) = document.as_object() else {
+        return Err(AppError::Validation(
+            "The patched document must be a JSON object".into(),
+        ));
+    };
+
+    match object.keys().find(|key| !allowed.contains(&key.as_str())) {
+        Some(key) => Err(AppError::Validation(format!(
+            "Field '{}' cannot be patched",
+            key
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Rejects patched documents missing one of `required`, which a merge patch
+/// removes by setting it to `null`.
+pub fn ensure_required(document: &Value, required: &[&str]) -> Result<(), AppError> {
+    let missing: Vec<FieldError> = required
+        .iter()
+        .filter(|field| document.get(field).is_none_or(Value::is_null))
+        .map(|field| FieldError {
+            field: field.to_string(),
+            code: "required".into(),
+            message: format!("Field '{}' is required", field),
+        })
+        .collect();
+
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(AppError::InvalidFields(missing))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+    use serde_json::json;
+
+    fn document() -> Value {
+        json!({ "username": "ada", "first_name": "Ada", "email": "ada@example.com" })
+    }
+
+    fn patched(content_type: &str, body: Value) -> Result<Value, AppError> {
+        let req = TestRequest::default()
+            .insert_header(("content-type", content_type))
+            .to_http_request();
+        let mut document = document();
+        apply(&req, body.to_string().as_bytes(), &mut document)?;
+        Ok(document)
+    }
+
+    #[test]
+    fn merge_patch_keeps_absent_fields_removes_nulls_and_sets_values() {
+        let document = patched(
+            MERGE_PATCH,
+            json!({ "first_name": null, "email": "lovelace@example.com" }),
+        )
+        .unwrap();
+
+        assert_eq!(
+            document,
+            json!({ "username": "ada", "email": "lovelace@example.com" })
+        );
+    }
+
+    #[test]
+    fn json_patch_applies_operations_in_order() {
+        let document = patched(
+            "application/json-patch+json; charset=utf-8",
+            json!([
+                { "op": "test", "path": "/username", "value": "ada" },
+                { "op": "replace", "path": "/username", "value": "lovelace" },
+                { "op": "remove", "path": "/first_name" },
+                { "op": "add", "path": "/phone", "value": "+44 20 7946 0000" },
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(
+            document,
+            json!({ "username": "lovelace", "email": "ada@example.com", "phone": "+44 20 7946 0000" })
+        );
+    }
+
+    #[test]
+    fn failing_test_operation_rejects_the_patch() {
+        let err = patched(
+            JSON_PATCH,
+            json!([
+                { "op": "test", "path": "/username", "value": "grace" },
+                { "op": "replace", "path": "/username", "value": "lovelace" },
+            ]),
+        )
+        .unwrap_err();
+
+        assert!(
+            matches!(err, AppError::Validation(message) if message.starts_with("JSON patch failed"))
+        );
+    }
+
+    #[test]
+    fn other_content_types_are_unsupported() {
+        for content_type in ["application/json", "text/plain", ""] {
+            let err = patched(content_type, json!({})).unwrap_err();
+
+            assert!(
+                matches!(err, AppError::UnsupportedMediaType(_)),
+                "{}",
+                content_type
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_fields_are_rejected() {
+        let document = patched(MERGE_PATCH, json!({ "role": "admin" })).unwrap();
+
+        let err = ensure_fields(&document, &["username", "first_name", "email"]).unwrap_err();
+
+        assert!(matches!(err, AppError::Validation(message) if message.contains("'role'")));
+    }
+
+    #[test]
+    fn removed_required_fields_are_reported_per_field() {
+        let document = patched(MERGE_PATCH, json!({ "username": null, "email": null })).unwrap();
+
+        let Err(AppError::InvalidFields(errors)) =
+            ensure_required(&document, &["username", "email"])
+        else {
+            panic!("expected field errors");
+        };
+
+        let fields: Vec<_> = errors
+            .iter()
+            .map(|e| (e.field.as_str(), e.code.as_str()))
+            .collect();
+        assert_eq!(fields, [("username", "required"), ("email", "required")]);
+    }
+}
@@ -0,0 +1,148 @@
+use actix_web::http::header::{ETag, EntityTag, IfMatch, IfNoneMatch};
+use actix_web::{HttpResponse, web};
+
+use crate::db::models::UserModel;
+use crate::error::AppError;
+
+/// Strong entity tag derived from the row version.
+pub fn user_etag(user: &UserModel) -> ETag {
+    ETag(EntityTag::new_strong(user.version.to_string()))
+}
+
+/// Checks `If-Match` against the current version of `user`. When `required`
+/// is set, a missing header is rejected with 428 instead of being ignored.
+pub fn check_if_match(
+    if_match: Option<&web::Header<IfMatch>>,
+    user: &UserModel,
+    required: bool,
+) -> Result<(), AppError> {
+    let current = user_etag(user).0;
+
+    // actix parses an absent header as an empty list.
+    let if_match = if_match
+        .map(|header| &header.0)
+        .filter(|header| !matches!(header, IfMatch::Items(tags) if tags.is_empty()));
+
+    match if_match {
+        None if required => Err(AppError::PreconditionRequired(
+            "This request requires an If-Match header with the user's ETag".into(),
+        )),
+        None | Some(IfMatch::Any) => Ok(()),
+        Some(IfMatch::Items(tags)) if tags.iter().any(|tag| tag.strong_eq(&current)) => Ok(()),
+        Some(IfMatch::Items(_)) => Err(AppError::PreconditionFailed(format!(
+            "User with ID {} has been modified; current ETag is {}",
+            user.id, current
+        ))),
+    }
+}
+
+/// Returns a `304 Not Modified` response when `If-None-Match` matches `user`.
+pub fn not_modified(
+    if_none_match: Option<&web::Header<IfNoneMatch>>,
+    user: &UserModel,
+) -> Option<HttpResponse> {
+    let current = user_etag(user);
+
+    let matches = match if_none_match.map(|header| &header.0) {
+        Some(IfNoneMatch::Any) => true,
+        Some(IfNoneMatch::Items(tags)) => tags.iter().any(|tag| tag.weak_eq(&current.0)),
+        None => false,
+    };
+
+    matches.then(|| HttpResponse::NotModified().insert_header(current).finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::StatusCode;
+    use actix_web::http::header::ETAG;
+    use sea_orm::sqlx::types::chrono::NaiveDate;
+
+    fn user(version: i32) -> UserModel {
+        let now = NaiveDate::from_ymd_opt(2026, 10, 18)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+        UserModel {
+            id: 7,
+            username: "jdoe".into(),
+            first_name: None,
+            last_name: None,
+            email: "jdoe@example.com".into(),
+            phone: None,
+            created_on: now,
+            updated_on: now,
+            deleted_on: None,
+            version,
+        }
+    }
+
+    fn if_match(tags: &[EntityTag]) -> web::Header<IfMatch> {
+        web::Header(IfMatch::Items(tags.to_vec()))
+    }
+
+    fn if_none_match(tags: &[EntityTag]) -> web::Header<IfNoneMatch> {
+        web::Header(IfNoneMatch::Items(tags.to_vec()))
+    }
+
+    #[test]
+    fn if_match_accepts_current_version() {
+        let header = if_match(&[
+            EntityTag::new_strong("1".into()),
+            EntityTag::new_strong("3".into()),
+        ]);
+
+        assert!(check_if_match(Some(&header), &user(3), true).is_ok());
+        assert!(check_if_match(Some(&web::Header(IfMatch::Any)), &user(3), true).is_ok());
+    }
+
+    #[test]
+    fn if_match_rejects_stale_and_weak_tags() {
+        for tag in [
+            EntityTag::new_strong("2".into()),
+            EntityTag::new_weak("3".into()),
+        ] {
+            assert!(matches!(
+                check_if_match(Some(&if_match(&[tag])), &user(3), false),
+                Err(AppError::PreconditionFailed(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn missing_if_match_is_only_rejected_when_required() {
+        let empty = if_match(&[]);
+
+        assert!(check_if_match(None, &user(3), false).is_ok());
+        assert!(check_if_match(Some(&empty), &user(3), false).is_ok());
+        for header in [None, Some(&empty)] {
+            assert!(matches!(
+                check_if_match(header, &user(3), true),
+                Err(AppError::PreconditionRequired(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn not_modified_when_a_tag_matches() {
+        for header in [
+            if_none_match(&[EntityTag::new_strong("3".into())]),
+            if_none_match(&[EntityTag::new_weak("3".into())]),
+            web::Header(IfNoneMatch::Any),
+        ] {
+            let res = not_modified(Some(&header), &user(3)).expect("expected 304");
+
+            assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+            assert_eq!(res.headers().get(ETAG).unwrap(), "\"3\"");
+        }
+    }
+
+    #[test]
+    fn modified_when_no_tag_matches() {
+        let stale = if_none_match(&[EntityTag::new_strong("2".into())]);
+
+        assert!(not_modified(Some(&stale), &user(3)).is_none());
+        assert!(not_modified(None, &user(3)).is_none());
+    }
+}
@@ -0,0 +1,147 @@
+use actix_web::{HttpResponse, web};
+use log::info;
+use sea_orm::DbConn;
+use sea_orm::sqlx::types::chrono::Local;
+use serde::Serialize;
+use std::sync::Arc;
+
+use crate::auth::{Principal, RequirePermission, permissions};
+use crate::db::models::RoleModel;
+use crate::db::repositories::{RoleRepository, UserRepository};
+use crate::error::AppError;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("/roles").route(
+            web::get()
+                .to(get_roles)
+                .wrap(RequirePermission(permissions::ROLES_ASSIGN)),
+        ),
+    );
+}
+
+/// Role assignment routes, nested in the `/users` scope.
+pub fn configure_user_roles(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("/{id}/roles").get(get_user_roles))
+        .service(
+            web::resource("/{id}/roles/{role}")
+                .route(
+                    web::put()
+                        .to(assign_role)
+                        .wrap(RequirePermission(permissions::ROLES_ASSIGN)),
+                )
+                .route(
+                    web::delete()
+                        .to(revoke_role)
+                        .wrap(RequirePermission(permissions::ROLES_ASSIGN)),
+                ),
+        );
+}
+
+#[derive(Serialize)]
+pub struct RoleResponse {
+    #[serde(flatten)]
+    pub role: RoleModel,
+    pub permissions: Vec<String>,
+}
+
+pub async fn get_roles(db: web::Data<DbConn>) -> Result<HttpResponse, AppError> {
+    let repo = RoleRepository::new(Arc::new(db.get_ref().clone()));
+
+    let roles: Vec<RoleResponse> = repo
+        .find_all_with_permissions()
+        .await?
+        .into_iter()
+        .map(|role| RoleResponse {
+            role: role.role,
+            permissions: role.permissions,
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(roles))
+}
+
+pub async fn get_user_roles(
+    principal: Principal,
+    db: web::Data<DbConn>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = path.into_inner();
+    principal.ensure_self_or(user_id, permissions::ROLES_ASSIGN)?;
+
+    let roles = RoleRepository::new(Arc::new(db.get_ref().clone()))
+        .find_for_user(user_id)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(roles))
+}
+
+pub async fn assign_role(
+    principal: Principal,
+    db: web::Data<DbConn>,
+    path: web::Path<(i32, String)>,
+) -> Result<HttpResponse, AppError> {
+    let (user_id, role_name) = path.into_inner();
+    let db = Arc::new(db.get_ref().clone());
+    let repo = RoleRepository::new(db.clone());
+
+    let role = find_role(&repo, &role_name).await?;
+    ensure_user_exists(&UserRepository::new(db), user_id).await?;
+
+    let assigned = repo
+        .assign(user_id, role.id, Local::now().naive_local())
+        .await?;
+
+    if assigned {
+        info!(
+            "User {} assigned role '{}' to user with ID {}",
+            principal.user_id, role.name, user_id
+        );
+        Ok(HttpResponse::Created().json(role))
+    } else {
+        Ok(HttpResponse::Ok().json(role))
+    }
+}
+
+pub async fn revoke_role(
+    principal: Principal,
+    db: web::Data<DbConn>,
+    path: web::Path<(i32, String)>,
+) -> Result<HttpResponse, AppError> {
+    let (user_id, role_name) = path.into_inner();
+    let repo = RoleRepository::new(Arc::new(db.get_ref().clone()));
+
+    let role = find_role(&repo, &role_name).await?;
+
+    if principal.user_id == user_id && role.name == permissions::ADMIN_ROLE {
+        return Err(AppError::Validation(
+            "Administrators cannot revoke their own admin role".into(),
+        ));
+    }
+
+    if !repo.revoke(user_id, role.id).await? {
+        return Err(AppError::NotFound(format!(
+            "User with ID {} does not have role '{}'",
+            user_id, role.name
+        )));
+    }
+
+    info!(
+        "User {} revoked role '{}' from user with ID {}",
+        principal.user_id, role.name, user_id
+    );
+    Ok(HttpResponse::NoContent().finish())
+}
+
+async fn find_role(repo: &RoleRepository, name: &str) -> Result<RoleModel, AppError> {
+    repo.find_by_name(name)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Role '{}' not found", name)))
+}
+
+async fn ensure_user_exists(repo: &UserRepository, user_id: i32) -> Result<(), AppError> {
+    match repo.find_by_id(user_id).await? {
+        Some(_) => Ok(()),
+        None => Err(AppError::UserNotFound(user_id)),
+    }
+}
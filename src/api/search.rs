@@ -0,0 +1,236 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use utoipa::ToSchema;
+
+use crate::db::models::UserModel;
+
+const MARK_START: &str = "<mark>";
+const MARK_END: &str = "</mark>";
+
+/// Splits free text into lowercase alphanumeric search terms.
+pub fn search_terms(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Builds a prefix `tsquery` (`john:* & smi:*`) from sanitized terms.
+pub fn prefix_ts_query(terms: &[String]) -> String {
+    terms
+        .iter()
+        .map(|term| format!("{}:*", term))
+        .collect::<Vec<_>>()
+        .join(" & ")
+}
+
+pub fn phone_digits(text: &str) -> String {
+    let digits: String = text.chars().filter(char::is_ascii_digit).collect();
+    if digits.len() >= 3 {
+        digits
+    } else {
+        String::new()
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SearchHit {
+    pub user: UserModel,
+    pub rank: f64,
+    /// Matched fields, HTML-escaped, with the terms wrapped in `<mark>`.
+    #[schema(value_type = BTreeMap<String, String>)]
+    pub highlights: BTreeMap<&'static str, String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SearchResults {
+    pub data: Vec<SearchHit>,
+}
+
+pub fn highlights(user: &UserModel, terms: &[String]) -> BTreeMap<&'static str, String> {
+    [
+        ("username", Some(&user.username)),
+        ("first_name", user.first_name.as_ref()),
+        ("last_name", user.last_name.as_ref()),
+        ("email", Some(&user.email)),
+    ]
+    .into_iter()
+    .filter_map(|(field, value)| Some((field, highlight(value?, terms)?)))
+    .collect()
+}
+
+/// Wraps every case-insensitive occurrence of a term in `<mark>` tags and
+/// HTML-escapes the rest, since the fields are user input. Returns `None`
+/// when nothing matched, e.g. for typo-tolerant hits.
+fn highlight(text: &str, terms: &[String]) -> Option<String> {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for term in terms {
+        for (start, _) in text.char_indices() {
+            if let Some(len) = match_len(&text[start..], term) {
+                ranges.push((start, start + len));
+            }
+        }
+    }
+
+    if ranges.is_empty() {
+        return None;
+    }
+
+    ranges.sort_unstable();
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+
+    let mut result = String::with_capacity(text.len() + merged.len() * 13);
+    let mut cursor = 0;
+    for (start, end) in merged {
+        escape_html(&text[cursor..start], &mut result);
+        result.push_str(MARK_START);
+        escape_html(&text[start..end], &mut result);
+        result.push_str(MARK_END);
+        cursor = end;
+    }
+    escape_html(&text[cursor..], &mut result);
+
+    Some(result)
+}
+
+/// Byte length of the prefix of `text` that lowercases to `term`, which
+/// must already be lowercase. Lowercasing the whole text instead could
+/// shift byte offsets, as some characters change length.
+fn match_len(text: &str, term: &str) -> Option<usize> {
+    let mut expected = term.chars();
+    for (index, c) in text.char_indices() {
+        for lower in c.to_lowercase() {
+            if expected.next() != Some(lower) {
+                return None;
+            }
+        }
+        if expected.as_str().is_empty() {
+            return Some(index + c.len_utf8());
+        }
+    }
+    None
+}
+
+fn escape_html(text: &str, out: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user() -> UserModel {
+        let now = sea_orm::sqlx::types::chrono::Local::now().naive_local();
+        UserModel {
+            id: 1,
+            username: "jsmith".into(),
+            first_name: Some("John".into()),
+            last_name: Some("Smith".into()),
+            email: "john.smith@example.com".into(),
+            phone: None,
+            created_on: now,
+            updated_on: now,
+            deleted_on: None,
+            version: 1,
+        }
+    }
+
+    #[test]
+    fn splits_and_lowercases_terms() {
+        assert_eq!(
+            search_terms("  John O'Smith-Ray "),
+            ["john", "o", "smith", "ray"]
+        );
+        assert!(search_terms("&|!:*").is_empty());
+    }
+
+    #[test]
+    fn builds_prefix_query() {
+        assert_eq!(prefix_ts_query(&search_terms("John smi")), "john:* & smi:*");
+    }
+
+    #[test]
+    fn phone_digits_need_three() {
+        assert_eq!(phone_digits("+1 (555) 01"), "155501");
+        assert_eq!(phone_digits("a12"), "");
+    }
+
+    #[test]
+    fn highlights_case_insensitively() {
+        assert_eq!(
+            highlight("John Johnson", &search_terms("john")).as_deref(),
+            Some("<mark>John</mark> <mark>John</mark>son")
+        );
+    }
+
+    #[test]
+    fn merges_overlapping_matches() {
+        assert_eq!(
+            highlight("smithson", &search_terms("smith ithso")).as_deref(),
+            Some("<mark>smithso</mark>n")
+        );
+    }
+
+    #[test]
+    fn no_match_is_none() {
+        assert_eq!(highlight("Smyth", &search_terms("smith")), None);
+    }
+
+    #[test]
+    fn handles_multibyte_text() {
+        assert_eq!(
+            highlight("Zoë Éclair", &search_terms("clair")).as_deref(),
+            Some("Zoë É<mark>clair</mark>")
+        );
+    }
+
+    #[test]
+    fn highlights_only_matching_fields() {
+        let highlights = highlights(&user(), &search_terms("smith"));
+
+        assert_eq!(highlights.len(), 3);
+        assert_eq!(highlights["username"], "j<mark>smith</mark>");
+        assert_eq!(highlights["last_name"], "<mark>Smith</mark>");
+        assert_eq!(highlights["email"], "john.<mark>smith</mark>@example.com");
+        assert!(!highlights.contains_key("first_name"));
+    }
+
+    #[test]
+    fn highlights_non_ascii_case_variants() {
+        assert_eq!(
+            highlight("ÉMILE Straße", &search_terms("émile STRASSE straße")).as_deref(),
+            Some("<mark>ÉMILE</mark> <mark>Straße</mark>")
+        );
+    }
+
+    #[test]
+    fn escapes_user_controlled_text() {
+        assert_eq!(
+            highlight(
+                r#"<img src=x onerror="alert('x')">&bob"#,
+                &search_terms("bob")
+            )
+            .as_deref(),
+            Some("&lt;img src=x onerror=&quot;alert(&#39;x&#39;)&quot;&gt;&amp;<mark>bob</mark>")
+        );
+        assert_eq!(
+            highlight("<b>script</b>", &search_terms("b")).as_deref(),
+            Some("&lt;<mark>b</mark>&gt;script&lt;/<mark>b</mark>&gt;")
+        );
+    }
+}
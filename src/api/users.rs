@@ -1,112 +1,480 @@
-use actix_web::{HttpResponse, web};
+use actix_web::http::header::{IfMatch, IfNoneMatch};
+use actix_web::{HttpRequest, HttpResponse, web};
 use log::{info, warn};
 use sea_orm::DbConn;
 use sea_orm::sqlx::types::chrono::Local;
 use serde::{Deserialize, Serialize};
+use std::fmt;
 use std::sync::Arc;
-
-use crate::db::models::UserActiveModel;
-use crate::db::repositories::UserRepository;
-use crate::error::AppError;
+use utoipa::{IntoParams, ToSchema};
+
+use super::audit;
+use super::filtering::{parse_filters, parse_sort};
+use super::pagination::{Cursor, CursorKey, Page, resolve_limit};
+use super::patch;
+use super::preconditions::{check_if_match, not_modified, user_etag};
+use super::roles;
+use super::search::{
+    SearchHit, SearchResults, highlights, phone_digits, prefix_ts_query, search_terms,
+};
+use crate::audit::AuditContext;
+use crate::auth::{Principal, RequirePermission, password, permissions};
+use crate::config::AppConfig;
+use crate::db::models::{UserActiveModel, UserColumn, UserModel};
+use crate::db::repositories::{UserPageQuery, UserRepository, UserSearchQuery};
+use crate::domain::User;
+use crate::error::{AppError, ErrorResponse};
+use crate::redaction::Redacted;
 use sea_orm::ActiveValue::Set;
+use validator::{ValidationError, ValidationErrors};
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/users")
-            .service(web::resource("").get(get_users).post(create_user))
+            .service(
+                web::resource("")
+                    .route(
+                        web::get()
+                            .to(get_users)
+                            .wrap(RequirePermission(permissions::USERS_READ)),
+                    )
+                    .post(create_user),
+            )
+            .service(
+                web::resource("/search").route(
+                    web::get()
+                        .to(search_users)
+                        .wrap(RequirePermission(permissions::USERS_READ)),
+                ),
+            )
             .service(
                 web::resource("/{id}")
                     .get(get_user)
-                    .put(update_user)
-                    .delete(delete_user_physical),
+                    .put(replace_user)
+                    .patch(patch_user)
+                    .route(
+                        web::delete()
+                            .to(delete_user_physical)
+                            .wrap(RequirePermission(permissions::USERS_DELETE)),
+                    ),
+            )
+            .service(
+                web::resource("/{id}/soft-delete").route(
+                    web::patch()
+                        .to(delete_user_logical)
+                        .wrap(RequirePermission(permissions::USERS_DELETE)),
+                ),
             )
-            .service(web::resource("/{id}/soft-delete").patch(delete_user_logical))
-            .service(web::resource("/{id}/restore").patch(restore_user)),
+            .service(
+                web::resource("/{id}/restore").route(
+                    web::patch()
+                        .to(restore_user)
+                        .wrap(RequirePermission(permissions::USERS_RESTORE)),
+                ),
+            )
+            .configure(roles::configure_user_roles)
+            .configure(audit::configure_user_audit),
     );
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct CreateUserRequest {
     pub username: String,
     pub first_name: Option<String>,
     pub last_name: Option<String>,
     pub email: String,
     pub phone: Option<String>,
+    /// Optional initial password enabling `POST /api/auth/login`.
+    #[serde(default)]
+    #[schema(value_type = Option<String>, write_only, min_length = 8)]
+    pub password: Option<Redacted<String>>,
+}
+
+impl fmt::Debug for CreateUserRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CreateUserRequest")
+            .field("username", &Redacted::identifier(&self.username))
+            .field("first_name", &self.first_name.as_ref().map(Redacted::name))
+            .field("last_name", &self.last_name.as_ref().map(Redacted::name))
+            .field("email", &Redacted::email(&self.email))
+            .field("phone", &self.phone.as_ref().map(Redacted::phone))
+            .field("password", &self.password)
+            .finish()
+    }
 }
 
-#[derive(Deserialize, Serialize)]
-pub struct UpdateUserRequest {
-    pub username: Option<String>,
+/// Full representation of the editable user fields: the body of `PUT` and
+/// the document a `PATCH` is applied to. Absent optional fields are cleared.
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct ReplaceUserRequest {
+    pub username: String,
     pub first_name: Option<String>,
     pub last_name: Option<String>,
-    pub email: Option<String>,
+    pub email: String,
     pub phone: Option<String>,
 }
 
-#[derive(Deserialize)]
+const EDITABLE_FIELDS: &[&str] = &["username", "first_name", "last_name", "email", "phone"];
+const REQUIRED_FIELDS: &[&str] = &["username", "email"];
+
+impl CreateUserRequest {
+    fn to_domain(&self) -> User {
+        User {
+            id: None,
+            username: self.username.clone(),
+            first_name: self.first_name.clone(),
+            last_name: self.last_name.clone(),
+            email: self.email.clone(),
+            phone: self.phone.clone(),
+        }
+    }
+
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = self.to_domain().validate_all().err().unwrap_or_default();
+
+        if let Some(password) = &self.password
+            && password.expose().chars().count() < password::MIN_PASSWORD_LENGTH
+        {
+            errors.add(
+                "password",
+                ValidationError::new("length").with_message(
+                    format!(
+                        "Password must be at least {} characters",
+                        password::MIN_PASSWORD_LENGTH
+                    )
+                    .into(),
+                ),
+            );
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl ReplaceUserRequest {
+    fn from_model(user: &UserModel) -> Self {
+        Self {
+            username: user.username.clone(),
+            first_name: user.first_name.clone(),
+            last_name: user.last_name.clone(),
+            email: user.email.clone(),
+            phone: user.phone.clone(),
+        }
+    }
+
+    fn to_domain(&self, id: i32) -> User {
+        User {
+            id: Some(id),
+            username: self.username.clone(),
+            first_name: self.first_name.clone(),
+            last_name: self.last_name.clone(),
+            email: self.email.clone(),
+            phone: self.phone.clone(),
+        }
+    }
+
+    /// Validates the replacement but only reports fields that change, so
+    /// legacy data echoed back unchanged does not block updates.
+    fn validate(&self, user: &UserModel) -> Result<(), ValidationErrors> {
+        let Err(mut errors) = self.to_domain(user.id).validate_all() else {
+            return Ok(());
+        };
+
+        let changed = [
+            ("username", self.username != user.username),
+            ("first_name", self.first_name != user.first_name),
+            ("last_name", self.last_name != user.last_name),
+            ("email", self.email != user.email),
+            ("phone", self.phone != user.phone),
+        ];
+        errors.errors_mut().retain(|field, _| {
+            changed
+                .iter()
+                .any(|(name, changed)| *changed && field.as_ref() == *name)
+        });
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// Besides these, `filter[field][op]=value` parameters filter the list;
+/// see the README for the supported fields and operators.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct GetUsersParams {
+    /// Include logically deleted users.
     include_deleted: Option<bool>,
+    limit: Option<u64>,
+    /// Offset pagination; cannot be combined with `cursor`.
+    offset: Option<u64>,
+    /// `next_cursor` of the previous page.
+    cursor: Option<String>,
+    /// Keyset order for cursor pagination; defaults to `id`.
+    order_by: Option<CursorKey>,
+    /// Comma-separated fields, `-` prefix for descending, e.g. `-created_on,id`.
+    sort: Option<String>,
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/users",
+    tag = "users",
+    params(GetUsersParams),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "A page of users", body = Page<UserModel>),
+        (status = 400, description = "Invalid query", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 403, description = "Missing `users:read`", body = ErrorResponse, content_type = "application/problem+json"),
+    )
+)]
 pub async fn get_users(
     db: web::Data<DbConn>,
+    config: web::Data<AppConfig>,
     query: web::Query<GetUsersParams>,
+    raw_query: web::Query<Vec<(String, String)>>,
 ) -> Result<HttpResponse, AppError> {
+    let filters = parse_filters(&raw_query)?;
+    let sort = match &query.sort {
+        Some(raw) => parse_sort(raw)?,
+        None => Vec::new(),
+    };
+
+    // Soft-deleted rows are only listed when the caller opts in, so a
+    // deleted_on filter alone must not reveal them.
     let include_deleted = query.include_deleted.unwrap_or(false);
+    if !include_deleted
+        && filters
+            .iter()
+            .any(|filter| matches!(filter.column, UserColumn::DeletedOn))
+    {
+        return Err(AppError::Validation(
+            "Filtering on deleted_on requires include_deleted=true".into(),
+        ));
+    }
+    let limit = resolve_limit(query.limit, &config.pagination)?;
     let repo = UserRepository::new(Arc::new(db.get_ref().clone()));
 
-    let users = repo.find_all(include_deleted).await?;
+    let custom_sort = !sort.is_empty();
+    if custom_sort && (query.cursor.is_some() || query.order_by.is_some()) {
+        return Err(AppError::Validation(
+            "sort cannot be combined with cursor or order_by".into(),
+        ));
+    }
+
+    let cursor = match &query.cursor {
+        Some(raw) => {
+            if query.offset.is_some() {
+                return Err(AppError::Validation(
+                    "cursor and offset cannot be combined".into(),
+                ));
+            }
+            Some(Cursor::decode(raw)?)
+        }
+        None => None,
+    };
+
+    let key = match (&cursor, query.order_by) {
+        (Some(cursor), Some(order_by)) if cursor.key != order_by => {
+            return Err(AppError::Validation(
+                "order_by does not match the cursor".into(),
+            ));
+        }
+        (Some(cursor), _) => cursor.key,
+        (None, order_by) => order_by.unwrap_or(CursorKey::Id),
+    };
+
+    let page = repo
+        .find_page(&UserPageQuery {
+            include_deleted,
+            filters,
+            sort,
+            sort_key: key.into(),
+            limit,
+            offset: query.offset.unwrap_or(0),
+            after: cursor.as_ref().map(Cursor::keyset),
+        })
+        .await?;
+
+    let next_cursor = match page.users.last() {
+        Some(last) if page.has_more && !custom_sort => Some(
+            Cursor {
+                key,
+                id: last.id,
+                created_on: last.created_on,
+            }
+            .encode(),
+        ),
+        _ => None,
+    };
+
+    Ok(HttpResponse::Ok().json(Page {
+        data: page.users,
+        total: page.total,
+        limit,
+        offset: cursor.is_none().then(|| query.offset.unwrap_or(0)),
+        next_cursor,
+    }))
+}
 
-    Ok(HttpResponse::Ok().json(users))
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SearchUsersParams {
+    /// Free text matched by prefix against names, username, email and phone.
+    q: String,
+    limit: Option<u64>,
+    include_deleted: Option<bool>,
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/users/search",
+    tag = "users",
+    params(SearchUsersParams),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Matches ordered by relevance", body = SearchResults),
+        (status = 400, description = "Invalid query", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 403, description = "Missing `users:read`", body = ErrorResponse, content_type = "application/problem+json"),
+    )
+)]
+pub async fn search_users(
+    db: web::Data<DbConn>,
+    config: web::Data<AppConfig>,
+    query: web::Query<SearchUsersParams>,
+) -> Result<HttpResponse, AppError> {
+    let terms = search_terms(&query.q);
+    if terms.is_empty() {
+        return Err(AppError::Validation(
+            "Search query must contain at least one letter or digit".into(),
+        ));
+    }
+
+    let limit = resolve_limit(query.limit, &config.pagination)?;
+    let repo = UserRepository::new(Arc::new(db.get_ref().clone()));
+
+    let hits = repo
+        .search(&UserSearchQuery {
+            text: query.q.trim().to_string(),
+            ts_query: prefix_ts_query(&terms),
+            phone_digits: phone_digits(&query.q),
+            include_deleted: query.include_deleted.unwrap_or(false),
+            limit,
+        })
+        .await?;
+
+    let data: Vec<SearchHit> = hits
+        .into_iter()
+        .map(|hit| SearchHit {
+            highlights: highlights(&hit.user, &terms),
+            user: hit.user,
+            rank: hit.rank,
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(SearchResults { data }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/users/{id}",
+    tag = "users",
+    params(
+        ("id" = i32, Path, description = "User id"),
+        ("If-None-Match" = Option<String>, Header, description = "ETag of a cached copy"),
+    ),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "The user", body = UserModel, headers(("ETag" = String, description = "Current version"))),
+        (status = 304, description = "The cached copy is current"),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 403, description = "Another user without `users:read`", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 404, description = "User not found", body = ErrorResponse, content_type = "application/problem+json"),
+    )
+)]
 pub async fn get_user(
+    principal: Principal,
     db: web::Data<DbConn>,
     path: web::Path<i32>,
+    if_none_match: Option<web::Header<IfNoneMatch>>,
 ) -> Result<HttpResponse, AppError> {
     let user_id = path.into_inner();
+    principal.ensure_self_or(user_id, permissions::USERS_READ)?;
     let repo = UserRepository::new(Arc::new(db.get_ref().clone()));
 
     let user = repo.find_by_id(user_id).await?;
 
     match user {
-        Some(user) => Ok(HttpResponse::Ok().json(user)),
-        None => Err(AppError::NotFound(format!(
-            "User with ID {} not found",
-            user_id
-        ))),
+        Some(user) => Ok(
+            not_modified(if_none_match.as_ref(), &user).unwrap_or_else(|| {
+                HttpResponse::Ok()
+                    .insert_header(user_etag(&user))
+                    .json(user)
+            }),
+        ),
+        None => Err(AppError::UserNotFound(user_id)),
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/users",
+    tag = "users",
+    request_body = CreateUserRequest,
+    responses(
+        (status = 201, description = "User created", body = UserModel, headers(("ETag" = String, description = "Current version"))),
+        (status = 400, description = "Malformed body", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 409, description = "Username or email taken", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid fields", body = ErrorResponse, content_type = "application/problem+json"),
+    )
+)]
 pub async fn create_user(
     db: web::Data<DbConn>,
+    config: web::Data<AppConfig>,
+    audit: AuditContext,
     item: web::Json<CreateUserRequest>,
 ) -> Result<HttpResponse, AppError> {
-    info!("Attempting to create user with username: {}", item.username);
+    info!(
+        "Attempting to create user with username: {}",
+        Redacted::identifier(&item.username)
+    );
     let repo = UserRepository::new(Arc::new(db.get_ref().clone()));
 
-    if item.username.trim().is_empty() {
-        return Err(AppError::Validation("Username cannot be empty".into()));
-    }
+    item.validate()?;
 
-    if item.email.trim().is_empty() {
-        return Err(AppError::Validation("Email cannot be empty".into()));
+    // Reserved even before the bootstrap creates it, so nobody can claim it.
+    if config
+        .auth
+        .bootstrap_admin
+        .as_deref()
+        .is_some_and(|reserved| reserved.eq_ignore_ascii_case(&item.username))
+    {
+        return Err(AppError::already_exists("username", &item.username));
     }
 
-    if let Some(_) = repo.find_by_username(&item.username).await? {
-        return Err(AppError::Validation(format!(
-            "Username {} already exists",
-            item.username
-        )));
+    if repo.find_by_username(&item.username).await?.is_some() {
+        return Err(AppError::already_exists("username", &item.username));
     }
 
-    if let Some(_) = repo.find_by_email(&item.email).await? {
-        return Err(AppError::Validation(format!(
-            "Email {} already exists",
-            item.email
-        )));
+    if repo.find_by_email(&item.email).await?.is_some() {
+        return Err(AppError::already_exists("email", &item.email));
     }
 
+    // Hashed only once the request can succeed; the KDF is deliberately slow.
+    let password_hash = match &item.password {
+        Some(password) => Some(password::hash_password(password.expose()).await?),
+        None => None,
+    };
+
     let now = Local::now().naive_local();
     let user_model = UserActiveModel {
         username: Set(item.username.clone()),
@@ -119,106 +487,196 @@ pub async fn create_user(
         ..Default::default()
     };
 
-    let user = repo.create(user_model).await?;
+    let user = repo.create(user_model, password_hash, &audit).await?;
 
     info!("User created with ID: {}", user.id);
-    Ok(HttpResponse::Created().json(user))
+    Ok(HttpResponse::Created()
+        .insert_header(user_etag(&user))
+        .json(user))
 }
 
-pub async fn update_user(
+#[utoipa::path(
+    put,
+    path = "/api/users/{id}",
+    tag = "users",
+    params(
+        ("id" = i32, Path, description = "User id"),
+        ("If-Match" = String, Header, description = "ETag the replacement is based on"),
+    ),
+    request_body = ReplaceUserRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "User replaced", body = UserModel, headers(("ETag" = String, description = "New version"))),
+        (status = 400, description = "Malformed body", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 403, description = "Another user without `users:update`", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 404, description = "User not found", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 409, description = "Username or email taken", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 412, description = "The user changed since the `If-Match` version", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid fields", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 428, description = "`If-Match` is missing", body = ErrorResponse, content_type = "application/problem+json"),
+    )
+)]
+pub async fn replace_user(
+    principal: Principal,
+    audit: AuditContext,
     db: web::Data<DbConn>,
     path: web::Path<i32>,
-    item: web::Json<UpdateUserRequest>,
+    item: web::Json<ReplaceUserRequest>,
+    if_match: Option<web::Header<IfMatch>>,
 ) -> Result<HttpResponse, AppError> {
     let user_id = path.into_inner();
+    principal.ensure_self_or(user_id, permissions::USERS_UPDATE)?;
     let repo = UserRepository::new(Arc::new(db.get_ref().clone()));
 
-    info!("Attempting to update user with ID: {}", user_id);
-
-    if let Some(ref username) = item.username {
-        if username.trim().is_empty() {
-            return Err(AppError::Validation("Username cannot be empty".into()));
-        }
-
-        if let Some(existing_user) = repo.find_by_username(username).await? {
-            if existing_user.id != user_id {
-                return Err(AppError::Validation(format!(
-                    "Username {} already exists",
-                    username
-                )));
-            }
-        }
-    }
+    info!(
+        "User {} attempting to replace user with ID: {}",
+        principal.user_id, user_id
+    );
 
-    if let Some(ref email) = item.email {
-        if email.trim().is_empty() {
-            return Err(AppError::Validation("Email cannot be empty".into()));
-        }
+    let user = repo
+        .find_by_id(user_id)
+        .await?
+        .ok_or(AppError::UserNotFound(user_id))?;
+    check_if_match(if_match.as_ref(), &user, true)?;
 
-        if let Some(existing_user) = repo.find_by_email(email).await? {
-            if existing_user.id != user_id {
-                return Err(AppError::Validation(format!(
-                    "Email {} already exists",
-                    email
-                )));
-            }
-        }
-    }
+    apply_replacement(&repo, &audit, user, item.into_inner()).await
+}
 
-    let user = repo.find_by_id(user_id).await?;
+#[utoipa::path(
+    patch,
+    path = "/api/users/{id}",
+    tag = "users",
+    params(
+        ("id" = i32, Path, description = "User id"),
+        ("If-Match" = String, Header, description = "ETag the patch is based on"),
+    ),
+    request_body(
+        description = "A JSON merge patch over the editable fields, where `null` clears a field, or a JSON Patch",
+        content(
+            (ReplaceUserRequest = "application/merge-patch+json"),
+            (json_patch::Patch = "application/json-patch+json"),
+        )
+    ),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "User patched", body = UserModel, headers(("ETag" = String, description = "New version"))),
+        (status = 400, description = "Malformed patch or failed `test` operation", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 403, description = "Another user without `users:update`", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 404, description = "User not found", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 409, description = "Username or email taken", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 412, description = "The user changed since the `If-Match` version", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 415, description = "Not a merge patch or JSON Patch", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid fields", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 428, description = "`If-Match` is missing", body = ErrorResponse, content_type = "application/problem+json"),
+    )
+)]
+pub async fn patch_user(
+    principal: Principal,
+    audit: AuditContext,
+    db: web::Data<DbConn>,
+    path: web::Path<i32>,
+    req: HttpRequest,
+    body: web::Bytes,
+    if_match: Option<web::Header<IfMatch>>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = path.into_inner();
+    principal.ensure_self_or(user_id, permissions::USERS_UPDATE)?;
+    let repo = UserRepository::new(Arc::new(db.get_ref().clone()));
 
-    match user {
-        Some(user) => {
-            let mut active_model: UserActiveModel = user.into();
+    info!(
+        "User {} attempting to patch user with ID: {}",
+        principal.user_id, user_id
+    );
 
-            if let Some(username) = &item.username {
-                active_model.username = Set(username.clone());
-            }
-            if let Some(first_name) = &item.first_name {
-                active_model.first_name = Set(Some(first_name.clone()));
-            }
-            if let Some(last_name) = &item.last_name {
-                active_model.last_name = Set(Some(last_name.clone()));
-            }
-            if let Some(email) = &item.email {
-                active_model.email = Set(email.clone());
-            }
-            if let Some(phone) = &item.phone {
-                active_model.phone = Set(Some(phone.clone()));
-            }
+    let user = repo
+        .find_by_id(user_id)
+        .await?
+        .ok_or(AppError::UserNotFound(user_id))?;
+    check_if_match(if_match.as_ref(), &user, true)?;
+
+    let mut document = serde_json::to_value(ReplaceUserRequest::from_model(&user))
+        .map_err(|_| AppError::InternalServerError)?;
+    patch::apply(&req, &body, &mut document)?;
+    patch::ensure_fields(&document, EDITABLE_FIELDS)?;
+    patch::ensure_required(&document, REQUIRED_FIELDS)?;
+    let item: ReplaceUserRequest = serde_json::from_value(document)
+        .map_err(|err| AppError::Validation(format!("Invalid patched user: {}", err)))?;
+
+    apply_replacement(&repo, &audit, user, item).await
+}
 
-            active_model.updated_on = Set(Local::now().naive_local());
+/// Validates and writes `item` over every editable field of `user`.
+async fn apply_replacement(
+    repo: &UserRepository,
+    audit: &AuditContext,
+    user: UserModel,
+    item: ReplaceUserRequest,
+) -> Result<HttpResponse, AppError> {
+    let user_id = user.id;
+    item.validate(&user)?;
 
-            let updated_user = repo.update(active_model).await?;
+    if item.username != user.username && repo.find_by_username(&item.username).await?.is_some() {
+        return Err(AppError::already_exists("username", &item.username));
+    }
 
-            info!("User with ID {} updated", user_id);
-            Ok(HttpResponse::Ok().json(updated_user))
-        }
-        None => Err(AppError::NotFound(format!(
-            "User with ID {} not found",
-            user_id
-        ))),
+    if item.email != user.email && repo.find_by_email(&item.email).await?.is_some() {
+        return Err(AppError::already_exists("email", &item.email));
     }
+
+    let mut active_model: UserActiveModel = user.clone().into();
+    active_model.username = Set(item.username);
+    active_model.first_name = Set(item.first_name);
+    active_model.last_name = Set(item.last_name);
+    active_model.email = Set(item.email);
+    active_model.phone = Set(item.phone);
+    active_model.updated_on = Set(Local::now().naive_local());
+
+    let updated_user = repo
+        .update(&user, active_model, audit)
+        .await?
+        .ok_or_else(|| concurrent_modification(user_id))?;
+
+    info!("User with ID {} updated", user_id);
+    Ok(HttpResponse::Ok()
+        .insert_header(user_etag(&updated_user))
+        .json(updated_user))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/api/users/{id}",
+    tag = "users",
+    params(("id" = i32, Path, description = "User id")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 204, description = "User deleted permanently"),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 403, description = "Missing `users:delete`", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 404, description = "User not found", body = ErrorResponse, content_type = "application/problem+json"),
+    )
+)]
 pub async fn delete_user_physical(
+    principal: Principal,
+    audit: AuditContext,
     db: web::Data<DbConn>,
     path: web::Path<i32>,
 ) -> Result<HttpResponse, AppError> {
     let user_id = path.into_inner();
     let repo = UserRepository::new(Arc::new(db.get_ref().clone()));
 
-    info!("Attempting to physically delete user with ID: {}", user_id);
+    info!(
+        "User {} attempting to physically delete user with ID: {}",
+        principal.user_id, user_id
+    );
 
     let user = repo.find_by_id(user_id).await?;
     if user.is_none() {
-        return Err(AppError::NotFound(format!(
-            "User with ID {} not found",
-            user_id
-        )));
+        return Err(AppError::UserNotFound(user_id));
     }
 
-    let delete_result = repo.delete(user_id).await?;
+    let delete_result = repo.delete(user_id, &audit).await?;
 
     if delete_result.rows_affected > 0 {
         info!("User with ID {} successfully deleted physically", user_id);
@@ -229,19 +687,45 @@ pub async fn delete_user_physical(
     }
 }
 
+#[utoipa::path(
+    patch,
+    path = "/api/users/{id}/soft-delete",
+    tag = "users",
+    params(
+        ("id" = i32, Path, description = "User id"),
+        ("If-Match" = Option<String>, Header, description = "ETag the deletion is based on"),
+    ),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 204, description = "User marked as deleted", headers(("ETag" = String, description = "New version"))),
+        (status = 400, description = "Already deleted", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 403, description = "Missing `users:delete`", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 404, description = "User not found", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 412, description = "The user changed since the `If-Match` version", body = ErrorResponse, content_type = "application/problem+json"),
+    )
+)]
 pub async fn delete_user_logical(
+    principal: Principal,
+    audit: AuditContext,
     db: web::Data<DbConn>,
     path: web::Path<i32>,
+    if_match: Option<web::Header<IfMatch>>,
 ) -> Result<HttpResponse, AppError> {
     let user_id = path.into_inner();
     let repo = UserRepository::new(Arc::new(db.get_ref().clone()));
 
-    info!("Attempting to logically delete user with ID: {}", user_id);
+    info!(
+        "User {} attempting to logically delete user with ID: {}",
+        principal.user_id, user_id
+    );
 
     let user = repo.find_by_id(user_id).await?;
 
     match user {
         Some(user) => {
+            check_if_match(if_match.as_ref(), &user, false)?;
+
             if user.deleted_on.is_some() {
                 warn!("User with ID {} is already logically deleted", user_id);
                 return Err(AppError::Validation(format!(
@@ -251,38 +735,59 @@ pub async fn delete_user_logical(
             }
 
             let now = Local::now().naive_local();
-            let result = repo.soft_delete(user_id, now).await?;
-
-            if result.is_some() {
-                info!("User with ID {} successfully marked as deleted", user_id);
-                Ok(HttpResponse::NoContent().finish())
-            } else {
-                Err(AppError::InternalServerError)
-            }
+            let user = repo
+                .soft_delete(user, now, &audit)
+                .await?
+                .ok_or_else(|| concurrent_modification(user_id))?;
+
+            info!("User with ID {} successfully marked as deleted", user_id);
+            Ok(HttpResponse::NoContent()
+                .insert_header(user_etag(&user))
+                .finish())
         }
-        None => Err(AppError::NotFound(format!(
-            "User with ID {} not found",
-            user_id
-        ))),
+        None => Err(AppError::UserNotFound(user_id)),
     }
 }
 
+#[utoipa::path(
+    patch,
+    path = "/api/users/{id}/restore",
+    tag = "users",
+    params(
+        ("id" = i32, Path, description = "User id"),
+        ("If-Match" = Option<String>, Header, description = "ETag the restore is based on"),
+    ),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 204, description = "User restored", headers(("ETag" = String, description = "New version"))),
+        (status = 400, description = "Not deleted", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 403, description = "Missing `users:restore`", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 404, description = "User not found", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 412, description = "The user changed since the `If-Match` version", body = ErrorResponse, content_type = "application/problem+json"),
+    )
+)]
 pub async fn restore_user(
+    principal: Principal,
+    audit: AuditContext,
     db: web::Data<DbConn>,
     path: web::Path<i32>,
+    if_match: Option<web::Header<IfMatch>>,
 ) -> Result<HttpResponse, AppError> {
     let user_id = path.into_inner();
     let repo = UserRepository::new(Arc::new(db.get_ref().clone()));
 
     info!(
-        "Attempting to restore logically deleted user with ID: {}",
-        user_id
+        "User {} attempting to restore logically deleted user with ID: {}",
+        principal.user_id, user_id
     );
 
     let user = repo.find_by_id(user_id).await?;
 
     match user {
         Some(user) => {
+            check_if_match(if_match.as_ref(), &user, false)?;
+
             if user.deleted_on.is_none() {
                 warn!("User with ID {} is not deleted, cannot restore", user_id);
                 return Err(AppError::Validation(format!(
@@ -292,18 +797,110 @@ pub async fn restore_user(
             }
 
             let now = Local::now().naive_local();
-            let result = repo.restore(user_id, now).await?;
+            let user = repo
+                .restore(user, now, &audit)
+                .await?
+                .ok_or_else(|| concurrent_modification(user_id))?;
+
+            info!("User with ID {} successfully restored", user_id);
+            Ok(HttpResponse::NoContent()
+                .insert_header(user_etag(&user))
+                .finish())
+        }
+        None => Err(AppError::UserNotFound(user_id)),
+    }
+}
 
-            if result.is_some() {
-                info!("User with ID {} successfully restored", user_id);
-                Ok(HttpResponse::NoContent().finish())
-            } else {
-                Err(AppError::InternalServerError)
-            }
+/// The row changed between reading it and writing it back.
+fn concurrent_modification(user_id: i32) -> AppError {
+    AppError::PreconditionFailed(format!(
+        "User with ID {} was modified concurrently; fetch it again and retry",
+        user_id
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::body::MessageBody;
+    use actix_web::dev::{ServiceRequest, ServiceResponse};
+    use actix_web::http::StatusCode;
+    use actix_web::middleware::{Next, from_fn};
+    use actix_web::{App, HttpMessage, test};
+    use std::collections::BTreeSet;
+
+    /// Authenticates every request as user 7, who holds no role.
+    async fn as_member(
+        req: ServiceRequest,
+        next: Next<impl MessageBody>,
+    ) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+        req.extensions_mut().insert(Principal {
+            user_id: 7,
+            username: "ada".to_string(),
+            roles: BTreeSet::new(),
+            permissions: BTreeSet::new(),
+        });
+        next.call(req).await
+    }
+
+    #[actix_web::test]
+    async fn members_may_only_read_and_modify_themselves() {
+        // Nothing listens on port 1, so queries fail with a database error.
+        let db = sea_orm::Database::connect(
+            sea_orm::ConnectOptions::new("postgres://localhost:1/none")
+                .connect_lazy(true)
+                .acquire_timeout(std::time::Duration::from_secs(1))
+                .to_owned(),
+        )
+        .await
+        .unwrap();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(db))
+                .wrap(from_fn(as_member))
+                .configure(configure),
+        )
+        .await;
+        let replacement = serde_json::json!({ "username": "ada", "email": "ada@example.com" });
+        let request = |method: &str, uri: &str| {
+            test::TestRequest::default()
+                .method(method.parse().unwrap())
+                .uri(uri)
+        };
+
+        for req in [
+            request("GET", "/users"),
+            request("GET", "/users/search?q=ada"),
+            request("DELETE", "/users/7"),
+            request("PATCH", "/users/7/soft-delete"),
+            request("PATCH", "/users/7/restore"),
+            request("GET", "/users/8"),
+            request("PUT", "/users/8").set_json(&replacement),
+            request("PATCH", "/users/8")
+                .insert_header(("content-type", "application/merge-patch+json"))
+                .set_payload("{}"),
+        ] {
+            let req = req.to_request();
+            let uri = format!("{} {}", req.method(), req.uri());
+            let status = match test::try_call_service(&app, req).await {
+                Ok(res) => res.status(),
+                Err(err) => err.as_response_error().status_code(),
+            };
+            assert_eq!(status, StatusCode::FORBIDDEN, "{}", uri);
+        }
+
+        // Past the permission check the handlers reach the database.
+        for req in [
+            request("GET", "/users/7"),
+            request("PUT", "/users/7").set_json(&replacement),
+            request("PATCH", "/users/7")
+                .insert_header(("content-type", "application/merge-patch+json"))
+                .set_payload("{}"),
+        ] {
+            let req = req.to_request();
+            let uri = format!("{} {}", req.method(), req.uri());
+            let res = test::call_service(&app, req).await;
+            assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR, "{}", uri);
         }
-        None => Err(AppError::NotFound(format!(
-            "User with ID {} not found",
-            user_id
-        ))),
     }
 }
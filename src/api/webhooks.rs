@@ -0,0 +1,488 @@
+use actix_web::{HttpResponse, web};
+use log::info;
+use sea_orm::DbConn;
+use sea_orm::sqlx::types::chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
+use validator::{ValidationError, ValidationErrors};
+
+use super::pagination::{Page, resolve_limit};
+use crate::auth::{Principal, RequirePermission, permissions};
+use crate::config::{AppConfig, WebhooksConfig};
+use crate::db::models::{
+    WebhookAttemptModel, WebhookDeliveryModel, WebhookSubscriptionActiveModel,
+    WebhookSubscriptionModel,
+};
+use crate::db::repositories::{
+    WebhookDeliveryQuery, WebhookDeliveryRepository, WebhookSubscriptionRepository,
+};
+use crate::error::{AppError, ErrorResponse};
+use crate::redaction::Redacted;
+use crate::webhooks::{self, DeliveryStatus, EVENT_TYPES};
+use sea_orm::ActiveValue::Set;
+
+const MIN_SECRET_LENGTH: usize = 16;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/webhooks")
+            .service(
+                web::resource("")
+                    .route(
+                        web::get()
+                            .to(get_webhooks)
+                            .wrap(RequirePermission(permissions::WEBHOOKS_MANAGE)),
+                    )
+                    .route(
+                        web::post()
+                            .to(create_webhook)
+                            .wrap(RequirePermission(permissions::WEBHOOKS_MANAGE)),
+                    ),
+            )
+            .service(
+                web::resource("/{id}")
+                    .route(
+                        web::get()
+                            .to(get_webhook)
+                            .wrap(RequirePermission(permissions::WEBHOOKS_MANAGE)),
+                    )
+                    .route(
+                        web::put()
+                            .to(replace_webhook)
+                            .wrap(RequirePermission(permissions::WEBHOOKS_MANAGE)),
+                    )
+                    .route(
+                        web::delete()
+                            .to(delete_webhook)
+                            .wrap(RequirePermission(permissions::WEBHOOKS_MANAGE)),
+                    ),
+            )
+            .service(
+                web::resource("/{id}/deliveries").route(
+                    web::get()
+                        .to(get_deliveries)
+                        .wrap(RequirePermission(permissions::WEBHOOKS_MANAGE)),
+                ),
+            )
+            .service(
+                web::resource("/{id}/deliveries/{delivery_id}").route(
+                    web::get()
+                        .to(get_delivery)
+                        .wrap(RequirePermission(permissions::WEBHOOKS_MANAGE)),
+                ),
+            )
+            .service(
+                web::resource("/{id}/deliveries/{delivery_id}/redeliver").route(
+                    web::post()
+                        .to(redeliver)
+                        .wrap(RequirePermission(permissions::WEBHOOKS_MANAGE)),
+                ),
+            ),
+    );
+}
+
+/// Body of `POST` and `PUT`. On `PUT`, an absent `secret` keeps the
+/// current one.
+#[derive(Deserialize, ToSchema)]
+pub struct WebhookRequest {
+    /// `http` or `https` URL receiving the `POST`s.
+    pub url: String,
+    /// HMAC key for `X-Signature`; generated on create when absent.
+    #[serde(default)]
+    #[schema(value_type = Option<String>, write_only, min_length = 16)]
+    pub secret: Option<Redacted<String>>,
+    /// Event types to deliver, e.g. `UserCreated`; every type when empty.
+    #[serde(default)]
+    pub event_types: Vec<String>,
+    #[serde(default = "default_active")]
+    #[schema(default = true)]
+    pub active: bool,
+}
+
+fn default_active() -> bool {
+    true
+}
+
+impl WebhookRequest {
+    fn validate(&self, config: &WebhooksConfig) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+
+        match reqwest::Url::parse(&self.url) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") && url.has_host() => {
+                if !config.allow_private_networks
+                    && let Err(message) = webhooks::check_url(&url)
+                {
+                    errors.add(
+                        "url",
+                        ValidationError::new("private_address").with_message(message.into()),
+                    );
+                }
+            }
+            _ => errors.add(
+                "url",
+                ValidationError::new("url").with_message("URL must be an http(s) URL".into()),
+            ),
+        }
+        if let Some(secret) = &self.secret
+            && secret.expose().chars().count() < MIN_SECRET_LENGTH
+        {
+            errors.add(
+                "secret",
+                ValidationError::new("length").with_message(
+                    format!("Secret must be at least {} characters", MIN_SECRET_LENGTH).into(),
+                ),
+            );
+        }
+        for event_type in &self.event_types {
+            if !EVENT_TYPES.contains(&event_type.as_str()) {
+                errors.add(
+                    "event_types",
+                    ValidationError::new("event_type").with_message(
+                        format!("'{}' is not one of {}", event_type, EVENT_TYPES.join(", ")).into(),
+                    ),
+                );
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// A new subscription with its secret, which is not returned again.
+#[derive(Serialize, ToSchema)]
+pub struct CreatedWebhookResponse {
+    #[serde(flatten)]
+    pub subscription: WebhookSubscriptionModel,
+    pub secret: String,
+}
+
+/// A delivery with every attempt made, oldest first.
+#[derive(Serialize, ToSchema)]
+pub struct WebhookDeliveryDetail {
+    #[serde(flatten)]
+    pub delivery: WebhookDeliveryModel,
+    pub attempts_made: Vec<WebhookAttemptModel>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DeliveryParams {
+    status: Option<DeliveryStatus>,
+    limit: Option<u64>,
+    offset: Option<u64>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/webhooks",
+    tag = "webhooks",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Every subscription", body = Vec<WebhookSubscriptionModel>),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 403, description = "Missing `webhooks:manage`", body = ErrorResponse, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_webhooks(db: web::Data<DbConn>) -> Result<HttpResponse, AppError> {
+    let subscriptions = WebhookSubscriptionRepository::new(Arc::new(db.get_ref().clone()))
+        .find_all()
+        .await?;
+
+    Ok(HttpResponse::Ok().json(subscriptions))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/webhooks",
+    tag = "webhooks",
+    request_body = WebhookRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 201, description = "Subscription created", body = CreatedWebhookResponse),
+        (status = 400, description = "Malformed body", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 403, description = "Missing `webhooks:manage`", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid fields", body = ErrorResponse, content_type = "application/problem+json"),
+    )
+)]
+pub async fn create_webhook(
+    principal: Principal,
+    db: web::Data<DbConn>,
+    config: web::Data<AppConfig>,
+    item: web::Json<WebhookRequest>,
+) -> Result<HttpResponse, AppError> {
+    item.validate(&config.webhooks)?;
+    let item = item.into_inner();
+
+    let secret = match item.secret {
+        Some(secret) => secret.expose().clone(),
+        None => webhooks::generate_secret(),
+    };
+    let now = Local::now().naive_local();
+    let subscription = WebhookSubscriptionRepository::new(Arc::new(db.get_ref().clone()))
+        .create(WebhookSubscriptionActiveModel {
+            url: Set(item.url),
+            secret: Set(secret.clone()),
+            event_types: Set(item.event_types.into()),
+            active: Set(item.active),
+            created_by: Set(Some(principal.user_id)),
+            created_on: Set(now),
+            updated_on: Set(now),
+            ..Default::default()
+        })
+        .await?;
+
+    info!(
+        "User {} created webhook subscription {}",
+        principal.user_id, subscription.id
+    );
+    Ok(HttpResponse::Created().json(CreatedWebhookResponse {
+        subscription,
+        secret,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/webhooks/{id}",
+    tag = "webhooks",
+    params(("id" = i32, Path, description = "Subscription id")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "The subscription", body = WebhookSubscriptionModel),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 403, description = "Missing `webhooks:manage`", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 404, description = "No such subscription", body = ErrorResponse, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_webhook(
+    db: web::Data<DbConn>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse, AppError> {
+    let repo = WebhookSubscriptionRepository::new(Arc::new(db.get_ref().clone()));
+    let subscription = find_subscription(&repo, path.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(subscription))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/webhooks/{id}",
+    tag = "webhooks",
+    params(("id" = i32, Path, description = "Subscription id")),
+    request_body = WebhookRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Subscription replaced", body = WebhookSubscriptionModel),
+        (status = 400, description = "Malformed body", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 403, description = "Missing `webhooks:manage`", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 404, description = "No such subscription", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid fields", body = ErrorResponse, content_type = "application/problem+json"),
+    )
+)]
+pub async fn replace_webhook(
+    principal: Principal,
+    db: web::Data<DbConn>,
+    config: web::Data<AppConfig>,
+    path: web::Path<i32>,
+    item: web::Json<WebhookRequest>,
+) -> Result<HttpResponse, AppError> {
+    item.validate(&config.webhooks)?;
+    let item = item.into_inner();
+    let repo = WebhookSubscriptionRepository::new(Arc::new(db.get_ref().clone()));
+    let subscription = find_subscription(&repo, path.into_inner()).await?;
+
+    let mut model: WebhookSubscriptionActiveModel = subscription.into();
+    model.url = Set(item.url);
+    model.event_types = Set(item.event_types.into());
+    model.active = Set(item.active);
+    if let Some(secret) = item.secret {
+        model.secret = Set(secret.expose().clone());
+    }
+    model.updated_on = Set(Local::now().naive_local());
+    let subscription = repo.update(model).await?;
+
+    info!(
+        "User {} updated webhook subscription {}",
+        principal.user_id, subscription.id
+    );
+    Ok(HttpResponse::Ok().json(subscription))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/webhooks/{id}",
+    tag = "webhooks",
+    params(("id" = i32, Path, description = "Subscription id")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 204, description = "Subscription and its deliveries deleted"),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 403, description = "Missing `webhooks:manage`", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 404, description = "No such subscription", body = ErrorResponse, content_type = "application/problem+json"),
+    )
+)]
+pub async fn delete_webhook(
+    principal: Principal,
+    db: web::Data<DbConn>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse, AppError> {
+    let id = path.into_inner();
+    let result = WebhookSubscriptionRepository::new(Arc::new(db.get_ref().clone()))
+        .delete(id)
+        .await?;
+    if result.rows_affected == 0 {
+        return Err(subscription_not_found(id));
+    }
+
+    info!(
+        "User {} deleted webhook subscription {}",
+        principal.user_id, id
+    );
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/webhooks/{id}/deliveries",
+    tag = "webhooks",
+    params(("id" = i32, Path, description = "Subscription id"), DeliveryParams),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Deliveries, newest first", body = Page<WebhookDeliveryModel>),
+        (status = 400, description = "Invalid query", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 403, description = "Missing `webhooks:manage`", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 404, description = "No such subscription", body = ErrorResponse, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_deliveries(
+    db: web::Data<DbConn>,
+    config: web::Data<AppConfig>,
+    path: web::Path<i32>,
+    query: web::Query<DeliveryParams>,
+) -> Result<HttpResponse, AppError> {
+    let db = Arc::new(db.get_ref().clone());
+    let subscription = find_subscription(
+        &WebhookSubscriptionRepository::new(db.clone()),
+        path.into_inner(),
+    )
+    .await?;
+
+    let query = WebhookDeliveryQuery {
+        subscription_id: subscription.id,
+        status: query.status,
+        limit: resolve_limit(query.limit, &config.pagination)?,
+        offset: query.offset.unwrap_or(0),
+    };
+    let page = WebhookDeliveryRepository::new(db).find_page(&query).await?;
+
+    Ok(HttpResponse::Ok().json(Page {
+        data: page.deliveries,
+        total: page.total,
+        limit: query.limit,
+        offset: Some(query.offset),
+        next_cursor: None,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/webhooks/{id}/deliveries/{delivery_id}",
+    tag = "webhooks",
+    params(
+        ("id" = i32, Path, description = "Subscription id"),
+        ("delivery_id" = i64, Path, description = "Delivery id"),
+    ),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "The delivery and its attempts", body = WebhookDeliveryDetail),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 403, description = "Missing `webhooks:manage`", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 404, description = "No such delivery", body = ErrorResponse, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_delivery(
+    db: web::Data<DbConn>,
+    path: web::Path<(i32, i64)>,
+) -> Result<HttpResponse, AppError> {
+    let (subscription_id, delivery_id) = path.into_inner();
+    let repo = WebhookDeliveryRepository::new(Arc::new(db.get_ref().clone()));
+
+    let delivery = find_delivery(&repo, subscription_id, delivery_id).await?;
+    let attempts_made = repo.find_attempts(delivery.id).await?;
+
+    Ok(HttpResponse::Ok().json(WebhookDeliveryDetail {
+        delivery,
+        attempts_made,
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/webhooks/{id}/deliveries/{delivery_id}/redeliver",
+    tag = "webhooks",
+    params(
+        ("id" = i32, Path, description = "Subscription id"),
+        ("delivery_id" = i64, Path, description = "Delivery id"),
+    ),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 202, description = "Delivery queued with a fresh set of attempts", body = WebhookDeliveryModel),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 403, description = "Missing `webhooks:manage`", body = ErrorResponse, content_type = "application/problem+json"),
+        (status = 404, description = "No such delivery", body = ErrorResponse, content_type = "application/problem+json"),
+    )
+)]
+pub async fn redeliver(
+    principal: Principal,
+    db: web::Data<DbConn>,
+    path: web::Path<(i32, i64)>,
+) -> Result<HttpResponse, AppError> {
+    let (subscription_id, delivery_id) = path.into_inner();
+    let repo = WebhookDeliveryRepository::new(Arc::new(db.get_ref().clone()));
+
+    find_delivery(&repo, subscription_id, delivery_id).await?;
+    repo.redeliver(delivery_id).await?;
+    let delivery = find_delivery(&repo, subscription_id, delivery_id).await?;
+
+    info!(
+        "User {} queued webhook delivery {} for redelivery",
+        principal.user_id, delivery_id
+    );
+    Ok(HttpResponse::Accepted().json(delivery))
+}
+
+async fn find_subscription(
+    repo: &WebhookSubscriptionRepository,
+    id: i32,
+) -> Result<WebhookSubscriptionModel, AppError> {
+    repo.find_by_id(id)
+        .await?
+        .ok_or_else(|| subscription_not_found(id))
+}
+
+fn subscription_not_found(id: i32) -> AppError {
+    AppError::NotFound(format!("Webhook subscription with ID {} not found", id))
+}
+
+async fn find_delivery(
+    repo: &WebhookDeliveryRepository,
+    subscription_id: i32,
+    delivery_id: i64,
+) -> Result<WebhookDeliveryModel, AppError> {
+    repo.find_by_id(subscription_id, delivery_id)
+        .await?
+        .ok_or_else(|| {
+            AppError::NotFound(format!(
+                "Delivery {} of webhook subscription {} not found",
+                delivery_id, subscription_id
+            ))
+        })
+}
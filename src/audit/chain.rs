@@ -0,0 +1,121 @@
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+
+use crate::db::models::UserAuditModel;
+
+/// `prev_hash` of the first entry.
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Hex SHA-256 of `prev_hash` followed by the entry's canonical JSON.
+pub fn entry_hash(prev_hash: &str, entry: &UserAuditModel) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(prev_hash.as_bytes());
+    hasher.update(canonical_json(entry).as_bytes());
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Every stored field except the hashes, with keys sorted at every level
+/// and no whitespace, so the same row always serializes identically.
+pub fn canonical_json(entry: &UserAuditModel) -> String {
+    let value = json!({
+        "id": entry.id,
+        "user_id": entry.user_id,
+        "action": entry.action,
+        "actor_id": entry.actor_id,
+        "request_id": entry.request_id,
+        "client_ip": entry.client_ip,
+        "before": entry.before,
+        "after": entry.after,
+        "created_on": entry.created_on.format("%Y-%m-%dT%H:%M:%S%.6f").to_string(),
+    });
+    let mut out = String::new();
+    write_canonical(&value, &mut out);
+    out
+}
+
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            out.push('{');
+            for (index, key) in keys.into_iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(&map[key], out);
+            }
+            out.push('}');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        scalar => out.push_str(&scalar.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::sqlx::types::chrono::NaiveDate;
+
+    fn entry() -> UserAuditModel {
+        UserAuditModel {
+            id: 1,
+            user_id: 7,
+            action: "updated".into(),
+            actor_id: Some(16),
+            request_id: Some("req-1".into()),
+            client_ip: None,
+            before: Some(json!({"email": "old@example.com", "first_name": "Jo"})),
+            after: Some(json!({"first_name": "Joe", "email": "new@example.com"})),
+            created_on: NaiveDate::from_ymd_opt(2026, 10, 18)
+                .unwrap()
+                .and_hms_micro_opt(12, 0, 0, 5)
+                .unwrap(),
+            prev_hash: "ignored".into(),
+            hash: "ignored".into(),
+        }
+    }
+
+    #[test]
+    fn canonical_json_sorts_keys_without_whitespace() {
+        assert_eq!(
+            canonical_json(&entry()),
+            concat!(
+                r#"{"action":"updated","actor_id":16,"#,
+                r#""after":{"email":"new@example.com","first_name":"Joe"},"#,
+                r#""before":{"email":"old@example.com","first_name":"Jo"},"#,
+                r#""client_ip":null,"created_on":"2026-10-18T12:00:00.000005","#,
+                r#""id":1,"request_id":"req-1","user_id":7}"#
+            )
+        );
+    }
+
+    #[test]
+    fn entry_hash_covers_prev_hash_and_content() {
+        let hash = entry_hash(GENESIS_HASH, &entry());
+        assert_eq!(
+            hash,
+            "8d23870121692b11000abc8c86c943770c24f5d1ba4b0f7adb883fb13a0b7e58"
+        );
+
+        assert_ne!(entry_hash(&hash, &entry()), hash);
+        let mut edited = entry();
+        edited.actor_id = Some(8);
+        assert_ne!(entry_hash(GENESIS_HASH, &edited), hash);
+    }
+}
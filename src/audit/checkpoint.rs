@@ -0,0 +1,121 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use sea_orm::sqlx::types::chrono::Local;
+use sea_orm::{DbConn, DbErr, Set};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::AuditConfig;
+use crate::db::models::{AuditCheckpointActiveModel, AuditCheckpointModel};
+use crate::db::repositories::{AuditCheckpointRepository, UserAuditRepository};
+use crate::shutdown::Shutdown;
+
+/// The signed message binding a checkpoint to an entry of the chain.
+pub fn message(last_entry_id: i64, hash: &str) -> String {
+    format!("tbl_user_audit:{}:{}", last_entry_id, hash)
+}
+
+/// First 16 hex characters of the SHA-256 of the public key.
+pub fn key_id(key: &VerifyingKey) -> String {
+    Sha256::digest(key.as_bytes())
+        .iter()
+        .take(8)
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+fn decode_key(key: &str) -> Option<[u8; 32]> {
+    STANDARD.decode(key.trim()).ok()?.try_into().ok()
+}
+
+/// The configured signing key, if any.
+pub fn signing_key(config: &AuditConfig) -> Option<SigningKey> {
+    let seed = decode_key(config.signing_key.as_ref()?.expose())?;
+    Some(SigningKey::from_bytes(&seed))
+}
+
+/// The configured verifying key, or the one matching the signing key.
+pub fn verifying_key(config: &AuditConfig) -> Option<VerifyingKey> {
+    match &config.verifying_key {
+        Some(key) => VerifyingKey::from_bytes(&decode_key(key)?).ok(),
+        None => signing_key(config).map(|key| key.verifying_key()),
+    }
+}
+
+/// Checks the checkpoint's signature with `key`.
+pub fn verify_signature(key: &VerifyingKey, checkpoint: &AuditCheckpointModel) -> bool {
+    let Some(signature) = STANDARD
+        .decode(&checkpoint.signature)
+        .ok()
+        .and_then(|bytes| Signature::from_slice(&bytes).ok())
+    else {
+        return false;
+    };
+    key.verify(
+        message(checkpoint.last_entry_id, &checkpoint.hash).as_bytes(),
+        &signature,
+    )
+    .is_ok()
+}
+
+/// Signs the newest entry unless the latest checkpoint already covers it.
+pub async fn write_checkpoint(
+    db: &DbConn,
+    key: &SigningKey,
+) -> Result<Option<AuditCheckpointModel>, DbErr> {
+    let db = Arc::new(db.clone());
+    let checkpoints = AuditCheckpointRepository::new(db.clone());
+
+    let Some((last_entry_id, hash)) = UserAuditRepository::new(db).latest_link().await? else {
+        return Ok(None);
+    };
+    if checkpoints
+        .latest()
+        .await?
+        .is_some_and(|latest| latest.last_entry_id >= last_entry_id)
+    {
+        return Ok(None);
+    }
+
+    let signature = key.sign(message(last_entry_id, &hash).as_bytes());
+    checkpoints
+        .create(AuditCheckpointActiveModel {
+            last_entry_id: Set(last_entry_id),
+            hash: Set(hash),
+            signature: Set(STANDARD.encode(signature.to_bytes())),
+            key_id: Set(key_id(&key.verifying_key())),
+            created_on: Set(Local::now().naive_local()),
+            ..Default::default()
+        })
+        .await
+        .map(Some)
+}
+
+/// Writes a checkpoint every `interval` and a last one on shutdown.
+pub fn spawn_checkpointer(key: SigningKey, db: DbConn, interval: Duration, shutdown: &Shutdown) {
+    let signal = shutdown.clone();
+
+    shutdown.spawn(async move {
+        let mut ticks = tokio::time::interval(interval);
+        ticks.tick().await;
+        loop {
+            let stopping = tokio::select! {
+                _ = signal.signalled() => true,
+                _ = ticks.tick() => false,
+            };
+            match write_checkpoint(&db, &key).await {
+                Ok(Some(checkpoint)) => log::info!(
+                    "Signed audit checkpoint at entry {}",
+                    checkpoint.last_entry_id
+                ),
+                Ok(None) => {}
+                Err(err) => log::warn!("Failed to write an audit checkpoint: {}", err),
+            }
+            if stopping {
+                break;
+            }
+        }
+    });
+}
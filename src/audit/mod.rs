@@ -0,0 +1,122 @@
+pub mod chain;
+pub mod checkpoint;
+pub mod verify;
+
+use actix_web::dev::Payload;
+use actix_web::http::header::AUTHORIZATION;
+use actix_web::{FromRequest, HttpMessage, HttpRequest};
+use futures_util::future::LocalBoxFuture;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use utoipa::ToSchema;
+
+use crate::auth::AuthenticatedUser;
+use crate::db::models::UserModel;
+use crate::error::AppError;
+use crate::logging::RequestContext;
+use crate::middleware::{RequestId, client_ip};
+
+/// Fields left out of audit entries; they change on every write.
+const IGNORED_FIELDS: &[&str] = &["updated_on", "version"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Created,
+    Updated,
+    SoftDeleted,
+    Restored,
+    Deleted,
+}
+
+impl AuditAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AuditAction::Created => "created",
+            AuditAction::Updated => "updated",
+            AuditAction::SoftDeleted => "soft_deleted",
+            AuditAction::Restored => "restored",
+            AuditAction::Deleted => "deleted",
+        }
+    }
+}
+
+/// Who made a change and from where, recorded with every user mutation.
+///
+/// As a handler argument it never fails: unauthenticated callers simply
+/// have no actor.
+#[derive(Debug, Clone, Default)]
+pub struct AuditContext {
+    pub actor_id: Option<i32>,
+    pub request_id: Option<String>,
+    pub client_ip: Option<String>,
+}
+
+impl AuditContext {
+    pub async fn from_request(req: &HttpRequest) -> Self {
+        let actor_id = if req.headers().contains_key(AUTHORIZATION) {
+            AuthenticatedUser::identify(req)
+                .await
+                .ok()
+                .map(|user| user.id)
+        } else {
+            None
+        };
+        let request_id = req
+            .extensions()
+            .get::<RequestId>()
+            .map(|id| id.0.clone())
+            .or_else(|| RequestContext::current().map(|context| context.request_id));
+
+        Self {
+            actor_id,
+            request_id,
+            client_ip: client_ip(req),
+        }
+    }
+}
+
+impl FromRequest for AuditContext {
+    type Error = AppError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let req = req.clone();
+        Box::pin(async move { Ok(AuditContext::from_request(&req).await) })
+    }
+}
+
+/// Before and after images of a change, keeping only the fields that
+/// differ. Creates have no before image and deletes no after image; both
+/// then carry every field.
+pub fn diff(
+    before: Option<&UserModel>,
+    after: Option<&UserModel>,
+) -> (Option<Value>, Option<Value>) {
+    let mut before = before.map(fields);
+    let mut after = after.map(fields);
+
+    if let (Some(before), Some(after)) = (&mut before, &mut after) {
+        let unchanged: Vec<String> = before
+            .iter()
+            .filter(|(name, value)| after.get(*name) == Some(value))
+            .map(|(name, _)| name.clone())
+            .collect();
+        for name in unchanged {
+            before.remove(&name);
+            after.remove(&name);
+        }
+    }
+
+    (before.map(Value::Object), after.map(Value::Object))
+}
+
+fn fields(user: &UserModel) -> Map<String, Value> {
+    let Ok(Value::Object(mut fields)) = serde_json::to_value(user) else {
+        return Map::new();
+    };
+    for name in IGNORED_FIELDS {
+        fields.remove(*name);
+    }
+    fields
+}
@@ -0,0 +1,367 @@
+use ed25519_dalek::VerifyingKey;
+use sea_orm::{DbConn, DbErr};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+use super::{chain, checkpoint};
+use crate::db::models::{AuditCheckpointModel, UserAuditModel};
+use crate::db::repositories::{AuditCheckpointRepository, UserAuditRepository};
+
+const BATCH_SIZE: u64 = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BrokenReason {
+    /// `prev_hash` differs from the previous entry's hash: an entry was
+    /// removed, inserted or reordered.
+    PrevHashMismatch,
+    /// The entry's content no longer matches its `hash`.
+    HashMismatch,
+    /// A checkpoint's signature does not verify.
+    InvalidSignature,
+    /// The chain is consistent but differs from a signed checkpoint, so it
+    /// was rewritten up to this entry.
+    CheckpointMismatch,
+    /// A signed checkpoint refers to an entry that no longer exists.
+    MissingEntry,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BrokenLink {
+    pub entry_id: i64,
+    pub reason: BrokenReason,
+    pub expected: String,
+    pub actual: String,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct VerifyReport {
+    pub intact: bool,
+    pub entries_checked: u64,
+    pub last_entry_id: Option<i64>,
+    pub checkpoints_checked: u64,
+    /// Whether checkpoint signatures were checked; requires a verifying
+    /// key in the configuration.
+    pub signatures_verified: bool,
+    /// Newest checkpoint matching the chain.
+    pub last_valid_checkpoint: Option<i64>,
+    /// The first problem in chain order.
+    pub first_broken: Option<BrokenLink>,
+}
+
+/// Walks the whole chain and checks it against every checkpoint, stopping
+/// at the first broken link.
+pub async fn verify(db: &DbConn, key: Option<&VerifyingKey>) -> Result<VerifyReport, DbErr> {
+    let db = Arc::new(db.clone());
+    let entries = UserAuditRepository::new(db.clone());
+    let checkpoints = AuditCheckpointRepository::new(db).find_all().await?;
+
+    let mut walk = match Walk::new(&checkpoints, key) {
+        Ok(walk) => walk,
+        Err(report) => return Ok(report),
+    };
+    loop {
+        let batch = entries.find_chain_batch(walk.last_id, BATCH_SIZE).await?;
+        if batch.is_empty() {
+            break;
+        }
+        for entry in batch {
+            if let Some(link) = walk.push(entry) {
+                return Ok(walk.broken(link));
+            }
+        }
+    }
+    Ok(walk.finish())
+}
+
+/// State carried along the chain while it is read in batches.
+struct Walk<'a> {
+    report: VerifyReport,
+    /// Checkpoints by the entry they cover.
+    by_entry: BTreeMap<i64, &'a AuditCheckpointModel>,
+    prev_hash: String,
+    last_id: i64,
+}
+
+impl<'a> Walk<'a> {
+    /// A bad checkpoint signature is reported at that entry, ahead of
+    /// anything found later in the chain.
+    fn new(
+        checkpoints: &'a [AuditCheckpointModel],
+        key: Option<&VerifyingKey>,
+    ) -> Result<Self, VerifyReport> {
+        let mut walk = Self {
+            report: VerifyReport {
+                intact: true,
+                entries_checked: 0,
+                last_entry_id: None,
+                checkpoints_checked: 0,
+                signatures_verified: key.is_some(),
+                last_valid_checkpoint: None,
+                first_broken: None,
+            },
+            by_entry: BTreeMap::new(),
+            prev_hash: chain::GENESIS_HASH.to_string(),
+            last_id: 0,
+        };
+        for checkpoint in checkpoints {
+            if let Some(key) = key
+                && !checkpoint::verify_signature(key, checkpoint)
+            {
+                return Err(walk.broken(BrokenLink {
+                    entry_id: checkpoint.last_entry_id,
+                    reason: BrokenReason::InvalidSignature,
+                    expected: format!("a valid signature by key {}", checkpoint::key_id(key)),
+                    actual: format!("{} by key {}", checkpoint.signature, checkpoint.key_id),
+                }));
+            }
+            walk.by_entry.insert(checkpoint.last_entry_id, checkpoint);
+        }
+        Ok(walk)
+    }
+
+    /// Checks the next entry in id order.
+    fn push(&mut self, entry: UserAuditModel) -> Option<BrokenLink> {
+        if let Some((&entry_id, checkpoint)) =
+            self.by_entry.range(self.last_id + 1..entry.id).next()
+        {
+            return Some(missing_entry(entry_id, checkpoint));
+        }
+        self.last_id = entry.id;
+
+        if entry.prev_hash != self.prev_hash {
+            return Some(BrokenLink {
+                entry_id: entry.id,
+                reason: BrokenReason::PrevHashMismatch,
+                expected: self.prev_hash.clone(),
+                actual: entry.prev_hash,
+            });
+        }
+        let hash = chain::entry_hash(&self.prev_hash, &entry);
+        if hash != entry.hash {
+            return Some(BrokenLink {
+                entry_id: entry.id,
+                reason: BrokenReason::HashMismatch,
+                expected: hash,
+                actual: entry.hash,
+            });
+        }
+        if let Some(checkpoint) = self.by_entry.get(&entry.id) {
+            if checkpoint.hash != entry.hash {
+                return Some(BrokenLink {
+                    entry_id: entry.id,
+                    reason: BrokenReason::CheckpointMismatch,
+                    expected: checkpoint.hash.clone(),
+                    actual: entry.hash,
+                });
+            }
+            self.report.checkpoints_checked += 1;
+            self.report.last_valid_checkpoint = Some(checkpoint.id);
+        }
+
+        self.report.entries_checked += 1;
+        self.report.last_entry_id = Some(entry.id);
+        self.prev_hash = entry.hash;
+        None
+    }
+
+    /// Checkpoints past the end of the chain mean its tail was truncated.
+    fn finish(self) -> VerifyReport {
+        match self.by_entry.range(self.last_id + 1..).next() {
+            Some((&entry_id, checkpoint)) => {
+                let link = missing_entry(entry_id, checkpoint);
+                self.broken(link)
+            }
+            None => self.report,
+        }
+    }
+
+    fn broken(self, link: BrokenLink) -> VerifyReport {
+        VerifyReport {
+            intact: false,
+            first_broken: Some(link),
+            ..self.report
+        }
+    }
+}
+
+fn missing_entry(entry_id: i64, checkpoint: &AuditCheckpointModel) -> BrokenLink {
+    BrokenLink {
+        entry_id,
+        reason: BrokenReason::MissingEntry,
+        expected: checkpoint.hash.clone(),
+        actual: "no entry".into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::Engine;
+    use base64::engine::general_purpose::STANDARD;
+    use ed25519_dalek::{Signer, SigningKey};
+    use sea_orm::sqlx::types::chrono::NaiveDate;
+
+    /// `count` entries linked from the genesis hash.
+    fn chain(count: i64) -> Vec<UserAuditModel> {
+        let created_on = NaiveDate::from_ymd_opt(2026, 10, 18)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+        let mut prev_hash = chain::GENESIS_HASH.to_string();
+        (1..=count)
+            .map(|id| {
+                let mut entry = UserAuditModel {
+                    id,
+                    user_id: 7,
+                    action: "updated".into(),
+                    actor_id: Some(16),
+                    request_id: None,
+                    client_ip: None,
+                    before: None,
+                    after: Some(serde_json::json!({ "version": id })),
+                    created_on,
+                    prev_hash: prev_hash.clone(),
+                    hash: String::new(),
+                };
+                entry.hash = chain::entry_hash(&prev_hash, &entry);
+                prev_hash = entry.hash.clone();
+                entry
+            })
+            .collect()
+    }
+
+    fn rehash(entries: &mut [UserAuditModel]) {
+        let mut prev_hash = chain::GENESIS_HASH.to_string();
+        for entry in entries {
+            entry.prev_hash = prev_hash;
+            entry.hash = chain::entry_hash(&entry.prev_hash, entry);
+            prev_hash = entry.hash.clone();
+        }
+    }
+
+    fn key() -> SigningKey {
+        SigningKey::from_bytes(&[7; 32])
+    }
+
+    fn checkpoint(id: i64, entry: &UserAuditModel) -> AuditCheckpointModel {
+        let signature = key().sign(checkpoint::message(entry.id, &entry.hash).as_bytes());
+        AuditCheckpointModel {
+            id,
+            last_entry_id: entry.id,
+            hash: entry.hash.clone(),
+            signature: STANDARD.encode(signature.to_bytes()),
+            key_id: checkpoint::key_id(&key().verifying_key()),
+            created_on: entry.created_on,
+        }
+    }
+
+    /// What [`verify`] does, over entries already in memory.
+    fn run(entries: Vec<UserAuditModel>, checkpoints: &[AuditCheckpointModel]) -> VerifyReport {
+        let key = key().verifying_key();
+        let mut walk = match Walk::new(checkpoints, Some(&key)) {
+            Ok(walk) => walk,
+            Err(report) => return report,
+        };
+        for entry in entries {
+            if let Some(link) = walk.push(entry) {
+                return walk.broken(link);
+            }
+        }
+        walk.finish()
+    }
+
+    fn first_broken(report: &VerifyReport) -> (i64, BrokenReason) {
+        assert!(!report.intact);
+        let link = report.first_broken.as_ref().expect("a broken link");
+        (link.entry_id, link.reason)
+    }
+
+    #[test]
+    fn intact_chain_passes() {
+        let entries = chain(3);
+        let checkpoints = [checkpoint(1, &entries[1])];
+
+        let report = run(entries, &checkpoints);
+
+        assert!(report.intact);
+        assert!(report.first_broken.is_none());
+        assert_eq!(report.entries_checked, 3);
+        assert_eq!(report.last_entry_id, Some(3));
+        assert_eq!(report.checkpoints_checked, 1);
+        assert_eq!(report.last_valid_checkpoint, Some(1));
+    }
+
+    #[test]
+    fn edited_entry_breaks_its_hash() {
+        let mut entries = chain(3);
+        entries[1].actor_id = Some(8);
+
+        let report = run(entries, &[]);
+
+        assert_eq!(first_broken(&report), (2, BrokenReason::HashMismatch));
+        assert_eq!(report.entries_checked, 1);
+    }
+
+    #[test]
+    fn deleted_entry_breaks_the_next_link() {
+        let mut entries = chain(3);
+        entries.remove(1);
+
+        assert_eq!(
+            first_broken(&run(entries, &[])),
+            (3, BrokenReason::PrevHashMismatch)
+        );
+    }
+
+    #[test]
+    fn reordered_entries_break_the_chain() {
+        let mut entries = chain(3);
+        entries.swap(1, 2);
+        (entries[1].id, entries[2].id) = (2, 3);
+
+        assert_eq!(
+            first_broken(&run(entries, &[])),
+            (2, BrokenReason::PrevHashMismatch)
+        );
+    }
+
+    #[test]
+    fn rewritten_chain_is_caught_by_a_checkpoint() {
+        let mut entries = chain(3);
+        let checkpoints = [checkpoint(1, &entries[2])];
+        entries[0].actor_id = Some(8);
+        rehash(&mut entries);
+
+        assert_eq!(
+            first_broken(&run(entries, &checkpoints)),
+            (3, BrokenReason::CheckpointMismatch)
+        );
+    }
+
+    #[test]
+    fn truncated_tail_is_caught_by_a_checkpoint() {
+        let mut entries = chain(3);
+        let checkpoints = [checkpoint(1, &entries[2])];
+        entries.truncate(2);
+
+        assert_eq!(
+            first_broken(&run(entries, &checkpoints)),
+            (3, BrokenReason::MissingEntry)
+        );
+    }
+
+    #[test]
+    fn forged_checkpoint_signature_is_rejected() {
+        let entries = chain(3);
+        let mut forged = checkpoint(1, &entries[1]);
+        forged.hash = entries[2].hash.clone();
+
+        let report = run(entries, &[forged]);
+
+        assert_eq!(first_broken(&report), (2, BrokenReason::InvalidSignature));
+        assert_eq!(report.entries_checked, 0);
+    }
+}
@@ -0,0 +1,98 @@
+use sea_orm::ActiveValue::Set;
+use sea_orm::DbConn;
+use sea_orm::sqlx::types::chrono::Local;
+use std::sync::Arc;
+use validator::Validate;
+
+use super::password;
+use super::permissions::ADMIN_ROLE;
+use crate::audit::AuditContext;
+use crate::db::models::UserActiveModel;
+use crate::db::repositories::{CredentialRepository, RoleRepository, UserRepository};
+use crate::domain::User;
+use crate::error::{AppError, FieldError};
+use crate::redaction::Redacted;
+
+/// Creates the configured admin account so a fresh deployment has someone
+/// who can assign roles through the API.
+///
+/// This only runs while nobody holds the admin role. The role is granted
+/// to an account this step creates, or to an existing one only if its
+/// password is the configured one. An account someone else registered
+/// under the name stops startup instead of being promoted.
+pub async fn ensure_admin(
+    db: &DbConn,
+    username: &str,
+    email: &str,
+    admin_password: &str,
+) -> Result<(), AppError> {
+    let db = Arc::new(db.clone());
+    let roles = RoleRepository::new(db.clone());
+    let role = roles
+        .find_by_name(ADMIN_ROLE)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Role '{}' not found", ADMIN_ROLE)))?;
+
+    if roles.has_members(role.id).await? {
+        log::info!(
+            "A user already holds the '{}' role, skipping the bootstrap admin",
+            ADMIN_ROLE
+        );
+        return Ok(());
+    }
+
+    let users = UserRepository::new(db.clone());
+    let user = match users.find_by_username(username).await? {
+        Some(user) => {
+            let owned = match CredentialRepository::new(db)
+                .find_by_user_id(user.id)
+                .await?
+            {
+                Some(credential) => {
+                    password::verify_password(admin_password, &credential.password_hash).await?
+                }
+                None => false,
+            };
+            if !owned {
+                return Err(AppError::Conflict(FieldError {
+                    field: "username".into(),
+                    code: "bootstrap_admin".into(),
+                    message: format!(
+                        "User {} exists without the bootstrap admin password; refusing to grant '{}'",
+                        Redacted::identifier(username),
+                        ADMIN_ROLE
+                    ),
+                }));
+            }
+            user
+        }
+        None => {
+            User::new(username.to_string(), email.to_string()).validate()?;
+            let now = Local::now().naive_local();
+            users
+                .create(
+                    UserActiveModel {
+                        username: Set(username.to_string()),
+                        email: Set(email.to_string()),
+                        created_on: Set(now),
+                        updated_on: Set(now),
+                        ..Default::default()
+                    },
+                    Some(password::hash_password(admin_password).await?),
+                    &AuditContext::default(),
+                )
+                .await?
+        }
+    };
+
+    roles
+        .assign(user.id, role.id, Local::now().naive_local())
+        .await?;
+    log::info!(
+        "Granted '{}' role to bootstrap user '{}'",
+        ADMIN_ROLE,
+        Redacted::identifier(username)
+    );
+
+    Ok(())
+}
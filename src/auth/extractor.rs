@@ -0,0 +1,85 @@
+use actix_web::dev::Payload;
+use actix_web::http::header::AUTHORIZATION;
+use actix_web::{FromRequest, HttpMessage, HttpRequest, web};
+use futures_util::future::LocalBoxFuture;
+use std::sync::Arc;
+
+use super::JwtKeys;
+use crate::error::AppError;
+
+/// The caller identified by the registered [`PrincipalExtractor`].
+///
+/// Adding this as a handler argument makes the route require authentication.
+#[derive(Debug, Clone)]
+pub struct AuthenticatedUser {
+    pub id: i32,
+    pub username: String,
+}
+
+/// Identifies the caller of a request.
+///
+/// Register an implementation as `web::Data<dyn PrincipalExtractor>` to
+/// replace the default bearer token scheme, e.g. with API keys or mTLS.
+#[async_trait::async_trait(?Send)]
+pub trait PrincipalExtractor: Send + Sync {
+    async fn identify(&self, req: &HttpRequest) -> Result<AuthenticatedUser, AppError>;
+}
+
+/// Identifies callers by a JWT access token in `Authorization: Bearer`.
+pub struct BearerTokenExtractor;
+
+#[async_trait::async_trait(?Send)]
+impl PrincipalExtractor for BearerTokenExtractor {
+    async fn identify(&self, req: &HttpRequest) -> Result<AuthenticatedUser, AppError> {
+        let keys = req.app_data::<web::Data<JwtKeys>>().ok_or_else(|| {
+            log::error!("JwtKeys are not registered as app data");
+            AppError::InternalServerError
+        })?;
+
+        let token = req
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| AppError::Unauthorized("Missing bearer token".into()))?;
+
+        let claims = keys.verify_access_token(token.trim())?;
+        let id = claims
+            .sub
+            .parse()
+            .map_err(|_| AppError::Unauthorized("Invalid access token subject".into()))?;
+
+        Ok(AuthenticatedUser {
+            id,
+            username: claims.username,
+        })
+    }
+}
+
+impl AuthenticatedUser {
+    pub async fn identify(req: &HttpRequest) -> Result<Self, AppError> {
+        if let Some(user) = req.extensions().get::<AuthenticatedUser>() {
+            return Ok(user.clone());
+        }
+
+        let extractor: Arc<dyn PrincipalExtractor> =
+            match req.app_data::<web::Data<dyn PrincipalExtractor>>() {
+                Some(extractor) => extractor.clone().into_inner(),
+                None => Arc::new(BearerTokenExtractor),
+            };
+
+        let user = extractor.identify(req).await?;
+        req.extensions_mut().insert(user.clone());
+        Ok(user)
+    }
+}
+
+impl FromRequest for AuthenticatedUser {
+    type Error = AppError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let req = req.clone();
+        Box::pin(async move { AuthenticatedUser::identify(&req).await })
+    }
+}
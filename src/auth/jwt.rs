@@ -0,0 +1,138 @@
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use serde::{Deserialize, Serialize};
+
+use crate::config::AuthConfig;
+use crate::error::AppError;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    /// User id.
+    pub sub: String,
+    pub username: String,
+    pub iss: String,
+    pub iat: u64,
+    pub exp: u64,
+}
+
+pub struct JwtKeys {
+    encoding: EncodingKey,
+    decoding: DecodingKey,
+    validation: Validation,
+    issuer: String,
+    pub access_token_ttl_secs: u64,
+}
+
+impl JwtKeys {
+    pub fn new(config: &AuthConfig) -> Self {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.set_issuer(&[&config.jwt_issuer]);
+        validation.set_required_spec_claims(&["exp", "iss", "sub"]);
+
+        Self {
+            encoding: EncodingKey::from_secret(config.jwt_secret.expose().as_bytes()),
+            decoding: DecodingKey::from_secret(config.jwt_secret.expose().as_bytes()),
+            validation,
+            issuer: config.jwt_issuer.clone(),
+            access_token_ttl_secs: config.access_token_ttl_secs,
+        }
+    }
+
+    pub fn issue_access_token(&self, user_id: i32, username: &str) -> Result<String, AppError> {
+        let now = jsonwebtoken::get_current_timestamp();
+        let claims = Claims {
+            sub: user_id.to_string(),
+            username: username.to_string(),
+            iss: self.issuer.clone(),
+            iat: now,
+            exp: now + self.access_token_ttl_secs,
+        };
+
+        encode(&Header::new(Algorithm::HS256), &claims, &self.encoding).map_err(|err| {
+            log::error!("Failed to sign access token: {}", err);
+            AppError::InternalServerError
+        })
+    }
+
+    pub fn verify_access_token(&self, token: &str) -> Result<Claims, AppError> {
+        decode::<Claims>(token, &self.decoding, &self.validation)
+            .map(|data| data.claims)
+            .map_err(|err| AppError::Unauthorized(format!("Invalid access token: {}", err)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::redaction::Redacted;
+
+    fn keys(secret: &str, issuer: &str) -> JwtKeys {
+        JwtKeys::new(&AuthConfig {
+            jwt_secret: Redacted::new(secret.to_string()),
+            jwt_issuer: issuer.to_string(),
+            access_token_ttl_secs: 900,
+            refresh_token_ttl_secs: 3600,
+            bootstrap_admin: None,
+            bootstrap_admin_email: None,
+            bootstrap_admin_password: None,
+        })
+    }
+
+    fn sign(keys: &JwtKeys, iat: u64, exp: u64) -> String {
+        let claims = Claims {
+            sub: "7".to_string(),
+            username: "ada".to_string(),
+            iss: keys.issuer.clone(),
+            iat,
+            exp,
+        };
+        encode(&Header::new(Algorithm::HS256), &claims, &keys.encoding).unwrap()
+    }
+
+    #[test]
+    fn issued_tokens_verify() {
+        let keys = keys("secret", "issuer");
+
+        let claims = keys
+            .verify_access_token(&keys.issue_access_token(7, "ada").unwrap())
+            .unwrap();
+
+        assert_eq!(claims.sub, "7");
+        assert_eq!(claims.username, "ada");
+        assert_eq!(claims.iss, "issuer");
+        assert_eq!(claims.exp - claims.iat, 900);
+    }
+
+    #[test]
+    fn expired_tokens_are_rejected() {
+        let keys = keys("secret", "issuer");
+        let now = jsonwebtoken::get_current_timestamp();
+
+        // Validation allows 60 seconds of clock skew.
+        assert!(
+            keys.verify_access_token(&sign(&keys, now - 900, now - 30))
+                .is_ok()
+        );
+        let err = keys
+            .verify_access_token(&sign(&keys, now - 900, now - 120))
+            .unwrap_err();
+
+        assert!(
+            matches!(err, AppError::Unauthorized(message) if message.contains("ExpiredSignature"))
+        );
+    }
+
+    #[test]
+    fn tokens_from_another_secret_or_issuer_are_rejected() {
+        let token = keys("secret", "issuer")
+            .issue_access_token(7, "ada")
+            .unwrap();
+
+        assert!(keys("other", "issuer").verify_access_token(&token).is_err());
+        assert!(keys("secret", "other").verify_access_token(&token).is_err());
+        assert!(
+            keys("secret", "issuer")
+                .verify_access_token(&format!("{}x", token))
+                .is_err()
+        );
+    }
+}
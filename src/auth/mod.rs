@@ -0,0 +1,13 @@
+pub mod bootstrap;
+mod extractor;
+pub mod jwt;
+pub mod password;
+pub mod permissions;
+mod principal;
+pub mod refresh_token;
+mod require_permission;
+
+pub use extractor::{AuthenticatedUser, BearerTokenExtractor, PrincipalExtractor};
+pub use jwt::{Claims, JwtKeys};
+pub use principal::Principal;
+pub use require_permission::RequirePermission;
@@ -0,0 +1,67 @@
+use actix_web::error::BlockingError;
+use actix_web::web;
+use argon2::Argon2;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use rand::rngs::OsRng;
+use std::sync::OnceLock;
+
+use crate::error::AppError;
+
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+
+/// Hashes a password with argon2id using the crate's default parameters,
+/// on the blocking thread pool so it does not stall the worker.
+pub async fn hash_password(password: &str) -> Result<String, AppError> {
+    let password = password.to_string();
+    web::block(move || hash(&password))
+        .await
+        .map_err(blocking_failed)?
+}
+
+pub async fn verify_password(password: &str, password_hash: &str) -> Result<bool, AppError> {
+    let (password, password_hash) = (password.to_string(), password_hash.to_string());
+    web::block(move || verify(&password, &password_hash))
+        .await
+        .map_err(blocking_failed)
+}
+
+/// Burns the same time as a real verification so that unknown usernames
+/// cannot be told apart from wrong passwords by response latency.
+pub async fn verify_dummy_password(password: &str) -> Result<(), AppError> {
+    static DUMMY_HASH: OnceLock<String> = OnceLock::new();
+    let password = password.to_string();
+    web::block(move || {
+        let hash = DUMMY_HASH.get_or_init(|| hash("dummy-password").unwrap_or_default());
+        verify(&password, hash);
+    })
+    .await
+    .map_err(blocking_failed)
+}
+
+fn hash(password: &str) -> Result<String, AppError> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|err| {
+            log::error!("Password hashing failed: {}", err);
+            AppError::InternalServerError
+        })
+}
+
+fn verify(password: &str, password_hash: &str) -> bool {
+    match PasswordHash::new(password_hash) {
+        Ok(parsed) => Argon2::default()
+            .verify_password(password.as_bytes(), &parsed)
+            .is_ok(),
+        Err(err) => {
+            log::error!("Stored password hash is malformed: {}", err);
+            false
+        }
+    }
+}
+
+fn blocking_failed(err: BlockingError) -> AppError {
+    log::error!("Password hashing task failed: {}", err);
+    AppError::InternalServerError
+}
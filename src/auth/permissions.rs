@@ -0,0 +1,9 @@
+pub const USERS_READ: &str = "users:read";
+pub const USERS_UPDATE: &str = "users:update";
+pub const USERS_DELETE: &str = "users:delete";
+pub const USERS_RESTORE: &str = "users:restore";
+pub const ROLES_ASSIGN: &str = "roles:assign";
+pub const AUDIT_READ: &str = "audit:read";
+pub const WEBHOOKS_MANAGE: &str = "webhooks:manage";
+
+pub const ADMIN_ROLE: &str = "admin";
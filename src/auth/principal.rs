@@ -0,0 +1,122 @@
+use actix_web::dev::Payload;
+use actix_web::{FromRequest, HttpMessage, HttpRequest, web};
+use futures_util::future::LocalBoxFuture;
+use sea_orm::DbConn;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use super::AuthenticatedUser;
+use crate::db::repositories::RoleRepository;
+use crate::error::AppError;
+
+/// An authenticated caller together with its roles and permissions.
+#[derive(Debug, Clone)]
+pub struct Principal {
+    pub user_id: i32,
+    pub username: String,
+    pub roles: BTreeSet<String>,
+    pub permissions: BTreeSet<String>,
+}
+
+impl Principal {
+    pub fn has_permission(&self, permission: &str) -> bool {
+        self.permissions.contains(permission)
+    }
+
+    /// Callers may always act on their own record; anything else requires
+    /// `permission`.
+    pub fn ensure_self_or(&self, user_id: i32, permission: &str) -> Result<(), AppError> {
+        if self.user_id == user_id || self.has_permission(permission) {
+            Ok(())
+        } else {
+            Err(forbidden(permission))
+        }
+    }
+
+    pub fn ensure(&self, permission: &str) -> Result<(), AppError> {
+        if self.has_permission(permission) {
+            Ok(())
+        } else {
+            Err(forbidden(permission))
+        }
+    }
+
+    pub async fn resolve(req: &HttpRequest) -> Result<Self, AppError> {
+        if let Some(principal) = req.extensions().get::<Principal>() {
+            return Ok(principal.clone());
+        }
+
+        let user = AuthenticatedUser::identify(req).await?;
+        let db = req.app_data::<web::Data<DbConn>>().ok_or_else(|| {
+            log::error!("DbConn is not registered as app data");
+            AppError::InternalServerError
+        })?;
+
+        let grants = RoleRepository::new(Arc::new(db.get_ref().clone()))
+            .find_grants(user.id)
+            .await?;
+
+        let principal = Principal {
+            user_id: user.id,
+            username: user.username,
+            roles: grants.roles,
+            permissions: grants.permissions,
+        };
+        req.extensions_mut().insert(principal.clone());
+        Ok(principal)
+    }
+}
+
+fn forbidden(permission: &str) -> AppError {
+    AppError::Forbidden(format!("Missing permission '{}'", permission))
+}
+
+impl FromRequest for Principal {
+    type Error = AppError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let req = req.clone();
+        Box::pin(async move { Principal::resolve(&req).await })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::permissions::{USERS_READ, USERS_UPDATE};
+
+    fn principal(permissions: &[&str]) -> Principal {
+        Principal {
+            user_id: 7,
+            username: "ada".to_string(),
+            roles: BTreeSet::new(),
+            permissions: permissions.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn callers_may_act_on_themselves_without_the_permission() {
+        assert!(principal(&[]).ensure_self_or(7, USERS_UPDATE).is_ok());
+    }
+
+    #[test]
+    fn acting_on_others_requires_the_permission() {
+        let err = principal(&[USERS_READ])
+            .ensure_self_or(8, USERS_UPDATE)
+            .unwrap_err();
+
+        assert!(matches!(err, AppError::Forbidden(message) if message.contains(USERS_UPDATE)));
+        assert!(
+            principal(&[USERS_UPDATE])
+                .ensure_self_or(8, USERS_UPDATE)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn ensure_ignores_ownership() {
+        assert!(principal(&[]).ensure(USERS_READ).is_err());
+        assert!(principal(&[USERS_READ]).ensure(USERS_READ).is_ok());
+    }
+}
@@ -0,0 +1,182 @@
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use log::warn;
+use rand::RngCore;
+use rand::rngs::OsRng;
+use sea_orm::ActiveValue::Set;
+use sea_orm::prelude::{DateTime, Uuid};
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+
+use crate::db::models::{RefreshTokenActiveModel, RefreshTokenModel};
+use crate::db::repositories::RefreshTokenRepository;
+use crate::error::AppError;
+
+/// Generates an opaque, URL-safe refresh token with 256 bits of entropy.
+pub fn generate() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// Refresh tokens are stored as SHA-256 digests so a database leak does not
+/// expose usable tokens.
+pub fn hash(token: &str) -> String {
+    let digest = Sha256::digest(token.as_bytes());
+    URL_SAFE_NO_PAD.encode(digest)
+}
+
+/// Builds a new refresh token in `family_id`, returning the raw token for
+/// the client alongside the row that stores its hash.
+pub fn new_token(
+    user_id: i32,
+    family_id: Uuid,
+    ttl_secs: u64,
+    now: DateTime,
+) -> (String, RefreshTokenActiveModel) {
+    let raw_token = generate();
+
+    let model = RefreshTokenActiveModel {
+        user_id: Set(user_id),
+        token_hash: Set(hash(&raw_token)),
+        family_id: Set(family_id),
+        created_on: Set(now),
+        expires_on: Set(now + Duration::from_secs(ttl_secs)),
+        ..Default::default()
+    };
+
+    (raw_token, model)
+}
+
+/// Looks up the token a client presented. A token that was already rotated
+/// is a replay, so its whole family is revoked.
+pub async fn redeem(
+    tokens: &RefreshTokenRepository,
+    raw_token: &str,
+    now: DateTime,
+) -> Result<RefreshTokenModel, AppError> {
+    let current = tokens
+        .find_by_hash(&hash(raw_token))
+        .await?
+        .ok_or_else(|| AppError::Unauthorized("Invalid refresh token".into()))?;
+
+    if current.revoked_on.is_some() {
+        return Err(reuse_detected(tokens, &current, now).await?);
+    }
+
+    if current.expires_on <= now {
+        return Err(AppError::Unauthorized("Refresh token expired".into()));
+    }
+
+    Ok(current)
+}
+
+/// Replaces `current` with a new token in the same family and returns the
+/// raw new token.
+pub async fn rotate(
+    tokens: &RefreshTokenRepository,
+    current: &RefreshTokenModel,
+    ttl_secs: u64,
+    now: DateTime,
+) -> Result<String, AppError> {
+    let (raw_token, next) = new_token(current.user_id, current.family_id, ttl_secs, now);
+
+    // A concurrent refresh with the same token wins the race; the loser is
+    // treated as a replay.
+    if tokens.rotate(current, next, now).await?.is_none() {
+        return Err(reuse_detected(tokens, current, now).await?);
+    }
+
+    Ok(raw_token)
+}
+
+async fn reuse_detected(
+    tokens: &RefreshTokenRepository,
+    current: &RefreshTokenModel,
+    now: DateTime,
+) -> Result<AppError, AppError> {
+    let revoked = tokens.revoke_family(current.family_id, now).await?;
+    warn!(
+        "Refresh token reuse detected for user with ID {}, revoked {} token(s) in family {}",
+        current.user_id, revoked, current.family_id
+    );
+    Ok(AppError::Unauthorized(
+        "Refresh token reuse detected".into(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_are_random_and_stored_hashed() {
+        let token = generate();
+
+        assert_eq!(token.len(), 43);
+        assert_ne!(token, generate());
+        assert_eq!(hash(&token), hash(&token));
+        assert_ne!(hash(&token), token);
+    }
+
+    /// Runs against the database in `TEST_DATABASE_URL`, which it migrates:
+    /// `TEST_DATABASE_URL=postgres://... cargo test -- --ignored`.
+    #[actix_web::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn rotation_replaces_tokens_and_reuse_revokes_the_family() {
+        use sea_orm::sqlx::types::chrono::Local;
+        use sea_orm_migration::MigratorTrait;
+        use std::sync::Arc;
+
+        use crate::audit::AuditContext;
+        use crate::db::migrations::Migrator;
+        use crate::db::models::UserActiveModel;
+        use crate::db::repositories::UserRepository;
+
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL is not set");
+        let db = Arc::new(sea_orm::Database::connect(url).await.unwrap());
+        Migrator::up(db.as_ref(), None).await.unwrap();
+        let users = UserRepository::new(db.clone());
+        let tokens = RefreshTokenRepository::new(db);
+        let now = Local::now().naive_local();
+        let name = format!("rotation-{}", Uuid::new_v4().simple());
+        let user = users
+            .create(
+                UserActiveModel {
+                    username: Set(name.clone()),
+                    email: Set(format!("{}@example.com", name)),
+                    created_on: Set(now),
+                    updated_on: Set(now),
+                    ..Default::default()
+                },
+                None,
+                &AuditContext::default(),
+            )
+            .await
+            .unwrap();
+        let (first, model) = new_token(user.id, Uuid::new_v4(), 3600, now);
+        tokens.create(model).await.unwrap();
+
+        let current = redeem(&tokens, &first, now).await.unwrap();
+        let second = rotate(&tokens, &current, 3600, now).await.unwrap();
+        let current = redeem(&tokens, &second, now).await.unwrap();
+        assert_eq!(current.user_id, user.id);
+
+        let replay = redeem(&tokens, &first, now).await.unwrap_err();
+        assert!(matches!(replay, AppError::Unauthorized(message) if message.contains("reuse")));
+        let second = tokens.find_by_hash(&hash(&second)).await.unwrap().unwrap();
+        assert!(second.revoked_on.is_some());
+        assert!(redeem(&tokens, &generate(), now).await.is_err());
+
+        let (expired, model) = new_token(user.id, Uuid::new_v4(), 60, now);
+        tokens.create(model).await.unwrap();
+        let later = now + Duration::from_secs(61);
+        let err = redeem(&tokens, &expired, later).await.unwrap_err();
+        assert!(matches!(err, AppError::Unauthorized(message) if message.contains("expired")));
+
+        users
+            .delete(user.id, &AuditContext::default())
+            .await
+            .unwrap();
+    }
+}
@@ -1,35 +1,955 @@
+use actix_web::http::Method;
+use actix_web::http::header::{HeaderName, HeaderValue};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
 use serde::Deserialize;
-use std::env;
+use std::str::FromStr;
 
-#[derive(Debug, Clone, Deserialize)]
+use super::loader::{ConfigIssues, Reader};
+use crate::auth::password::MIN_PASSWORD_LENGTH;
+use crate::redaction::Redacted;
+
+/// Placeholder secret shipped in `.env` for local development.
+const DEV_JWT_SECRET: &str = "change-me-in-production";
+const MIN_PROD_JWT_SECRET_LENGTH: usize = 32;
+/// Profiles for a developer machine; every other profile must be given
+/// real secrets.
+const LOCAL_PROFILES: &[&str] = &["dev", "test"];
+
+#[derive(Debug, Clone)]
 pub struct AppConfig {
+    /// Profile the configuration was loaded for, e.g. `dev` or `prod`.
+    pub profile: String,
     pub server: ServerConfig,
     pub database: DatabaseConfig,
+    pub pagination: PaginationConfig,
+    pub auth: AuthConfig,
+    pub logging: LoggingConfig,
+    pub cors: CorsConfig,
+    pub security_headers: SecurityHeadersConfig,
+    pub rate_limit: RateLimitConfig,
+    pub health: HealthConfig,
+    pub tracing: TracingConfig,
+    pub shutdown: ShutdownConfig,
+    pub audit: AuditConfig,
+    pub outbox: OutboxConfig,
+    pub webhooks: WebhooksConfig,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone)]
 pub struct ServerConfig {
     pub host: String,
     pub port: u16,
+    /// Worker threads; defaults to the number of physical cores.
+    pub workers: Option<usize>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone)]
 pub struct DatabaseConfig {
     pub url: String,
+    /// Server-side `statement_timeout`; unlimited when absent.
+    pub statement_timeout_ms: Option<u64>,
+    /// Logs every SQL statement at `sqlx_log_level`.
+    pub sqlx_logging: bool,
+    pub sqlx_log_level: String,
+    pub pool: PoolConfig,
+    pub retry: RetryConfig,
 }
 
-impl AppConfig {
-    pub fn from_env() -> Self {
-        let host = env::var("SERVER_HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
-        let port = env::var("SERVER_PORT")
-            .unwrap_or_else(|_| "8000".to_string())
-            .parse()
-            .expect("SERVER_PORT must be a number");
-        let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+#[derive(Debug, Clone)]
+pub struct PoolConfig {
+    pub max_connections: u32,
+    pub min_connections: u32,
+    pub connect_timeout_secs: u64,
+    pub acquire_timeout_secs: u64,
+    pub idle_timeout_secs: u64,
+    pub max_lifetime_secs: u64,
+}
+
+/// Exponential backoff for the initial connection, so the API survives a
+/// database that starts after it.
+#[derive(Debug, Clone)]
+pub struct RetryConfig {
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+    /// Gives up once this much time has passed since the first attempt.
+    pub deadline_secs: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct HealthConfig {
+    /// Upper bound for each readiness check.
+    pub check_timeout_ms: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ShutdownConfig {
+    /// Upper bound for draining in-flight requests, and then background
+    /// tasks with whatever time is left.
+    pub timeout_secs: u64,
+    /// Time between failing readiness and closing the listeners, so load
+    /// balancers stop routing new traffic first.
+    pub drain_delay_secs: u64,
+}
+
+/// Ed25519 keys are base64-encoded 32-byte values.
+#[derive(Debug, Clone)]
+pub struct AuditConfig {
+    /// Seed of the key signing checkpoints; no checkpoints are written
+    /// without it.
+    pub signing_key: Option<Redacted<String>>,
+    /// Public key checking checkpoint signatures; derived from
+    /// `signing_key` when absent, so verifiers need not hold the secret.
+    pub verifying_key: Option<String>,
+    pub checkpoint_interval_secs: u64,
+}
+
+/// Delivery of domain events from the outbox. Events are always written;
+/// these settings only control the relay.
+#[derive(Debug, Clone)]
+pub struct OutboxConfig {
+    /// Runs the relay in this process.
+    pub enabled: bool,
+    pub sinks: Vec<OutboxSinkKind>,
+    pub poll_interval_ms: u64,
+    pub batch_size: u64,
+    /// How long a claimed event stays hidden from other relays; renewed
+    /// as the delivery of each event starts.
+    pub lease_secs: u64,
+    /// Attempts before an event is marked `failed`.
+    pub max_attempts: u32,
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutboxSinkKind {
+    /// Logs each event under the `events` target.
+    Log,
+    /// Queues a delivery for each matching webhook subscription.
+    Webhooks,
+}
+
+/// Sending queued webhook deliveries.
+#[derive(Debug, Clone)]
+pub struct WebhooksConfig {
+    /// Runs the dispatcher in this process.
+    pub enabled: bool,
+    /// Upper bound for one request, including reading the response.
+    pub timeout_ms: u64,
+    pub poll_interval_ms: u64,
+    /// Deliveries sent concurrently per poll.
+    pub batch_size: u64,
+    /// How long a claimed delivery stays hidden from other dispatchers;
+    /// must exceed `timeout_ms`.
+    pub lease_secs: u64,
+    /// Failed attempts before a delivery is marked `dead`.
+    pub max_attempts: u32,
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+    /// Allows receivers on loopback, private and link-local addresses,
+    /// e.g. for local development.
+    pub allow_private_networks: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct TracingConfig {
+    pub exporter: TraceExporter,
+    /// OTLP/HTTP traces endpoint, used when `exporter` is `otlp`.
+    pub otlp_endpoint: String,
+    pub service_name: String,
+    /// Fraction of new traces sampled; incoming sampling decisions win.
+    pub sample_ratio: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TraceExporter {
+    /// Spans are created for propagation but not exported.
+    None,
+    Stdout,
+    Otlp,
+}
+
+#[derive(Debug, Clone)]
+pub struct PaginationConfig {
+    pub default_limit: u64,
+    pub max_limit: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct AuthConfig {
+    pub jwt_secret: Redacted<String>,
+    pub jwt_issuer: String,
+    pub access_token_ttl_secs: u64,
+    pub refresh_token_ttl_secs: u64,
+    /// Username of an admin account created at startup while nobody holds
+    /// the admin role; requires the email and password below.
+    pub bootstrap_admin: Option<String>,
+    pub bootstrap_admin_email: Option<String>,
+    pub bootstrap_admin_password: Option<Redacted<String>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LoggingConfig {
+    /// `env_logger` filter, e.g. `info` or `info,sqlx=warn`.
+    pub level: String,
+    pub format: LogFormat,
+    pub redaction: RedactionConfig,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    Text,
+    /// One JSON object per line, for log aggregation.
+    Json,
+}
+
+/// How personal data is rendered in log output and debug dumps.
+#[derive(Debug, Clone)]
+pub struct RedactionConfig {
+    pub enabled: bool,
+    pub email: RedactionRule,
+    pub phone: RedactionRule,
+    pub name: RedactionRule,
+    /// Usernames and other identifiers of a person.
+    pub identifier: RedactionRule,
+    /// Mixed into hashed values so they cannot be reversed by hashing
+    /// guessed inputs.
+    pub hash_key: String,
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            email: RedactionRule::Mask,
+            phone: RedactionRule::Drop,
+            name: RedactionRule::Mask,
+            identifier: RedactionRule::Hash,
+            hash_key: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RedactionRule {
+    Keep,
+    /// Keeps the first character, e.g. `j***@example.com`.
+    Mask,
+    /// Replaces the value with a short keyed SHA-256 digest, so records
+    /// about the same value can still be correlated.
+    Hash,
+    Drop,
+}
+
+#[derive(Debug, Clone)]
+pub struct CorsConfig {
+    /// Allowed origins; `*` allows any origin and one `*` inside an origin
+    /// matches host labels or a port, e.g. `https://*.example.com`.
+    pub allowed_origins: Vec<String>,
+    pub allowed_methods: Vec<String>,
+    pub allowed_headers: Vec<String>,
+    /// Response headers readable by browser scripts.
+    pub exposed_headers: Vec<String>,
+    pub allow_credentials: bool,
+    pub max_age_secs: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct SecurityHeadersConfig {
+    pub enabled: bool,
+    /// `Strict-Transport-Security` max-age; 0 omits the header.
+    pub hsts_max_age_secs: u64,
+    pub hsts_include_subdomains: bool,
+    pub referrer_policy: String,
+    /// Policy sent with HTML responses that do not set their own.
+    pub content_security_policy: String,
+    /// `frame-ancestors` directive added to every response's policy.
+    pub frame_ancestors: String,
+    /// Per-scope replacements, matched by the longest path prefix.
+    pub overrides: Vec<SecurityHeadersOverride>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SecurityHeadersOverride {
+    /// Path prefix, e.g. `/api/docs`.
+    pub path: String,
+    pub referrer_policy: Option<String>,
+    pub content_security_policy: Option<String>,
+    pub frame_ancestors: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    pub backend: RateLimitBackend,
+    pub requests_per_minute: u32,
+    pub burst: u32,
+    /// Per-route limits overriding the defaults above.
+    pub routes: Vec<RouteRateLimit>,
+    /// Header carrying an API key to limit by; only keys listed in
+    /// `api_keys` get their own bucket.
+    pub api_key_header: Option<String>,
+    pub api_keys: Vec<Redacted<String>>,
+    /// Takes the client IP from `Forwarded`/`X-Forwarded-For`; only safe
+    /// behind a proxy that overwrites those headers.
+    pub trust_forwarded_for: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RateLimitBackend {
+    Memory,
+    Postgres,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RouteRateLimit {
+    /// Route pattern as registered, e.g. `/api/users/{id}`.
+    pub path: String,
+    /// Limits every method when absent.
+    pub method: Option<String>,
+    pub requests_per_minute: u32,
+    pub burst: u32,
+}
 
+impl AppConfig {
+    /// Reads every key from the merged layers, recording type errors
+    /// instead of stopping at the first one.
+    pub(super) fn read(profile: &str, reader: &mut Reader) -> Self {
         AppConfig {
-            server: ServerConfig { host, port },
-            database: DatabaseConfig { url: database_url },
+            profile: profile.to_string(),
+            server: ServerConfig {
+                host: reader.get_or("server.host", "127.0.0.1".to_string()),
+                port: reader.get_or("server.port", 8000),
+                workers: reader.get_opt("server.workers"),
+            },
+            database: DatabaseConfig {
+                url: reader.get_or("database.url", String::new()),
+                statement_timeout_ms: reader.get_opt("database.statement_timeout_ms"),
+                sqlx_logging: reader.get_or("database.sqlx_logging", false),
+                sqlx_log_level: reader.get_or("database.sqlx_log_level", "debug".to_string()),
+                pool: PoolConfig {
+                    max_connections: reader.get_or("database.pool.max_connections", 10),
+                    min_connections: reader.get_or("database.pool.min_connections", 1),
+                    connect_timeout_secs: reader.get_or("database.pool.connect_timeout_secs", 10),
+                    acquire_timeout_secs: reader.get_or("database.pool.acquire_timeout_secs", 30),
+                    idle_timeout_secs: reader.get_or("database.pool.idle_timeout_secs", 600),
+                    max_lifetime_secs: reader.get_or("database.pool.max_lifetime_secs", 1800),
+                },
+                retry: RetryConfig {
+                    initial_backoff_ms: reader.get_or("database.retry.initial_backoff_ms", 500),
+                    max_backoff_ms: reader.get_or("database.retry.max_backoff_ms", 10_000),
+                    deadline_secs: reader.get_or("database.retry.deadline_secs", 60),
+                },
+            },
+            pagination: PaginationConfig {
+                default_limit: reader.get_or("pagination.default_limit", 20),
+                max_limit: reader.get_or("pagination.max_limit", 100),
+            },
+            auth: AuthConfig {
+                jwt_secret: reader.get_or("auth.jwt_secret", Redacted::new(String::new())),
+                jwt_issuer: reader.get_or("auth.jwt_issuer", "rust-actix-seaorm".to_string()),
+                access_token_ttl_secs: reader.get_or("auth.access_token_ttl_secs", 900),
+                refresh_token_ttl_secs: reader.get_or("auth.refresh_token_ttl_secs", 1_209_600),
+                bootstrap_admin: reader.get_opt("auth.bootstrap_admin"),
+                bootstrap_admin_email: reader.get_opt("auth.bootstrap_admin_email"),
+                bootstrap_admin_password: reader.get_opt("auth.bootstrap_admin_password"),
+            },
+            logging: LoggingConfig {
+                level: reader.get_or("logging.level", "info".to_string()),
+                format: reader.get_or("logging.format", LogFormat::Text),
+                redaction: {
+                    let defaults = RedactionConfig::default();
+                    RedactionConfig {
+                        enabled: reader.get_or("logging.redaction.enabled", defaults.enabled),
+                        email: reader.get_or("logging.redaction.email", defaults.email),
+                        phone: reader.get_or("logging.redaction.phone", defaults.phone),
+                        name: reader.get_or("logging.redaction.name", defaults.name),
+                        identifier: reader
+                            .get_or("logging.redaction.identifier", defaults.identifier),
+                        hash_key: reader.get_or("logging.redaction.hash_key", defaults.hash_key),
+                    }
+                },
+            },
+            cors: CorsConfig {
+                allowed_origins: reader.get_or("cors.allowed_origins", Vec::new()),
+                allowed_methods: reader.get_or(
+                    "cors.allowed_methods",
+                    ["GET", "POST", "PUT", "PATCH", "DELETE"]
+                        .map(String::from)
+                        .to_vec(),
+                ),
+                allowed_headers: reader.get_or(
+                    "cors.allowed_headers",
+                    ["authorization", "content-type", "if-match", "if-none-match"]
+                        .map(String::from)
+                        .to_vec(),
+                ),
+                exposed_headers: reader.get_or(
+                    "cors.exposed_headers",
+                    [
+                        "x-request-id",
+                        "etag",
+                        "location",
+                        "retry-after",
+                        "ratelimit-limit",
+                        "ratelimit-remaining",
+                        "ratelimit-reset",
+                        "ratelimit-policy",
+                    ]
+                    .map(String::from)
+                    .to_vec(),
+                ),
+                allow_credentials: reader.get_or("cors.allow_credentials", false),
+                max_age_secs: reader.get_or("cors.max_age_secs", 3600),
+            },
+            security_headers: SecurityHeadersConfig {
+                enabled: reader.get_or("security_headers.enabled", true),
+                hsts_max_age_secs: reader.get_or("security_headers.hsts_max_age_secs", 31_536_000),
+                hsts_include_subdomains: reader
+                    .get_or("security_headers.hsts_include_subdomains", false),
+                referrer_policy: reader
+                    .get_or("security_headers.referrer_policy", "no-referrer".into()),
+                content_security_policy: reader.get_or(
+                    "security_headers.content_security_policy",
+                    "default-src 'none'".into(),
+                ),
+                frame_ancestors: reader.get_or("security_headers.frame_ancestors", "'none'".into()),
+                overrides: reader.get_or("security_headers.overrides", Vec::new()),
+            },
+            rate_limit: RateLimitConfig {
+                enabled: reader.get_or("rate_limit.enabled", false),
+                backend: reader.get_or("rate_limit.backend", RateLimitBackend::Memory),
+                requests_per_minute: reader.get_or("rate_limit.requests_per_minute", 120),
+                burst: reader.get_or("rate_limit.burst", 30),
+                routes: reader.get_or("rate_limit.routes", Vec::new()),
+                api_key_header: reader.get_opt("rate_limit.api_key_header"),
+                api_keys: reader.get_or("rate_limit.api_keys", Vec::new()),
+                trust_forwarded_for: reader.get_or("rate_limit.trust_forwarded_for", false),
+            },
+            health: HealthConfig {
+                check_timeout_ms: reader.get_or("health.check_timeout_ms", 1000),
+            },
+            tracing: TracingConfig {
+                exporter: reader.get_or("tracing.exporter", TraceExporter::None),
+                otlp_endpoint: reader.get_or(
+                    "tracing.otlp_endpoint",
+                    "http://localhost:4318/v1/traces".to_string(),
+                ),
+                service_name: reader
+                    .get_or("tracing.service_name", "rust-actix-seaorm".to_string()),
+                sample_ratio: reader.get_or("tracing.sample_ratio", 1.0),
+            },
+            shutdown: ShutdownConfig {
+                timeout_secs: reader.get_or("shutdown.timeout_secs", 30),
+                drain_delay_secs: reader.get_or("shutdown.drain_delay_secs", 0),
+            },
+            audit: AuditConfig {
+                signing_key: reader.get_opt("audit.signing_key"),
+                verifying_key: reader.get_opt("audit.verifying_key"),
+                checkpoint_interval_secs: reader.get_or("audit.checkpoint_interval_secs", 3600),
+            },
+            outbox: OutboxConfig {
+                enabled: reader.get_or("outbox.enabled", true),
+                sinks: reader.get_or(
+                    "outbox.sinks",
+                    vec![OutboxSinkKind::Log, OutboxSinkKind::Webhooks],
+                ),
+                poll_interval_ms: reader.get_or("outbox.poll_interval_ms", 1000),
+                batch_size: reader.get_or("outbox.batch_size", 100),
+                lease_secs: reader.get_or("outbox.lease_secs", 60),
+                max_attempts: reader.get_or("outbox.max_attempts", 10),
+                initial_backoff_ms: reader.get_or("outbox.initial_backoff_ms", 1000),
+                max_backoff_ms: reader.get_or("outbox.max_backoff_ms", 300_000),
+            },
+            webhooks: WebhooksConfig {
+                enabled: reader.get_or("webhooks.enabled", true),
+                timeout_ms: reader.get_or("webhooks.timeout_ms", 5000),
+                poll_interval_ms: reader.get_or("webhooks.poll_interval_ms", 1000),
+                batch_size: reader.get_or("webhooks.batch_size", 20),
+                lease_secs: reader.get_or("webhooks.lease_secs", 60),
+                max_attempts: reader.get_or("webhooks.max_attempts", 8),
+                initial_backoff_ms: reader.get_or("webhooks.initial_backoff_ms", 10_000),
+                max_backoff_ms: reader.get_or("webhooks.max_backoff_ms", 3_600_000),
+                allow_private_networks: reader.get_or("webhooks.allow_private_networks", false),
+            },
+        }
+    }
+
+    /// Semantic checks across keys; every violation is recorded.
+    pub(super) fn validate(&self, issues: &mut ConfigIssues) {
+        let prod = self.profile == "prod";
+        let local = LOCAL_PROFILES.contains(&self.profile.as_str());
+
+        if self.server.host.trim().is_empty() {
+            issues.add("server.host", "must not be empty");
+        }
+        if self.server.port == 0 {
+            issues.add("server.port", "must be between 1 and 65535");
+        }
+        if self.server.workers == Some(0) {
+            issues.add("server.workers", "must be at least 1");
+        }
+
+        if self.database.url.is_empty() {
+            issues.add(
+                "database.url",
+                "is required (set DATABASE_URL or APP__DATABASE__URL)",
+            );
+        } else if !self.database.url.starts_with("postgres://")
+            && !self.database.url.starts_with("postgresql://")
+        {
+            issues.add("database.url", "must be a postgres:// URL");
+        } else if self.database.statement_timeout_ms.is_some()
+            && self.database.url.contains("options=")
+        {
+            issues.add(
+                "database.statement_timeout_ms",
+                "cannot be combined with an `options` parameter in database.url",
+            );
+        }
+        if self.database.statement_timeout_ms == Some(0) {
+            issues.add(
+                "database.statement_timeout_ms",
+                "must be at least 1; omit it to disable the timeout",
+            );
+        }
+        if log::LevelFilter::from_str(&self.database.sqlx_log_level).is_err() {
+            issues.add(
+                "database.sqlx_log_level",
+                "must be one of off, error, warn, info, debug, trace",
+            );
+        }
+
+        let pool = &self.database.pool;
+        if pool.max_connections == 0 {
+            issues.add("database.pool.max_connections", "must be at least 1");
         }
+        if pool.min_connections > pool.max_connections {
+            issues.add(
+                "database.pool.min_connections",
+                "must not exceed database.pool.max_connections",
+            );
+        }
+        if pool.connect_timeout_secs == 0 {
+            issues.add("database.pool.connect_timeout_secs", "must be at least 1");
+        }
+        if pool.acquire_timeout_secs == 0 {
+            issues.add("database.pool.acquire_timeout_secs", "must be at least 1");
+        }
+
+        let retry = &self.database.retry;
+        if retry.initial_backoff_ms == 0 {
+            issues.add("database.retry.initial_backoff_ms", "must be at least 1");
+        }
+        if retry.max_backoff_ms < retry.initial_backoff_ms {
+            issues.add(
+                "database.retry.max_backoff_ms",
+                "must not be lower than database.retry.initial_backoff_ms",
+            );
+        }
+
+        if self.health.check_timeout_ms == 0 {
+            issues.add("health.check_timeout_ms", "must be at least 1");
+        }
+
+        if self.shutdown.timeout_secs == 0 {
+            issues.add("shutdown.timeout_secs", "must be at least 1");
+        }
+
+        if self.tracing.exporter == TraceExporter::Otlp
+            && !self.tracing.otlp_endpoint.starts_with("http://")
+            && !self.tracing.otlp_endpoint.starts_with("https://")
+        {
+            issues.add(
+                "tracing.otlp_endpoint",
+                "must be an http:// or https:// URL",
+            );
+        }
+        if self.tracing.service_name.trim().is_empty() {
+            issues.add("tracing.service_name", "must not be empty");
+        }
+        if !(0.0..=1.0).contains(&self.tracing.sample_ratio) {
+            issues.add("tracing.sample_ratio", "must be between 0.0 and 1.0");
+        }
+
+        if self.pagination.default_limit == 0 {
+            issues.add("pagination.default_limit", "must be at least 1");
+        }
+        if self.pagination.max_limit < self.pagination.default_limit {
+            issues.add(
+                "pagination.max_limit",
+                "must not be lower than pagination.default_limit",
+            );
+        }
+
+        if self.auth.jwt_secret.expose().is_empty() {
+            issues.add(
+                "auth.jwt_secret",
+                "is required (set JWT_SECRET or APP__AUTH__JWT_SECRET)",
+            );
+        } else if !local
+            && (self.auth.jwt_secret.expose() == DEV_JWT_SECRET
+                || self.auth.jwt_secret.expose().len() < MIN_PROD_JWT_SECRET_LENGTH)
+        {
+            issues.add(
+                "auth.jwt_secret",
+                format!(
+                    "must be a non-default secret of at least {} bytes outside the dev and test profiles",
+                    MIN_PROD_JWT_SECRET_LENGTH
+                ),
+            );
+        }
+        if self.auth.jwt_issuer.is_empty() {
+            issues.add("auth.jwt_issuer", "must not be empty");
+        }
+        if self.auth.access_token_ttl_secs == 0 {
+            issues.add("auth.access_token_ttl_secs", "must be at least 1");
+        }
+        if self.auth.refresh_token_ttl_secs <= self.auth.access_token_ttl_secs {
+            issues.add(
+                "auth.refresh_token_ttl_secs",
+                "must be greater than auth.access_token_ttl_secs",
+            );
+        }
+        if self.auth.bootstrap_admin.is_some() {
+            if self.auth.bootstrap_admin_email.is_none() {
+                issues.add(
+                    "auth.bootstrap_admin_email",
+                    "is required with auth.bootstrap_admin",
+                );
+            }
+            match &self.auth.bootstrap_admin_password {
+                None => issues.add(
+                    "auth.bootstrap_admin_password",
+                    "is required with auth.bootstrap_admin",
+                ),
+                Some(password) if password.expose().chars().count() < MIN_PASSWORD_LENGTH => issues
+                    .add(
+                        "auth.bootstrap_admin_password",
+                        format!("must be at least {} characters", MIN_PASSWORD_LENGTH),
+                    ),
+                Some(_) => {}
+            }
+        } else if self.auth.bootstrap_admin_email.is_some()
+            || self.auth.bootstrap_admin_password.is_some()
+        {
+            issues.add(
+                "auth.bootstrap_admin",
+                "is required with auth.bootstrap_admin_email and auth.bootstrap_admin_password",
+            );
+        }
+
+        if !is_valid_log_filter(&self.logging.level) {
+            issues.add(
+                "logging.level",
+                "must be a log filter such as `info` or `info,sqlx=warn`",
+            );
+        }
+        if !local && self.logging.redaction.hash_key.is_empty() {
+            issues.add(
+                "logging.redaction.hash_key",
+                "is required outside the dev and test profiles (set REDACTION_HASH_KEY)",
+            );
+        }
+
+        for origin in &self.cors.allowed_origins {
+            if origin != "*" && !origin.starts_with("http://") && !origin.starts_with("https://") {
+                issues.add(
+                    "cors.allowed_origins",
+                    format!("'{}' must be `*` or start with http:// or https://", origin),
+                );
+            } else if origin != "*" && origin.matches('*').count() > 1 {
+                issues.add(
+                    "cors.allowed_origins",
+                    format!("'{}' may contain at most one `*`", origin),
+                );
+            }
+        }
+        let any_origin = self.cors.allowed_origins.iter().any(|origin| origin == "*");
+        if any_origin && self.cors.allow_credentials {
+            issues.add(
+                "cors.allow_credentials",
+                "cannot be combined with a `*` origin",
+            );
+        }
+        if any_origin && prod {
+            issues.add("cors.allowed_origins", "must not contain `*` in prod");
+        }
+        for method in &self.cors.allowed_methods {
+            if Method::from_str(method).is_err() {
+                issues.add(
+                    "cors.allowed_methods",
+                    format!("'{}' is not an HTTP method", method),
+                );
+            }
+        }
+        for (key, headers) in [
+            ("cors.allowed_headers", &self.cors.allowed_headers),
+            ("cors.exposed_headers", &self.cors.exposed_headers),
+        ] {
+            for header in headers {
+                if HeaderName::from_str(header).is_err() {
+                    issues.add(key, format!("'{}' is not a valid header name", header));
+                }
+            }
+        }
+
+        let security = &self.security_headers;
+        for (key, value) in [
+            (
+                "security_headers.referrer_policy",
+                &security.referrer_policy,
+            ),
+            (
+                "security_headers.content_security_policy",
+                &security.content_security_policy,
+            ),
+            (
+                "security_headers.frame_ancestors",
+                &security.frame_ancestors,
+            ),
+        ] {
+            if HeaderValue::from_str(value).is_err() {
+                issues.add(key, "must be a valid header value");
+            }
+        }
+        for (index, scope) in security.overrides.iter().enumerate() {
+            let key = format!("security_headers.overrides[{}]", index);
+            if !scope.path.starts_with('/') {
+                issues.add(&key, format!("path '{}' must start with '/'", scope.path));
+            }
+            let values = [
+                &scope.referrer_policy,
+                &scope.content_security_policy,
+                &scope.frame_ancestors,
+            ];
+            if values
+                .into_iter()
+                .flatten()
+                .any(|value| HeaderValue::from_str(value).is_err())
+            {
+                issues.add(&key, "values must be valid header values");
+            }
+        }
+
+        if let Some(key) = &self.audit.signing_key
+            && !is_ed25519_key(key.expose())
+        {
+            issues.add("audit.signing_key", "must be a base64-encoded 32-byte key");
+        }
+        if let Some(key) = &self.audit.verifying_key
+            && !is_ed25519_key(key)
+        {
+            issues.add(
+                "audit.verifying_key",
+                "must be a base64-encoded 32-byte key",
+            );
+        }
+        if self.audit.checkpoint_interval_secs == 0 {
+            issues.add("audit.checkpoint_interval_secs", "must be at least 1");
+        }
+
+        let outbox = &self.outbox;
+        if outbox.enabled && outbox.sinks.is_empty() {
+            issues.add("outbox.sinks", "must name at least one sink");
+        }
+        for (key, value) in [
+            ("outbox.poll_interval_ms", outbox.poll_interval_ms),
+            ("outbox.batch_size", outbox.batch_size),
+            ("outbox.lease_secs", outbox.lease_secs),
+            ("outbox.max_attempts", u64::from(outbox.max_attempts)),
+            ("outbox.initial_backoff_ms", outbox.initial_backoff_ms),
+        ] {
+            if value == 0 {
+                issues.add(key, "must be at least 1");
+            }
+        }
+        if outbox.max_backoff_ms < outbox.initial_backoff_ms {
+            issues.add(
+                "outbox.max_backoff_ms",
+                "must not be lower than outbox.initial_backoff_ms",
+            );
+        }
+
+        let webhooks = &self.webhooks;
+        for (key, value) in [
+            ("webhooks.timeout_ms", webhooks.timeout_ms),
+            ("webhooks.poll_interval_ms", webhooks.poll_interval_ms),
+            ("webhooks.batch_size", webhooks.batch_size),
+            ("webhooks.max_attempts", u64::from(webhooks.max_attempts)),
+            ("webhooks.initial_backoff_ms", webhooks.initial_backoff_ms),
+        ] {
+            if value == 0 {
+                issues.add(key, "must be at least 1");
+            }
+        }
+        if webhooks.lease_secs.saturating_mul(1000) <= webhooks.timeout_ms {
+            issues.add("webhooks.lease_secs", "must exceed webhooks.timeout_ms");
+        }
+        if webhooks.max_backoff_ms < webhooks.initial_backoff_ms {
+            issues.add(
+                "webhooks.max_backoff_ms",
+                "must not be lower than webhooks.initial_backoff_ms",
+            );
+        }
+
+        let limits = &self.rate_limit;
+        if limits.requests_per_minute == 0 {
+            issues.add("rate_limit.requests_per_minute", "must be at least 1");
+        }
+        if limits.burst == 0 {
+            issues.add("rate_limit.burst", "must be at least 1");
+        }
+        if let Some(header) = &limits.api_key_header
+            && HeaderName::from_str(header).is_err()
+        {
+            issues.add(
+                "rate_limit.api_key_header",
+                format!("'{}' is not a valid header name", header),
+            );
+        }
+        if limits.api_key_header.is_some() && limits.api_keys.is_empty() {
+            issues.add(
+                "rate_limit.api_keys",
+                "is required with rate_limit.api_key_header",
+            );
+        }
+        for (index, route) in limits.routes.iter().enumerate() {
+            let key = format!("rate_limit.routes[{}]", index);
+            if !route.path.starts_with('/') {
+                issues.add(&key, format!("path '{}' must start with '/'", route.path));
+            }
+            if let Some(method) = &route.method
+                && Method::from_str(method).is_err()
+            {
+                issues.add(&key, format!("'{}' is not an HTTP method", method));
+            }
+            if route.requests_per_minute == 0 || route.burst == 0 {
+                issues.add(&key, "requests_per_minute and burst must be at least 1");
+            }
+        }
+    }
+}
+
+fn is_ed25519_key(key: &str) -> bool {
+    STANDARD
+        .decode(key.trim())
+        .is_ok_and(|bytes| bytes.len() == 32)
+}
+
+/// Accepts `env_logger` filters made of `level` or `target=level` directives.
+fn is_valid_log_filter(filter: &str) -> bool {
+    !filter.trim().is_empty()
+        && filter.split(',').all(|directive| {
+            let level = directive.rsplit('=').next().unwrap_or_default().trim();
+            log::LevelFilter::from_str(level).is_ok()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Cli;
+    use clap::Parser;
+    use config::Map;
+    use std::collections::BTreeSet;
+    use std::path::{Path, PathBuf};
+
+    /// Writes `{profile}.toml` into a directory of its own.
+    fn config_dir(profile: &str, toml: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("app-config-{}-{}", std::process::id(), profile));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(format!("{}.toml", profile)), toml).unwrap();
+        dir
+    }
+
+    fn cli(profile: &str, dir: &Path, args: &[&str]) -> Cli {
+        let dir = dir.to_str().unwrap();
+        let base = ["app", "--profile", profile, "--config-dir", dir];
+        Cli::try_parse_from(base.iter().chain(args)).unwrap()
+    }
+
+    fn env(vars: &[(&str, &str)]) -> Map<String, String> {
+        vars.iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn reports_every_issue_at_once() {
+        let dir = config_dir(
+            "staging",
+            r#"
+            [server]
+            port = 0
+            hots = "0.0.0.0"
+
+            [pagination]
+            default_limit = 0
+            max_limit = "many"
+            "#,
+        );
+
+        let err = AppConfig::load_from(&cli("staging", &dir, &[]), env(&[])).unwrap_err();
+
+        let keys: BTreeSet<&str> = err.issues.iter().map(|issue| issue.key.as_str()).collect();
+        assert_eq!(
+            keys,
+            BTreeSet::from([
+                "server.port",
+                "server.hots",
+                "pagination.default_limit",
+                "pagination.max_limit",
+                "database.url",
+                "auth.jwt_secret",
+                "logging.redaction.hash_key",
+            ])
+        );
+        assert!(err.to_string().contains("(7 issue(s))"));
+    }
+
+    #[test]
+    fn later_layers_win() {
+        let dir = config_dir(
+            "layers",
+            r#"
+            [server]
+            host = "0.0.0.0"
+            port = 8100
+
+            [pagination]
+            max_limit = 50
+            "#,
+        );
+        let env = env(&[
+            ("SERVER_HOST", "10.0.0.1"),
+            ("APP__SERVER__PORT", "8200"),
+            ("APP__SERVER__WORKERS", "4"),
+            ("DATABASE_URL", "postgres://legacy/app"),
+            ("APP__DATABASE__URL", "postgres://prefixed/app"),
+            ("JWT_SECRET", "a-secret-long-enough-for-a-profile"),
+            ("REDACTION_HASH_KEY", "a-hash-key"),
+        ]);
+        let cli = cli(
+            "layers",
+            &dir,
+            &["--port", "8300", "--set", "server.workers=2"],
+        );
+
+        let config = AppConfig::load_from(&cli, env).unwrap();
+
+        assert_eq!(config.pagination.default_limit, 20);
+        assert_eq!(config.pagination.max_limit, 50);
+        assert_eq!(config.server.host, "10.0.0.1");
+        assert_eq!(config.database.url, "postgres://prefixed/app");
+        assert_eq!(config.server.port, 8300);
+        assert_eq!(config.server.workers, Some(2));
     }
 }
@@ -0,0 +1,310 @@
+use clap::{Parser, Subcommand};
+use config::{Config, Environment, File, FileFormat, Map, Value};
+use serde::de::DeserializeOwned;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::PathBuf;
+
+use super::AppConfig;
+
+/// Unprefixed environment variables kept for compatibility with existing
+/// `.env` files and container definitions.
+const LEGACY_ENV: &[(&str, &str)] = &[
+    ("SERVER_HOST", "server.host"),
+    ("SERVER_PORT", "server.port"),
+    ("DATABASE_URL", "database.url"),
+    ("RUST_LOG", "logging.level"),
+    ("REDACTION_HASH_KEY", "logging.redaction.hash_key"),
+    ("PAGINATION_DEFAULT_LIMIT", "pagination.default_limit"),
+    ("PAGINATION_MAX_LIMIT", "pagination.max_limit"),
+    ("JWT_SECRET", "auth.jwt_secret"),
+    ("JWT_ISSUER", "auth.jwt_issuer"),
+    ("ACCESS_TOKEN_TTL_SECS", "auth.access_token_ttl_secs"),
+    ("REFRESH_TOKEN_TTL_SECS", "auth.refresh_token_ttl_secs"),
+    ("BOOTSTRAP_ADMIN_USERNAME", "auth.bootstrap_admin"),
+    ("BOOTSTRAP_ADMIN_EMAIL", "auth.bootstrap_admin_email"),
+    ("BOOTSTRAP_ADMIN_PASSWORD", "auth.bootstrap_admin_password"),
+];
+
+/// Keys holding lists, parsed from comma-separated environment values.
+const LIST_KEYS: &[&str] = &[
+    "cors.allowed_origins",
+    "cors.allowed_methods",
+    "cors.allowed_headers",
+    "cors.exposed_headers",
+    "outbox.sinks",
+    "rate_limit.api_keys",
+];
+
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// Configuration profile; loads `{config_dir}/{profile}.toml`.
+    #[arg(long, env = "APP_PROFILE", default_value = "dev")]
+    pub profile: String,
+
+    /// Directory containing the profile files.
+    #[arg(long, env = "APP_CONFIG_DIR", default_value = "config")]
+    pub config_dir: PathBuf,
+
+    /// Shorthand for `--set server.host=<HOST>`.
+    #[arg(long)]
+    pub host: Option<String>,
+
+    /// Shorthand for `--set server.port=<PORT>`.
+    #[arg(long)]
+    pub port: Option<String>,
+
+    /// Shorthand for `--set database.url=<URL>`.
+    #[arg(long)]
+    pub database_url: Option<String>,
+
+    /// Overrides any key, e.g. `--set pagination.max_limit=50`.
+    #[arg(long = "set", value_name = "KEY=VALUE")]
+    pub overrides: Vec<String>,
+
+    /// Runs a maintenance command instead of the server.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Walks the audit hash chain, checks it against the signed
+    /// checkpoints and prints a JSON report; exits with 3 if it is broken.
+    VerifyAudit,
+}
+
+impl Cli {
+    fn overrides(&self) -> Vec<(String, String)> {
+        let mut overrides: Vec<(String, String)> = [
+            ("server.host", &self.host),
+            ("server.port", &self.port),
+            ("database.url", &self.database_url),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| Some((key.to_string(), value.clone()?)))
+        .collect();
+
+        overrides.extend(self.overrides.iter().map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (key.trim().to_string(), value.to_string())
+        }));
+
+        overrides
+    }
+}
+
+/// A single invalid or unknown configuration key.
+#[derive(Debug, Clone)]
+pub struct ConfigIssue {
+    pub key: String,
+    pub message: String,
+}
+
+#[derive(Debug, Default)]
+pub struct ConfigIssues(Vec<ConfigIssue>);
+
+impl ConfigIssues {
+    pub fn add(&mut self, key: impl Into<String>, message: impl Into<String>) {
+        self.0.push(ConfigIssue {
+            key: key.into(),
+            message: message.into(),
+        });
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ConfigIssue> {
+        self.0.iter()
+    }
+}
+
+/// Every problem found while loading the configuration.
+#[derive(Debug)]
+pub struct ConfigError {
+    pub profile: String,
+    pub issues: ConfigIssues,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "invalid configuration for profile '{}' ({} issue(s)):",
+            self.profile,
+            self.issues.0.len()
+        )?;
+        for issue in self.issues.iter() {
+            writeln!(f, "  - {}: {}", issue.key, issue.message)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Typed access to the merged configuration that records failures.
+pub(super) struct Reader {
+    config: Config,
+    read: BTreeSet<String>,
+    issues: ConfigIssues,
+}
+
+impl Reader {
+    pub fn get_or<T: DeserializeOwned>(&mut self, key: &str, default: T) -> T {
+        self.get_opt(key).unwrap_or(default)
+    }
+
+    pub fn get_opt<T: DeserializeOwned>(&mut self, key: &str) -> Option<T> {
+        self.read.insert(key.to_string());
+        match self.config.get::<Option<T>>(key) {
+            Ok(value) => value,
+            Err(config::ConfigError::NotFound(_)) => None,
+            Err(err) => {
+                self.issues.add(key, describe(err));
+                None
+            }
+        }
+    }
+
+    /// Reports keys present in some layer that no section reads, which
+    /// usually means a typo.
+    fn report_unknown_keys(&mut self) {
+        let Ok(root) = self.config.clone().try_deserialize::<Map<String, Value>>() else {
+            return;
+        };
+        let mut unknown = Vec::new();
+        collect_unknown("", root, &self.read, &mut unknown);
+        for key in unknown {
+            self.issues.add(key, "unknown key");
+        }
+    }
+}
+
+fn collect_unknown(
+    prefix: &str,
+    table: Map<String, Value>,
+    read: &BTreeSet<String>,
+    unknown: &mut Vec<String>,
+) {
+    for (name, value) in table {
+        let key = if prefix.is_empty() {
+            name
+        } else {
+            format!("{}.{}", prefix, name)
+        };
+
+        if read.contains(&key) {
+            continue;
+        }
+        let is_section = read
+            .iter()
+            .any(|known| known.starts_with(&format!("{}.", key)));
+        match value.into_table() {
+            Ok(table) if is_section => collect_unknown(&key, table, read, unknown),
+            _ => unknown.push(key),
+        }
+    }
+}
+
+/// Drops the key and origin that `config` appends, which the issue
+/// already carries.
+fn describe(err: config::ConfigError) -> String {
+    let message = err.to_string();
+    message
+        .split(" for key")
+        .next()
+        .unwrap_or(&message)
+        .to_string()
+}
+
+impl AppConfig {
+    /// Loads the configuration in layers, later ones winning: built-in
+    /// defaults, `config/{profile}.toml`, environment variables
+    /// (`APP__SECTION__KEY` or the legacy names), then CLI flags.
+    pub fn load(cli: &Cli) -> Result<AppConfig, ConfigError> {
+        let env = std::env::vars_os()
+            .filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)))
+            .collect();
+        Self::load_from(cli, env)
+    }
+
+    /// [`AppConfig::load`] with the environment variables passed in.
+    pub(super) fn load_from(cli: &Cli, env: Map<String, String>) -> Result<AppConfig, ConfigError> {
+        let profile = cli.profile.clone();
+        let mut issues = ConfigIssues::default();
+        let profile_path = cli.config_dir.join(format!("{}.toml", profile));
+
+        if !profile_path.is_file() {
+            issues.add(
+                "profile",
+                format!("'{}' has no file at {}", profile, profile_path.display()),
+            );
+        }
+
+        let legacy_env: Map<String, String> = LEGACY_ENV
+            .iter()
+            .filter_map(|(name, key)| {
+                let value = env.get(*name)?;
+                Some((
+                    format!("APP__{}", key.replace('.', "__").to_uppercase()),
+                    value.clone(),
+                ))
+            })
+            .collect();
+
+        let mut builder = Config::builder()
+            .add_source(
+                File::from(profile_path)
+                    .format(FileFormat::Toml)
+                    .required(false),
+            )
+            .add_source(app_environment().source(Some(legacy_env)))
+            .add_source(app_environment().source(Some(env)));
+
+        for (key, value) in cli.overrides() {
+            builder = match builder.clone().set_override(&key, value) {
+                Ok(builder) => builder,
+                Err(err) => {
+                    issues.add(&key, describe(err));
+                    builder
+                }
+            };
+        }
+
+        let config = match builder.build() {
+            Ok(config) => config,
+            Err(err) => {
+                issues.add("config", err.to_string());
+                return Err(ConfigError { profile, issues });
+            }
+        };
+
+        let mut reader = Reader {
+            config,
+            read: BTreeSet::new(),
+            issues,
+        };
+        let app_config = AppConfig::read(&profile, &mut reader);
+        reader.report_unknown_keys();
+
+        let mut issues = reader.issues;
+        app_config.validate(&mut issues);
+
+        if issues.0.is_empty() {
+            Ok(app_config)
+        } else {
+            Err(ConfigError { profile, issues })
+        }
+    }
+}
+
+fn app_environment() -> Environment {
+    LIST_KEYS.iter().fold(
+        Environment::with_prefix("APP")
+            .prefix_separator("__")
+            .separator("__")
+            .list_separator(",")
+            .try_parsing(true),
+        |env, key| env.with_list_parse_key(key),
+    )
+}
@@ -1,5 +1,29 @@
 mod app_config;
+mod loader;
 
 pub use app_config::AppConfig;
+pub use app_config::AuditConfig;
+pub use app_config::AuthConfig;
+pub use app_config::CorsConfig;
 pub use app_config::DatabaseConfig;
+pub use app_config::HealthConfig;
+pub use app_config::LogFormat;
+pub use app_config::LoggingConfig;
+pub use app_config::OutboxConfig;
+pub use app_config::OutboxSinkKind;
+pub use app_config::PaginationConfig;
+pub use app_config::PoolConfig;
+pub use app_config::RateLimitBackend;
+pub use app_config::RateLimitConfig;
+pub use app_config::RedactionConfig;
+pub use app_config::RedactionRule;
+pub use app_config::RetryConfig;
+pub use app_config::RouteRateLimit;
+pub use app_config::SecurityHeadersConfig;
+pub use app_config::SecurityHeadersOverride;
 pub use app_config::ServerConfig;
+pub use app_config::ShutdownConfig;
+pub use app_config::TraceExporter;
+pub use app_config::TracingConfig;
+pub use app_config::WebhooksConfig;
+pub use loader::{Cli, Command, ConfigError, ConfigIssue};
@@ -0,0 +1,94 @@
+use log::{info, warn};
+use sea_orm::{ConnectOptions, Database, DbConn, DbErr, RuntimeErr};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use crate::config::DatabaseConfig;
+
+/// Builds the pool options from configuration.
+pub fn connect_options(config: &DatabaseConfig) -> ConnectOptions {
+    let url = match config.statement_timeout_ms {
+        Some(timeout) => with_statement_timeout(&config.url, timeout),
+        None => config.url.clone(),
+    };
+    let pool = &config.pool;
+
+    let mut options = ConnectOptions::new(url);
+    options
+        .max_connections(pool.max_connections)
+        .min_connections(pool.min_connections)
+        .connect_timeout(Duration::from_secs(pool.connect_timeout_secs))
+        .acquire_timeout(Duration::from_secs(pool.acquire_timeout_secs))
+        .idle_timeout(Duration::from_secs(pool.idle_timeout_secs))
+        .max_lifetime(Duration::from_secs(pool.max_lifetime_secs))
+        .sqlx_logging(config.sqlx_logging)
+        .sqlx_logging_level(
+            log::LevelFilter::from_str(&config.sqlx_log_level).unwrap_or(log::LevelFilter::Debug),
+        );
+    options
+}
+
+/// Connects to the database, retrying with exponential backoff until
+/// `database.retry.deadline_secs` has elapsed.
+pub async fn connect(config: &DatabaseConfig) -> Result<DbConn, DbErr> {
+    let retry = &config.retry;
+    let deadline = Instant::now() + Duration::from_secs(retry.deadline_secs);
+    let mut backoff = Duration::from_millis(retry.initial_backoff_ms);
+    let max_backoff = Duration::from_millis(retry.max_backoff_ms);
+    let mut attempt = 1;
+
+    loop {
+        let err = match try_connect(config, deadline).await {
+            Ok(db) => {
+                info!("Connected to the database after {} attempt(s)", attempt);
+                return Ok(db);
+            }
+            Err(err) => err,
+        };
+
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining < backoff {
+            warn!(
+                "Giving up connecting to the database after {} attempt(s)",
+                attempt
+            );
+            return Err(err);
+        }
+
+        warn!(
+            "Database connection attempt {} failed: {}; retrying in {} ms",
+            attempt,
+            err,
+            backoff.as_millis()
+        );
+        tokio::time::sleep(backoff).await;
+
+        backoff = (backoff * 2).min(max_backoff);
+        attempt += 1;
+    }
+}
+
+/// sqlx keeps retrying refused connections until the acquire timeout, so
+/// each attempt is bounded by the connect timeout and the deadline.
+async fn try_connect(config: &DatabaseConfig, deadline: Instant) -> Result<DbConn, DbErr> {
+    let timeout = Duration::from_secs(config.pool.connect_timeout_secs)
+        .min(deadline.saturating_duration_since(Instant::now()))
+        .max(Duration::from_millis(100));
+
+    match tokio::time::timeout(timeout, Database::connect(connect_options(config))).await {
+        Ok(result) => result,
+        Err(_) => Err(DbErr::Conn(RuntimeErr::Internal(format!(
+            "connection attempt timed out after {} ms",
+            timeout.as_millis()
+        )))),
+    }
+}
+
+/// Appends `options=-c statement_timeout=<ms>` to the connection URL.
+fn with_statement_timeout(url: &str, timeout_ms: u64) -> String {
+    let separator = if url.contains('?') { '&' } else { '?' };
+    format!(
+        "{}{}options=-c%20statement_timeout%3D{}",
+        url, separator, timeout_ms
+    )
+}
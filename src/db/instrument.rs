@@ -0,0 +1,97 @@
+use sea_orm::{DbErr, DeleteResult, UpdateResult};
+use std::future::Future;
+use tracing::Instrument;
+use tracing::field::Empty;
+
+use crate::metrics::observe_query;
+
+#[derive(Debug, Clone, Copy)]
+pub enum StatementKind {
+    Select,
+    Insert,
+    Update,
+    Delete,
+}
+
+impl StatementKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Select => "SELECT",
+            Self::Insert => "INSERT",
+            Self::Update => "UPDATE",
+            Self::Delete => "DELETE",
+        }
+    }
+}
+
+/// Rows returned or affected by a repository call.
+pub trait RowCount {
+    fn row_count(&self) -> u64;
+}
+
+impl<T> RowCount for Option<T> {
+    fn row_count(&self) -> u64 {
+        u64::from(self.is_some())
+    }
+}
+
+impl<T> RowCount for Vec<T> {
+    fn row_count(&self) -> u64 {
+        self.len() as u64
+    }
+}
+
+impl RowCount for DeleteResult {
+    fn row_count(&self) -> u64 {
+        self.rows_affected
+    }
+}
+
+/// Rows affected by a statement that returns none.
+impl RowCount for u64 {
+    fn row_count(&self) -> u64 {
+        *self
+    }
+}
+
+impl RowCount for UpdateResult {
+    fn row_count(&self) -> u64 {
+        self.rows_affected
+    }
+}
+
+/// Runs a repository call inside a `db.query` span recording the statement
+/// kind and row count, and times it for the query metrics.
+pub async fn instrumented<T: RowCount>(
+    repository: &'static str,
+    method: &'static str,
+    kind: StatementKind,
+    query: impl Future<Output = Result<T, DbErr>>,
+) -> Result<T, DbErr> {
+    let span = tracing::info_span!(
+        "db.query",
+        otel.name = %format!("{}.{}", repository, method),
+        otel.kind = "client",
+        otel.status_code = Empty,
+        db.system = "postgresql",
+        db.operation = kind.as_str(),
+        db.repository = repository,
+        db.method = method,
+        db.rows = Empty,
+    );
+
+    let result = observe_query(repository, method, query)
+        .instrument(span.clone())
+        .await;
+
+    match &result {
+        Ok(value) => {
+            span.record("db.rows", value.row_count());
+        }
+        Err(_) => {
+            span.record("otel.status_code", "ERROR");
+        }
+    }
+
+    result
+}
@@ -0,0 +1,57 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared("CREATE EXTENSION IF NOT EXISTS pg_trgm")
+            .await?;
+
+        // Email is indexed both whole and split on punctuation so that
+        // "smith" finds "john.smith@example.com".
+        db.execute_unprepared(
+            r#"ALTER TABLE tbl_users ADD COLUMN search_vector tsvector GENERATED ALWAYS AS (
+                setweight(to_tsvector('simple', username), 'A') ||
+                setweight(to_tsvector('simple', coalesce(first_name, '')), 'A') ||
+                setweight(to_tsvector('simple', coalesce(last_name, '')), 'A') ||
+                setweight(to_tsvector('simple', email), 'B') ||
+                setweight(to_tsvector('simple', regexp_replace(email, '[^[:alnum:]]+', ' ', 'g')), 'C')
+            ) STORED"#,
+        )
+        .await?;
+
+        db.execute_unprepared(
+            "CREATE INDEX idx_users_search_vector ON tbl_users USING gin (search_vector)",
+        )
+        .await?;
+
+        for column in ["username", "first_name", "last_name", "email"] {
+            db.execute_unprepared(&format!(
+                "CREATE INDEX idx_users_{column}_trgm ON tbl_users USING gin ({column} gin_trgm_ops)"
+            ))
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        for column in ["username", "first_name", "last_name", "email"] {
+            db.execute_unprepared(&format!("DROP INDEX IF EXISTS idx_users_{column}_trgm"))
+                .await?;
+        }
+
+        db.execute_unprepared("DROP INDEX IF EXISTS idx_users_search_vector")
+            .await?;
+        db.execute_unprepared("ALTER TABLE tbl_users DROP COLUMN IF EXISTS search_vector")
+            .await?;
+
+        Ok(())
+    }
+}
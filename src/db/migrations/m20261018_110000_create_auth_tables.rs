@@ -0,0 +1,154 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(TblUserCredentials::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TblUserCredentials::UserId)
+                            .integer()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(TblUserCredentials::PasswordHash)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TblUserCredentials::CreatedOn)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TblUserCredentials::UpdatedOn)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_user_credentials_user_id")
+                            .from(TblUserCredentials::Table, TblUserCredentials::UserId)
+                            .to(TblUsers::Table, TblUsers::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(TblRefreshTokens::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TblRefreshTokens::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(TblRefreshTokens::UserId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TblRefreshTokens::TokenHash)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(TblRefreshTokens::FamilyId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(TblRefreshTokens::CreatedOn)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TblRefreshTokens::ExpiresOn)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TblRefreshTokens::RevokedOn)
+                            .timestamp()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(TblRefreshTokens::ReplacedBy)
+                            .integer()
+                            .null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_refresh_tokens_user_id")
+                            .from(TblRefreshTokens::Table, TblRefreshTokens::UserId)
+                            .to(TblUsers::Table, TblUsers::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .index(
+                        Index::create()
+                            .name("idx_refresh_token_hash")
+                            .col(TblRefreshTokens::TokenHash)
+                            .unique(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_refresh_token_family")
+                    .table(TblRefreshTokens::Table)
+                    .col(TblRefreshTokens::FamilyId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(TblRefreshTokens::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(TblUserCredentials::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum TblUsers {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum TblUserCredentials {
+    Table,
+    UserId,
+    PasswordHash,
+    CreatedOn,
+    UpdatedOn,
+}
+
+#[derive(DeriveIden)]
+enum TblRefreshTokens {
+    Table,
+    Id,
+    UserId,
+    TokenHash,
+    FamilyId,
+    CreatedOn,
+    ExpiresOn,
+    RevokedOn,
+    ReplacedBy,
+}
@@ -0,0 +1,249 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const PERMISSIONS: &[(&str, &str)] = &[
+    ("users:read", "Read any user record"),
+    ("users:update", "Update any user record"),
+    ("users:delete", "Soft delete or physically delete users"),
+    ("users:restore", "Restore soft deleted users"),
+    ("roles:assign", "Assign and revoke user roles"),
+];
+
+const ROLES: &[(&str, &str, &[&str])] = &[
+    (
+        "admin",
+        "Full access to user management",
+        &[
+            "users:read",
+            "users:update",
+            "users:delete",
+            "users:restore",
+            "roles:assign",
+        ],
+    ),
+    ("support", "Read-only access to all users", &["users:read"]),
+];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(TblRoles::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TblRoles::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(TblRoles::Name).string().not_null())
+                    .col(ColumnDef::new(TblRoles::Description).string().null())
+                    .index(
+                        Index::create()
+                            .name("idx_role_name")
+                            .col(TblRoles::Name)
+                            .unique(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(TblPermissions::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TblPermissions::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(TblPermissions::Name).string().not_null())
+                    .col(ColumnDef::new(TblPermissions::Description).string().null())
+                    .index(
+                        Index::create()
+                            .name("idx_permission_name")
+                            .col(TblPermissions::Name)
+                            .unique(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(TblRolePermissions::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TblRolePermissions::RoleId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TblRolePermissions::PermissionId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(TblRolePermissions::RoleId)
+                            .col(TblRolePermissions::PermissionId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_role_permissions_role_id")
+                            .from(TblRolePermissions::Table, TblRolePermissions::RoleId)
+                            .to(TblRoles::Table, TblRoles::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_role_permissions_permission_id")
+                            .from(TblRolePermissions::Table, TblRolePermissions::PermissionId)
+                            .to(TblPermissions::Table, TblPermissions::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(TblUserRoles::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(TblUserRoles::UserId).integer().not_null())
+                    .col(ColumnDef::new(TblUserRoles::RoleId).integer().not_null())
+                    .col(
+                        ColumnDef::new(TblUserRoles::AssignedOn)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(TblUserRoles::UserId)
+                            .col(TblUserRoles::RoleId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_user_roles_user_id")
+                            .from(TblUserRoles::Table, TblUserRoles::UserId)
+                            .to(TblUsers::Table, TblUsers::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_user_roles_role_id")
+                            .from(TblUserRoles::Table, TblUserRoles::RoleId)
+                            .to(TblRoles::Table, TblRoles::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        let mut permissions = Query::insert()
+            .into_table(TblPermissions::Table)
+            .columns([TblPermissions::Name, TblPermissions::Description])
+            .to_owned();
+        for (name, description) in PERMISSIONS {
+            permissions.values_panic([(*name).into(), (*description).into()]);
+        }
+        manager.exec_stmt(permissions).await?;
+
+        let mut roles = Query::insert()
+            .into_table(TblRoles::Table)
+            .columns([TblRoles::Name, TblRoles::Description])
+            .to_owned();
+        for (name, description, _) in ROLES {
+            roles.values_panic([(*name).into(), (*description).into()]);
+        }
+        manager.exec_stmt(roles).await?;
+
+        for (role, _, granted) in ROLES {
+            for permission in *granted {
+                let grant = Query::insert()
+                    .into_table(TblRolePermissions::Table)
+                    .columns([TblRolePermissions::RoleId, TblRolePermissions::PermissionId])
+                    .select_from(
+                        Query::select()
+                            .column((TblRoles::Table, TblRoles::Id))
+                            .column((TblPermissions::Table, TblPermissions::Id))
+                            .from(TblRoles::Table)
+                            .from(TblPermissions::Table)
+                            .and_where(Expr::col((TblRoles::Table, TblRoles::Name)).eq(*role))
+                            .and_where(
+                                Expr::col((TblPermissions::Table, TblPermissions::Name))
+                                    .eq(*permission),
+                            )
+                            .to_owned(),
+                    )
+                    .map_err(|err| DbErr::Migration(err.to_string()))?
+                    .to_owned();
+                manager.exec_stmt(grant).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(TblUserRoles::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(TblRolePermissions::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(TblPermissions::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(TblRoles::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum TblUsers {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum TblRoles {
+    Table,
+    Id,
+    Name,
+    Description,
+}
+
+#[derive(DeriveIden)]
+enum TblPermissions {
+    Table,
+    Id,
+    Name,
+    Description,
+}
+
+#[derive(DeriveIden)]
+enum TblRolePermissions {
+    Table,
+    RoleId,
+    PermissionId,
+}
+
+#[derive(DeriveIden)]
+enum TblUserRoles {
+    Table,
+    UserId,
+    RoleId,
+    AssignedOn,
+}
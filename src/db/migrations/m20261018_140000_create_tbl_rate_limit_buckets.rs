@@ -0,0 +1,66 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(TblRateLimitBuckets::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TblRateLimitBuckets::Key)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(TblRateLimitBuckets::Tokens)
+                            .double()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TblRateLimitBuckets::Allowed)
+                            .boolean()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TblRateLimitBuckets::UpdatedOn)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_rate_limit_buckets_updated_on")
+                    .table(TblRateLimitBuckets::Table)
+                    .col(TblRateLimitBuckets::UpdatedOn)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(TblRateLimitBuckets::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum TblRateLimitBuckets {
+    Table,
+    Key,
+    Tokens,
+    /// Whether the last request took a token; lets one upsert both update
+    /// the bucket and report the decision.
+    Allowed,
+    UpdatedOn,
+}
@@ -0,0 +1,137 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const AUDIT_READ: (&str, &str) = ("audit:read", "Read the user audit log");
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // No foreign key on user_id: entries must outlive a physical delete.
+        manager
+            .create_table(
+                Table::create()
+                    .table(TblUserAudit::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TblUserAudit::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(TblUserAudit::UserId).integer().not_null())
+                    .col(ColumnDef::new(TblUserAudit::Action).string().not_null())
+                    .col(ColumnDef::new(TblUserAudit::ActorId).integer().null())
+                    .col(ColumnDef::new(TblUserAudit::RequestId).string().null())
+                    .col(ColumnDef::new(TblUserAudit::ClientIp).string().null())
+                    .col(ColumnDef::new(TblUserAudit::Before).json_binary().null())
+                    .col(ColumnDef::new(TblUserAudit::After).json_binary().null())
+                    .col(
+                        ColumnDef::new(TblUserAudit::CreatedOn)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        for (name, column) in [
+            ("idx_user_audit_user_id", TblUserAudit::UserId),
+            ("idx_user_audit_actor_id", TblUserAudit::ActorId),
+            ("idx_user_audit_created_on", TblUserAudit::CreatedOn),
+        ] {
+            manager
+                .create_index(
+                    Index::create()
+                        .name(name)
+                        .table(TblUserAudit::Table)
+                        .col(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        manager
+            .exec_stmt(
+                Query::insert()
+                    .into_table(TblPermissions::Table)
+                    .columns([TblPermissions::Name, TblPermissions::Description])
+                    .values_panic([AUDIT_READ.0.into(), AUDIT_READ.1.into()])
+                    .to_owned(),
+            )
+            .await?;
+
+        let grant = Query::insert()
+            .into_table(TblRolePermissions::Table)
+            .columns([TblRolePermissions::RoleId, TblRolePermissions::PermissionId])
+            .select_from(
+                Query::select()
+                    .column((TblRoles::Table, TblRoles::Id))
+                    .column((TblPermissions::Table, TblPermissions::Id))
+                    .from(TblRoles::Table)
+                    .from(TblPermissions::Table)
+                    .and_where(Expr::col((TblRoles::Table, TblRoles::Name)).eq("admin"))
+                    .and_where(
+                        Expr::col((TblPermissions::Table, TblPermissions::Name)).eq(AUDIT_READ.0),
+                    )
+                    .to_owned(),
+            )
+            .map_err(|err| DbErr::Migration(err.to_string()))?
+            .to_owned();
+        manager.exec_stmt(grant).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .exec_stmt(
+                Query::delete()
+                    .from_table(TblPermissions::Table)
+                    .and_where(Expr::col(TblPermissions::Name).eq(AUDIT_READ.0))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(TblUserAudit::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum TblUserAudit {
+    Table,
+    Id,
+    UserId,
+    Action,
+    ActorId,
+    RequestId,
+    ClientIp,
+    /// Changed fields before the mutation; absent on create.
+    Before,
+    /// Changed fields after the mutation; absent on physical delete.
+    After,
+    CreatedOn,
+}
+
+#[derive(DeriveIden)]
+enum TblRoles {
+    Table,
+    Id,
+    Name,
+}
+
+#[derive(DeriveIden)]
+enum TblPermissions {
+    Table,
+    Id,
+    Name,
+    Description,
+}
+
+#[derive(DeriveIden)]
+enum TblRolePermissions {
+    Table,
+    RoleId,
+    PermissionId,
+}
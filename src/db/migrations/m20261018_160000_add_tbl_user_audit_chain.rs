@@ -0,0 +1,271 @@
+use sea_orm::prelude::{DateTime, Json};
+use sea_orm::{FromQueryResult, Order};
+use sea_orm_migration::prelude::*;
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const BACKFILL_BATCH: u64 = 1000;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TblUserAudit::Table)
+                    .add_column(
+                        ColumnDef::new(TblUserAudit::PrevHash)
+                            .string()
+                            .not_null()
+                            .default(""),
+                    )
+                    .add_column(
+                        ColumnDef::new(TblUserAudit::Hash)
+                            .string()
+                            .not_null()
+                            .default(""),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Chain the entries written before this migration, oldest first.
+        let db = manager.get_connection();
+        let mut prev_hash = GENESIS_HASH.to_string();
+        let mut last_id = 0;
+        loop {
+            let select = Query::select()
+                .columns([
+                    TblUserAudit::Id,
+                    TblUserAudit::UserId,
+                    TblUserAudit::Action,
+                    TblUserAudit::ActorId,
+                    TblUserAudit::RequestId,
+                    TblUserAudit::ClientIp,
+                    TblUserAudit::Before,
+                    TblUserAudit::After,
+                    TblUserAudit::CreatedOn,
+                ])
+                .from(TblUserAudit::Table)
+                .and_where(Expr::col(TblUserAudit::Id).gt(last_id))
+                .order_by(TblUserAudit::Id, Order::Asc)
+                .limit(BACKFILL_BATCH)
+                .to_owned();
+            let entries = AuditEntry::find_by_statement(db.get_database_backend().build(&select))
+                .all(db)
+                .await?;
+            if entries.is_empty() {
+                break;
+            }
+
+            for entry in entries {
+                last_id = entry.id;
+                let hash = entry_hash(&prev_hash, &entry);
+                manager
+                    .exec_stmt(
+                        Query::update()
+                            .table(TblUserAudit::Table)
+                            .values([
+                                (TblUserAudit::PrevHash, prev_hash.into()),
+                                (TblUserAudit::Hash, hash.clone().into()),
+                            ])
+                            .and_where(Expr::col(TblUserAudit::Id).eq(entry.id))
+                            .to_owned(),
+                    )
+                    .await?;
+                prev_hash = hash;
+            }
+        }
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TblUserAudit::Table)
+                    .modify_column(ColumnDef::new(TblUserAudit::PrevHash).string().not_null())
+                    .modify_column(ColumnDef::new(TblUserAudit::Hash).string().not_null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "ALTER TABLE tbl_user_audit \
+                 ALTER COLUMN prev_hash DROP DEFAULT, ALTER COLUMN hash DROP DEFAULT",
+            )
+            .await?;
+
+        // Two entries claiming the same predecessor would fork the chain.
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_user_audit_prev_hash")
+                    .table(TblUserAudit::Table)
+                    .col(TblUserAudit::PrevHash)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(TblAuditCheckpoints::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TblAuditCheckpoints::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(TblAuditCheckpoints::LastEntryId)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TblAuditCheckpoints::Hash)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TblAuditCheckpoints::Signature)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TblAuditCheckpoints::KeyId)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TblAuditCheckpoints::CreatedOn)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(TblAuditCheckpoints::Table).to_owned())
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TblUserAudit::Table)
+                    .drop_column(TblUserAudit::PrevHash)
+                    .drop_column(TblUserAudit::Hash)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+/// `tbl_user_audit` as of this migration.
+#[derive(FromQueryResult)]
+struct AuditEntry {
+    id: i64,
+    user_id: i32,
+    action: String,
+    actor_id: Option<i32>,
+    request_id: Option<String>,
+    client_ip: Option<String>,
+    before: Option<Json>,
+    after: Option<Json>,
+    created_on: DateTime,
+}
+
+// The hash as defined in `audit::chain` when this migration was written,
+// kept here so later changes to the application cannot alter the backfill.
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+fn entry_hash(prev_hash: &str, entry: &AuditEntry) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(prev_hash.as_bytes());
+    hasher.update(canonical_json(entry).as_bytes());
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+fn canonical_json(entry: &AuditEntry) -> String {
+    let value = json!({
+        "id": entry.id,
+        "user_id": entry.user_id,
+        "action": entry.action,
+        "actor_id": entry.actor_id,
+        "request_id": entry.request_id,
+        "client_ip": entry.client_ip,
+        "before": entry.before,
+        "after": entry.after,
+        "created_on": entry.created_on.format("%Y-%m-%dT%H:%M:%S%.6f").to_string(),
+    });
+    let mut out = String::new();
+    write_canonical(&value, &mut out);
+    out
+}
+
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            out.push('{');
+            for (index, key) in keys.into_iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(&map[key], out);
+            }
+            out.push('}');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        scalar => out.push_str(&scalar.to_string()),
+    }
+}
+
+#[derive(DeriveIden)]
+enum TblUserAudit {
+    Table,
+    Id,
+    UserId,
+    Action,
+    ActorId,
+    RequestId,
+    ClientIp,
+    Before,
+    After,
+    CreatedOn,
+    PrevHash,
+    Hash,
+}
+
+#[derive(DeriveIden)]
+enum TblAuditCheckpoints {
+    Table,
+    Id,
+    LastEntryId,
+    Hash,
+    Signature,
+    KeyId,
+    CreatedOn,
+}
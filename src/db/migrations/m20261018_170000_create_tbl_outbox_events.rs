@@ -0,0 +1,120 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(TblOutboxEvents::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TblOutboxEvents::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(TblOutboxEvents::EventId)
+                            .uuid()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(TblOutboxEvents::EventType)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TblOutboxEvents::AggregateType)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TblOutboxEvents::AggregateId)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TblOutboxEvents::Payload)
+                            .json_binary()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(TblOutboxEvents::ActorId).integer().null())
+                    .col(ColumnDef::new(TblOutboxEvents::RequestId).string().null())
+                    .col(
+                        ColumnDef::new(TblOutboxEvents::OccurredOn)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TblOutboxEvents::Status)
+                            .string()
+                            .not_null()
+                            .default("pending"),
+                    )
+                    .col(
+                        ColumnDef::new(TblOutboxEvents::Attempts)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(TblOutboxEvents::NextAttemptOn)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(TblOutboxEvents::LastError).text().null())
+                    .col(
+                        ColumnDef::new(TblOutboxEvents::DeliveredOn)
+                            .timestamp()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // The relay scans pending events in due order.
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_outbox_events_status_next_attempt_on")
+                    .table(TblOutboxEvents::Table)
+                    .col(TblOutboxEvents::Status)
+                    .col(TblOutboxEvents::NextAttemptOn)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(TblOutboxEvents::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum TblOutboxEvents {
+    Table,
+    Id,
+    /// Stable id sinks use to drop redelivered events.
+    EventId,
+    EventType,
+    AggregateType,
+    AggregateId,
+    Payload,
+    ActorId,
+    RequestId,
+    OccurredOn,
+    /// `pending`, `delivered` or `failed`.
+    Status,
+    Attempts,
+    NextAttemptOn,
+    LastError,
+    DeliveredOn,
+}
@@ -0,0 +1,345 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const WEBHOOKS_MANAGE: (&str, &str) = ("webhooks:manage", "Manage webhook subscriptions");
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(TblWebhookSubscriptions::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TblWebhookSubscriptions::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(TblWebhookSubscriptions::Url)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TblWebhookSubscriptions::Secret)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TblWebhookSubscriptions::EventTypes)
+                            .json_binary()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TblWebhookSubscriptions::Active)
+                            .boolean()
+                            .not_null()
+                            .default(true),
+                    )
+                    .col(
+                        ColumnDef::new(TblWebhookSubscriptions::CreatedBy)
+                            .integer()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(TblWebhookSubscriptions::CreatedOn)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TblWebhookSubscriptions::UpdatedOn)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(TblWebhookDeliveries::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TblWebhookDeliveries::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(TblWebhookDeliveries::SubscriptionId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TblWebhookDeliveries::EventId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TblWebhookDeliveries::EventType)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(TblWebhookDeliveries::Body).text().not_null())
+                    .col(
+                        ColumnDef::new(TblWebhookDeliveries::Status)
+                            .string()
+                            .not_null()
+                            .default("pending"),
+                    )
+                    .col(
+                        ColumnDef::new(TblWebhookDeliveries::Attempts)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(TblWebhookDeliveries::NextAttemptOn)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TblWebhookDeliveries::LastStatusCode)
+                            .integer()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(TblWebhookDeliveries::LastError)
+                            .text()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(TblWebhookDeliveries::CreatedOn)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TblWebhookDeliveries::DeliveredOn)
+                            .timestamp()
+                            .null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_webhook_deliveries_subscription_id")
+                            .from(
+                                TblWebhookDeliveries::Table,
+                                TblWebhookDeliveries::SubscriptionId,
+                            )
+                            .to(TblWebhookSubscriptions::Table, TblWebhookSubscriptions::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Outbox events may be relayed more than once.
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_webhook_deliveries_subscription_event")
+                    .table(TblWebhookDeliveries::Table)
+                    .col(TblWebhookDeliveries::SubscriptionId)
+                    .col(TblWebhookDeliveries::EventId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_webhook_deliveries_status_next_attempt_on")
+                    .table(TblWebhookDeliveries::Table)
+                    .col(TblWebhookDeliveries::Status)
+                    .col(TblWebhookDeliveries::NextAttemptOn)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(TblWebhookAttempts::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TblWebhookAttempts::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(TblWebhookAttempts::DeliveryId)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TblWebhookAttempts::StatusCode)
+                            .integer()
+                            .null(),
+                    )
+                    .col(ColumnDef::new(TblWebhookAttempts::Error).text().null())
+                    .col(
+                        ColumnDef::new(TblWebhookAttempts::ResponseBody)
+                            .text()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(TblWebhookAttempts::DurationMs)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TblWebhookAttempts::AttemptedOn)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_webhook_attempts_delivery_id")
+                            .from(TblWebhookAttempts::Table, TblWebhookAttempts::DeliveryId)
+                            .to(TblWebhookDeliveries::Table, TblWebhookDeliveries::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_webhook_attempts_delivery_id")
+                    .table(TblWebhookAttempts::Table)
+                    .col(TblWebhookAttempts::DeliveryId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .exec_stmt(
+                Query::insert()
+                    .into_table(TblPermissions::Table)
+                    .columns([TblPermissions::Name, TblPermissions::Description])
+                    .values_panic([WEBHOOKS_MANAGE.0.into(), WEBHOOKS_MANAGE.1.into()])
+                    .to_owned(),
+            )
+            .await?;
+
+        let grant = Query::insert()
+            .into_table(TblRolePermissions::Table)
+            .columns([TblRolePermissions::RoleId, TblRolePermissions::PermissionId])
+            .select_from(
+                Query::select()
+                    .column((TblRoles::Table, TblRoles::Id))
+                    .column((TblPermissions::Table, TblPermissions::Id))
+                    .from(TblRoles::Table)
+                    .from(TblPermissions::Table)
+                    .and_where(Expr::col((TblRoles::Table, TblRoles::Name)).eq("admin"))
+                    .and_where(
+                        Expr::col((TblPermissions::Table, TblPermissions::Name))
+                            .eq(WEBHOOKS_MANAGE.0),
+                    )
+                    .to_owned(),
+            )
+            .map_err(|err| DbErr::Migration(err.to_string()))?
+            .to_owned();
+        manager.exec_stmt(grant).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .exec_stmt(
+                Query::delete()
+                    .from_table(TblPermissions::Table)
+                    .and_where(Expr::col(TblPermissions::Name).eq(WEBHOOKS_MANAGE.0))
+                    .to_owned(),
+            )
+            .await?;
+        for table in [
+            TblWebhookAttempts::Table.into_iden(),
+            TblWebhookDeliveries::Table.into_iden(),
+            TblWebhookSubscriptions::Table.into_iden(),
+        ] {
+            manager
+                .drop_table(Table::drop().table(table).to_owned())
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum TblWebhookSubscriptions {
+    Table,
+    Id,
+    Url,
+    /// HMAC key; kept in clear because every delivery is signed with it.
+    Secret,
+    /// Event types to deliver; every type when empty.
+    EventTypes,
+    Active,
+    CreatedBy,
+    CreatedOn,
+    UpdatedOn,
+}
+
+#[derive(DeriveIden)]
+enum TblWebhookDeliveries {
+    Table,
+    Id,
+    SubscriptionId,
+    EventId,
+    EventType,
+    /// The request body, fixed so every attempt sends the same bytes.
+    Body,
+    /// `pending`, `delivered` or `dead`.
+    Status,
+    Attempts,
+    NextAttemptOn,
+    LastStatusCode,
+    LastError,
+    CreatedOn,
+    DeliveredOn,
+}
+
+#[derive(DeriveIden)]
+enum TblWebhookAttempts {
+    Table,
+    Id,
+    DeliveryId,
+    StatusCode,
+    Error,
+    /// The start of the response body.
+    ResponseBody,
+    DurationMs,
+    AttemptedOn,
+}
+
+#[derive(DeriveIden)]
+enum TblRoles {
+    Table,
+    Id,
+    Name,
+}
+
+#[derive(DeriveIden)]
+enum TblPermissions {
+    Table,
+    Id,
+    Name,
+    Description,
+}
+
+#[derive(DeriveIden)]
+enum TblRolePermissions {
+    Table,
+    RoleId,
+    PermissionId,
+}
@@ -1,12 +1,32 @@
 pub use sea_orm_migration::prelude::*;
 
 mod m20250319_093000_create_tbl_users;
+mod m20261018_100000_add_tbl_users_search;
+mod m20261018_110000_create_auth_tables;
+mod m20261018_120000_create_rbac_tables;
+mod m20261018_130000_add_tbl_users_version;
+mod m20261018_140000_create_tbl_rate_limit_buckets;
+mod m20261018_150000_create_tbl_user_audit;
+mod m20261018_160000_add_tbl_user_audit_chain;
+mod m20261018_170000_create_tbl_outbox_events;
+mod m20261018_180000_create_webhook_tables;
 
 pub struct Migrator;
 
 #[async_trait::async_trait]
 impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
-        vec![Box::new(m20250319_093000_create_tbl_users::Migration)]
+        vec![
+            Box::new(m20250319_093000_create_tbl_users::Migration),
+            Box::new(m20261018_100000_add_tbl_users_search::Migration),
+            Box::new(m20261018_110000_create_auth_tables::Migration),
+            Box::new(m20261018_120000_create_rbac_tables::Migration),
+            Box::new(m20261018_130000_add_tbl_users_version::Migration),
+            Box::new(m20261018_140000_create_tbl_rate_limit_buckets::Migration),
+            Box::new(m20261018_150000_create_tbl_user_audit::Migration),
+            Box::new(m20261018_160000_add_tbl_user_audit_chain::Migration),
+            Box::new(m20261018_170000_create_tbl_outbox_events::Migration),
+            Box::new(m20261018_180000_create_webhook_tables::Migration),
+        ]
     }
 }
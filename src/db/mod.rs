@@ -1,3 +1,8 @@
+mod connection;
+mod instrument;
 pub mod migrations;
 pub mod models;
 pub mod repositories;
+
+pub use connection::{connect, connect_options};
+pub use instrument::{RowCount, StatementKind, instrumented};
@@ -0,0 +1,26 @@
+use sea_orm::{entity::prelude::*, sqlx::types::chrono::NaiveDateTime};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, PartialEq, DeriveEntityModel, Serialize, ToSchema)]
+#[sea_orm(table_name = "tbl_audit_checkpoints")]
+#[schema(as = AuditCheckpointModel)]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// Newest audit entry covered by the checkpoint.
+    pub last_entry_id: i64,
+    /// That entry's `hash`.
+    pub hash: String,
+    /// Base64 Ed25519 signature over `tbl_user_audit:{last_entry_id}:{hash}`.
+    pub signature: String,
+    /// Identifies the signing key: the first 16 hex characters of the
+    /// SHA-256 of its public key.
+    pub key_id: String,
+    pub created_on: NaiveDateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,16 @@
+use sea_orm::{entity::prelude::*, sqlx::types::chrono::NaiveDateTime};
+
+#[derive(Debug, Clone, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "tbl_user_credentials")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub user_id: i32,
+    pub password_hash: String,
+    pub created_on: NaiveDateTime,
+    pub updated_on: NaiveDateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -1,4 +1,53 @@
+pub mod audit_checkpoint;
+pub mod credential;
+pub mod outbox_event;
+pub mod refresh_token;
+pub mod role;
 pub mod user;
+pub mod user_audit;
+pub mod user_role;
+pub mod webhook_attempt;
+pub mod webhook_delivery;
+pub mod webhook_subscription;
+pub use audit_checkpoint::{
+    ActiveModel as AuditCheckpointActiveModel, Column as AuditCheckpointColumn,
+    Entity as AuditCheckpointEntity, Model as AuditCheckpointModel,
+};
+pub use credential::{
+    ActiveModel as CredentialActiveModel, Column as CredentialColumn, Entity as CredentialEntity,
+    Model as CredentialModel,
+};
+pub use outbox_event::{
+    ActiveModel as OutboxEventActiveModel, Column as OutboxEventColumn,
+    Entity as OutboxEventEntity, Model as OutboxEventModel,
+};
+pub use refresh_token::{
+    ActiveModel as RefreshTokenActiveModel, Column as RefreshTokenColumn,
+    Entity as RefreshTokenEntity, Model as RefreshTokenModel,
+};
+pub use role::{
+    ActiveModel as RoleActiveModel, Column as RoleColumn, Entity as RoleEntity, Model as RoleModel,
+};
 pub use user::{
     ActiveModel as UserActiveModel, Column as UserColumn, Entity as UserEntity, Model as UserModel,
 };
+pub use user_audit::{
+    ActiveModel as UserAuditActiveModel, Column as UserAuditColumn, Entity as UserAuditEntity,
+    Model as UserAuditModel,
+};
+pub use user_role::{
+    ActiveModel as UserRoleActiveModel, Column as UserRoleColumn, Entity as UserRoleEntity,
+    Model as UserRoleModel,
+};
+pub use webhook_attempt::{
+    ActiveModel as WebhookAttemptActiveModel, Column as WebhookAttemptColumn,
+    Entity as WebhookAttemptEntity, Model as WebhookAttemptModel,
+};
+pub use webhook_delivery::{
+    ActiveModel as WebhookDeliveryActiveModel, Column as WebhookDeliveryColumn,
+    Entity as WebhookDeliveryEntity, Model as WebhookDeliveryModel,
+};
+pub use webhook_subscription::{
+    ActiveModel as WebhookSubscriptionActiveModel, Column as WebhookSubscriptionColumn,
+    Entity as WebhookSubscriptionEntity, Model as WebhookSubscriptionModel,
+};
@@ -0,0 +1,35 @@
+use sea_orm::{entity::prelude::*, sqlx::types::chrono::NaiveDateTime};
+
+#[derive(Debug, Clone, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "tbl_outbox_events")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// Stable across redeliveries, so sinks can drop duplicates.
+    pub event_id: Uuid,
+    /// E.g. `UserCreated`.
+    pub event_type: String,
+    /// `user`.
+    pub aggregate_type: String,
+    pub aggregate_id: String,
+    /// The event, tagged with its `type`.
+    pub payload: Json,
+    /// User who made the change; absent for unauthenticated sign-ups.
+    pub actor_id: Option<i32>,
+    pub request_id: Option<String>,
+    pub occurred_on: NaiveDateTime,
+    /// `pending`, `delivered` or `failed`.
+    pub status: String,
+    /// Delivery attempts started, including ones cut short by a crash.
+    pub attempts: i32,
+    /// When a pending event is next due; claimed events are leased by
+    /// pushing this forward.
+    pub next_attempt_on: NaiveDateTime,
+    pub last_error: Option<String>,
+    pub delivered_on: Option<NaiveDateTime>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
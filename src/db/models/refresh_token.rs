@@ -0,0 +1,20 @@
+use sea_orm::{entity::prelude::*, sqlx::types::chrono::NaiveDateTime};
+
+#[derive(Debug, Clone, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "tbl_refresh_tokens")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub user_id: i32,
+    pub token_hash: String,
+    pub family_id: Uuid,
+    pub created_on: NaiveDateTime,
+    pub expires_on: NaiveDateTime,
+    pub revoked_on: Option<NaiveDateTime>,
+    pub replaced_by: Option<i32>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -1,8 +1,13 @@
 use sea_orm::{entity::prelude::*, sqlx::types::chrono::NaiveDateTime};
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use utoipa::ToSchema;
 
-#[derive(Debug, Clone, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+use crate::redaction::Redacted;
+
+#[derive(Clone, PartialEq, DeriveEntityModel, Serialize, Deserialize, ToSchema)]
 #[sea_orm(table_name = "tbl_users")]
+#[schema(as = UserModel)]
 pub struct Model {
     #[sea_orm(primary_key)]
     pub id: i32,
@@ -14,6 +19,26 @@ pub struct Model {
     pub created_on: NaiveDateTime,
     pub updated_on: NaiveDateTime,
     pub deleted_on: Option<NaiveDateTime>,
+    /// Incremented on every write; exposed as the user's `ETag`.
+    pub version: i32,
+}
+
+/// Personal fields are redacted so models can be logged safely.
+impl fmt::Debug for Model {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Model")
+            .field("id", &self.id)
+            .field("username", &Redacted::identifier(&self.username))
+            .field("first_name", &self.first_name.as_ref().map(Redacted::name))
+            .field("last_name", &self.last_name.as_ref().map(Redacted::name))
+            .field("email", &Redacted::email(&self.email))
+            .field("phone", &self.phone.as_ref().map(Redacted::phone))
+            .field("created_on", &self.created_on)
+            .field("updated_on", &self.updated_on)
+            .field("deleted_on", &self.deleted_on)
+            .field("version", &self.version)
+            .finish()
+    }
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -0,0 +1,34 @@
+use sea_orm::{entity::prelude::*, sqlx::types::chrono::NaiveDateTime};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, PartialEq, DeriveEntityModel, Serialize, ToSchema)]
+#[sea_orm(table_name = "tbl_user_audit")]
+#[schema(as = UserAuditModel)]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    pub user_id: i32,
+    /// `created`, `updated`, `soft_deleted`, `restored` or `deleted`.
+    pub action: String,
+    /// User who made the change; absent for unauthenticated sign-ups.
+    pub actor_id: Option<i32>,
+    pub request_id: Option<String>,
+    pub client_ip: Option<String>,
+    /// Changed fields before the mutation; absent on create.
+    #[schema(value_type = Option<Object>)]
+    pub before: Option<Json>,
+    /// Changed fields after the mutation; absent on physical delete.
+    #[schema(value_type = Option<Object>)]
+    pub after: Option<Json>,
+    pub created_on: NaiveDateTime,
+    /// `hash` of the previous entry, chaining the log.
+    pub prev_hash: String,
+    /// SHA-256 over `prev_hash` and this entry's canonical JSON.
+    pub hash: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
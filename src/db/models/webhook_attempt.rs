@@ -0,0 +1,24 @@
+use sea_orm::{entity::prelude::*, sqlx::types::chrono::NaiveDateTime};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, PartialEq, DeriveEntityModel, Serialize, ToSchema)]
+#[sea_orm(table_name = "tbl_webhook_attempts")]
+#[schema(as = WebhookAttemptModel)]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    pub delivery_id: i64,
+    /// Absent when no response was received.
+    pub status_code: Option<i32>,
+    pub error: Option<String>,
+    /// The start of the response body.
+    pub response_body: Option<String>,
+    pub duration_ms: i32,
+    pub attempted_on: NaiveDateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
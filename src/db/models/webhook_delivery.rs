@@ -0,0 +1,31 @@
+use sea_orm::{entity::prelude::*, sqlx::types::chrono::NaiveDateTime};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, PartialEq, DeriveEntityModel, Serialize, ToSchema)]
+#[sea_orm(table_name = "tbl_webhook_deliveries")]
+#[schema(as = WebhookDeliveryModel)]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    pub subscription_id: i32,
+    /// Sent as `X-Webhook-Id`; the same on every attempt.
+    pub event_id: Uuid,
+    pub event_type: String,
+    /// The request body, sent unchanged on every attempt.
+    pub body: String,
+    /// `pending`, `delivered` or `dead`.
+    pub status: String,
+    pub attempts: i32,
+    /// When a pending delivery is next due.
+    pub next_attempt_on: NaiveDateTime,
+    pub last_status_code: Option<i32>,
+    pub last_error: Option<String>,
+    pub created_on: NaiveDateTime,
+    pub delivered_on: Option<NaiveDateTime>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,47 @@
+use sea_orm::{entity::prelude::*, sqlx::types::chrono::NaiveDateTime};
+use serde::Serialize;
+use std::fmt;
+use utoipa::ToSchema;
+
+use crate::redaction::Redacted;
+
+#[derive(Clone, PartialEq, DeriveEntityModel, Serialize, ToSchema)]
+#[sea_orm(table_name = "tbl_webhook_subscriptions")]
+#[schema(as = WebhookSubscriptionModel)]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub url: String,
+    /// HMAC key signing each delivery; only returned when created.
+    #[serde(skip)]
+    pub secret: String,
+    /// Event types delivered, e.g. `UserCreated`; every type when empty.
+    #[schema(value_type = Vec<String>)]
+    pub event_types: Json,
+    /// Inactive subscriptions receive no new deliveries.
+    pub active: bool,
+    pub created_by: Option<i32>,
+    pub created_on: NaiveDateTime,
+    pub updated_on: NaiveDateTime,
+}
+
+/// The secret is redacted so models can be logged safely.
+impl fmt::Debug for Model {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Model")
+            .field("id", &self.id)
+            .field("url", &self.url)
+            .field("secret", &Redacted::new(&self.secret))
+            .field("event_types", &self.event_types)
+            .field("active", &self.active)
+            .field("created_by", &self.created_by)
+            .field("created_on", &self.created_on)
+            .field("updated_on", &self.updated_on)
+            .finish()
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,57 @@
+use sea_orm::{ActiveModelTrait, DatabaseConnection, DbErr, EntityTrait, Order, QueryOrder};
+use std::sync::Arc;
+
+use crate::db::models::{
+    AuditCheckpointActiveModel, AuditCheckpointColumn, AuditCheckpointEntity, AuditCheckpointModel,
+};
+use crate::db::{RowCount, StatementKind, instrumented};
+
+/// `repository` label of the query metrics and spans.
+const REPOSITORY: &str = "audit_checkpoint";
+
+impl RowCount for AuditCheckpointModel {
+    fn row_count(&self) -> u64 {
+        1
+    }
+}
+
+pub struct AuditCheckpointRepository {
+    db: Arc<DatabaseConnection>,
+}
+
+impl AuditCheckpointRepository {
+    pub fn new(db: Arc<DatabaseConnection>) -> Self {
+        Self { db }
+    }
+
+    pub async fn latest(&self) -> Result<Option<AuditCheckpointModel>, DbErr> {
+        instrumented(REPOSITORY, "latest", StatementKind::Select, async {
+            AuditCheckpointEntity::find()
+                .order_by(AuditCheckpointColumn::Id, Order::Desc)
+                .one(self.db.as_ref())
+                .await
+        })
+        .await
+    }
+
+    /// Every checkpoint, oldest first.
+    pub async fn find_all(&self) -> Result<Vec<AuditCheckpointModel>, DbErr> {
+        instrumented(REPOSITORY, "find_all", StatementKind::Select, async {
+            AuditCheckpointEntity::find()
+                .order_by(AuditCheckpointColumn::Id, Order::Asc)
+                .all(self.db.as_ref())
+                .await
+        })
+        .await
+    }
+
+    pub async fn create(
+        &self,
+        model: AuditCheckpointActiveModel,
+    ) -> Result<AuditCheckpointModel, DbErr> {
+        instrumented(REPOSITORY, "create", StatementKind::Insert, async {
+            model.insert(self.db.as_ref()).await
+        })
+        .await
+    }
+}
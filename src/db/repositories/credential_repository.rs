@@ -0,0 +1,38 @@
+use crate::db::models::{CredentialActiveModel, CredentialEntity, CredentialModel};
+use sea_orm::prelude::*;
+use sea_orm::{ActiveModelTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, Set};
+use std::sync::Arc;
+
+pub struct CredentialRepository {
+    db: Arc<DatabaseConnection>,
+}
+
+impl CredentialRepository {
+    pub fn new(db: Arc<DatabaseConnection>) -> Self {
+        Self { db }
+    }
+
+    pub async fn find_by_user_id(&self, user_id: i32) -> Result<Option<CredentialModel>, DbErr> {
+        CredentialEntity::find_by_id(user_id)
+            .one(self.db.as_ref())
+            .await
+    }
+
+    /// Stores the first credential of a user; called by
+    /// [`super::UserRepository`] on the transaction creating the user.
+    pub(super) async fn insert<C: ConnectionTrait>(
+        conn: &C,
+        user_id: i32,
+        password_hash: String,
+        now: DateTime,
+    ) -> Result<CredentialModel, DbErr> {
+        CredentialActiveModel {
+            user_id: Set(user_id),
+            password_hash: Set(password_hash),
+            created_on: Set(now),
+            updated_on: Set(now),
+        }
+        .insert(conn)
+        .await
+    }
+}
@@ -1,3 +1,30 @@
+pub mod audit_checkpoint_repository;
+pub mod credential_repository;
+pub mod outbox_repository;
+pub mod rate_limit_repository;
+pub mod refresh_token_repository;
+pub mod role_repository;
+pub mod user_audit_repository;
+pub mod user_filter;
 pub mod user_repository;
+pub mod webhook_delivery_repository;
+pub mod webhook_subscription_repository;
 
-pub use user_repository::UserRepository;
+pub use audit_checkpoint_repository::AuditCheckpointRepository;
+pub use credential_repository::CredentialRepository;
+pub use outbox_repository::OutboxRepository;
+pub use rate_limit_repository::RateLimitRepository;
+pub use refresh_token_repository::RefreshTokenRepository;
+pub use role_repository::{RoleRepository, RoleWithPermissions, UserGrants};
+pub use user_audit_repository::{UserAuditPage, UserAuditQuery, UserAuditRepository};
+pub use user_filter::{
+    FieldKind, FilterOp, FilterValue, UserField, UserFilter, UserSort, user_field,
+};
+pub use user_repository::{
+    UserKeyset, UserPage, UserPageQuery, UserRepository, UserSearchHit, UserSearchQuery,
+    UserSortKey,
+};
+pub use webhook_delivery_repository::{
+    DeliveryOutcome, WebhookDeliveryPage, WebhookDeliveryQuery, WebhookDeliveryRepository,
+};
+pub use webhook_subscription_repository::WebhookSubscriptionRepository;
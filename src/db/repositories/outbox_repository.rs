@@ -0,0 +1,168 @@
+use sea_orm::prelude::*;
+use sea_orm::sea_query::Expr;
+use sea_orm::sqlx::types::chrono::Local;
+use sea_orm::{
+    ConnectionTrait, DatabaseConnection, DbBackend, DbErr, EntityTrait, Set, Statement,
+    UpdateResult,
+};
+use std::sync::Arc;
+
+use crate::audit::AuditContext;
+use crate::db::models::{
+    OutboxEventActiveModel, OutboxEventColumn, OutboxEventEntity, OutboxEventModel,
+};
+use crate::db::{StatementKind, instrumented};
+use crate::events::{DomainEvent, USER_AGGREGATE};
+
+/// `repository` label of the query metrics and spans.
+const REPOSITORY: &str = "outbox";
+
+/// Leases due events to the caller by pushing `next_attempt_on` past the
+/// lease. `SKIP LOCKED` lets several relays claim disjoint batches, and an
+/// event whose relay died becomes due again once the lease runs out.
+const CLAIM_SQL: &str = r#"
+UPDATE tbl_outbox_events
+SET attempts = attempts + 1,
+    next_attempt_on = LOCALTIMESTAMP + make_interval(secs => $1)
+WHERE id IN (
+    SELECT id FROM tbl_outbox_events
+    WHERE status = 'pending' AND next_attempt_on <= LOCALTIMESTAMP
+    ORDER BY id
+    LIMIT $2
+    FOR UPDATE SKIP LOCKED
+)
+RETURNING *
+"#;
+
+pub struct OutboxRepository {
+    db: Arc<DatabaseConnection>,
+}
+
+impl OutboxRepository {
+    pub fn new(db: Arc<DatabaseConnection>) -> Self {
+        Self { db }
+    }
+
+    /// Stores events for delivery; called by [`super::UserRepository`] on
+    /// the transaction making the change, so events are published exactly
+    /// when the change commits.
+    pub(super) async fn append<C: ConnectionTrait>(
+        conn: &C,
+        events: Vec<DomainEvent>,
+        context: &AuditContext,
+    ) -> Result<(), DbErr> {
+        if events.is_empty() {
+            return Ok(());
+        }
+
+        let now = Local::now().naive_local();
+        let rows = events.into_iter().map(|event| OutboxEventActiveModel {
+            event_id: Set(Uuid::new_v4()),
+            event_type: Set(event.event_type().to_string()),
+            aggregate_type: Set(USER_AGGREGATE.to_string()),
+            aggregate_id: Set(event.user_id().to_string()),
+            payload: Set(serde_json::to_value(&event).unwrap_or_default()),
+            actor_id: Set(context.actor_id),
+            request_id: Set(context.request_id.clone()),
+            occurred_on: Set(now),
+            next_attempt_on: Set(now),
+            ..Default::default()
+        });
+        OutboxEventEntity::insert_many(rows).exec(conn).await?;
+        Ok(())
+    }
+
+    /// Claims up to `limit` due events, oldest first, for `lease_secs`.
+    pub async fn claim_due(
+        &self,
+        limit: u64,
+        lease_secs: u64,
+    ) -> Result<Vec<OutboxEventModel>, DbErr> {
+        instrumented(REPOSITORY, "claim_due", StatementKind::Update, async {
+            let mut events = OutboxEventEntity::find()
+                .from_raw_sql(Statement::from_sql_and_values(
+                    DbBackend::Postgres,
+                    CLAIM_SQL,
+                    [(lease_secs as f64).into(), (limit as i64).into()],
+                ))
+                .all(self.db.as_ref())
+                .await?;
+            events.sort_by_key(|event| event.id);
+            Ok(events)
+        })
+        .await
+    }
+
+    /// Extends the lease on a claimed event as its delivery starts, so a
+    /// slow batch cannot let the lease on later events run out. Returns
+    /// `false` when it already has, as another relay may have claimed the
+    /// event since.
+    pub async fn renew_lease(
+        &self,
+        event: &OutboxEventModel,
+        lease_secs: u64,
+    ) -> Result<bool, DbErr> {
+        let result = instrumented(REPOSITORY, "renew_lease", StatementKind::Update, async {
+            OutboxEventEntity::update_many()
+                .col_expr(
+                    OutboxEventColumn::NextAttemptOn,
+                    Expr::cust_with_values(
+                        "LOCALTIMESTAMP + make_interval(secs => $1)",
+                        [lease_secs as f64],
+                    ),
+                )
+                .filter(OutboxEventColumn::Id.eq(event.id))
+                .filter(OutboxEventColumn::Status.eq("pending"))
+                .filter(OutboxEventColumn::Attempts.eq(event.attempts))
+                .filter(
+                    Expr::col(OutboxEventColumn::NextAttemptOn).gt(Expr::cust("LOCALTIMESTAMP")),
+                )
+                .exec(self.db.as_ref())
+                .await
+        })
+        .await?;
+        Ok(result.rows_affected == 1)
+    }
+
+    pub async fn mark_delivered(&self, id: i64) -> Result<UpdateResult, DbErr> {
+        instrumented(REPOSITORY, "mark_delivered", StatementKind::Update, async {
+            OutboxEventEntity::update_many()
+                .col_expr(OutboxEventColumn::Status, Expr::value("delivered"))
+                .col_expr(
+                    OutboxEventColumn::DeliveredOn,
+                    Expr::value(Local::now().naive_local()),
+                )
+                .col_expr(
+                    OutboxEventColumn::LastError,
+                    Expr::value(Option::<String>::None),
+                )
+                .filter(OutboxEventColumn::Id.eq(id))
+                .exec(self.db.as_ref())
+                .await
+        })
+        .await
+    }
+
+    /// Records a failed attempt, scheduling the next one at `retry_on`, or
+    /// giving up on the event when there is none.
+    pub async fn mark_failed(
+        &self,
+        id: i64,
+        error: &str,
+        retry_on: Option<DateTime>,
+    ) -> Result<UpdateResult, DbErr> {
+        instrumented(REPOSITORY, "mark_failed", StatementKind::Update, async {
+            let update = OutboxEventEntity::update_many()
+                .col_expr(OutboxEventColumn::LastError, Expr::value(error))
+                .filter(OutboxEventColumn::Id.eq(id));
+            let update = match retry_on {
+                Some(retry_on) => {
+                    update.col_expr(OutboxEventColumn::NextAttemptOn, Expr::value(retry_on))
+                }
+                None => update.col_expr(OutboxEventColumn::Status, Expr::value("failed")),
+            };
+            update.exec(self.db.as_ref()).await
+        })
+        .await
+    }
+}
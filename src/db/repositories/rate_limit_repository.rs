@@ -0,0 +1,65 @@
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, DbErr, Statement};
+use std::sync::Arc;
+
+/// Refills the bucket for the time since its last update, then takes one
+/// token if available, in a single statement so concurrent replicas cannot
+/// both spend the last token.
+const TAKE_SQL: &str = r#"
+INSERT INTO tbl_rate_limit_buckets AS b (key, tokens, allowed, updated_on)
+VALUES ($1, $2 - 1, TRUE, LOCALTIMESTAMP)
+ON CONFLICT (key) DO UPDATE SET
+    tokens = LEAST($2, b.tokens + EXTRACT(EPOCH FROM LOCALTIMESTAMP - b.updated_on)::float8 * $3)
+        - CASE
+            WHEN LEAST($2, b.tokens + EXTRACT(EPOCH FROM LOCALTIMESTAMP - b.updated_on)::float8 * $3) >= 1
+            THEN 1 ELSE 0
+          END,
+    allowed = LEAST($2, b.tokens + EXTRACT(EPOCH FROM LOCALTIMESTAMP - b.updated_on)::float8 * $3) >= 1,
+    updated_on = LOCALTIMESTAMP
+RETURNING tokens, allowed
+"#;
+
+const PURGE_SQL: &str = r#"
+DELETE FROM tbl_rate_limit_buckets
+WHERE updated_on < LOCALTIMESTAMP - make_interval(secs => $1)
+"#;
+
+pub struct RateLimitRepository {
+    db: Arc<DatabaseConnection>,
+}
+
+impl RateLimitRepository {
+    pub fn new(db: Arc<DatabaseConnection>) -> Self {
+        Self { db }
+    }
+
+    /// Takes a token from the bucket `key` holding at most `capacity`
+    /// tokens and refilling `refill_per_sec` per second. Returns the tokens
+    /// left and whether one was taken.
+    pub async fn take(
+        &self,
+        key: &str,
+        capacity: f64,
+        refill_per_sec: f64,
+    ) -> Result<(f64, bool), DbErr> {
+        let statement = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            TAKE_SQL,
+            [key.into(), capacity.into(), refill_per_sec.into()],
+        );
+
+        let row = self
+            .db
+            .query_one(statement)
+            .await?
+            .ok_or_else(|| DbErr::RecordNotFound("rate limit bucket".into()))?;
+
+        Ok((row.try_get("", "tokens")?, row.try_get("", "allowed")?))
+    }
+
+    /// Deletes buckets untouched for `idle_secs`; they would be full again.
+    pub async fn purge_idle(&self, idle_secs: f64) -> Result<u64, DbErr> {
+        let statement =
+            Statement::from_sql_and_values(DbBackend::Postgres, PURGE_SQL, [idle_secs.into()]);
+        Ok(self.db.execute(statement).await?.rows_affected())
+    }
+}
@@ -0,0 +1,78 @@
+use crate::db::models::{
+    RefreshTokenActiveModel, RefreshTokenColumn, RefreshTokenEntity, RefreshTokenModel,
+};
+use sea_orm::prelude::*;
+use sea_orm::sea_query::Expr;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter,
+    TransactionTrait,
+};
+use std::sync::Arc;
+
+pub struct RefreshTokenRepository {
+    db: Arc<DatabaseConnection>,
+}
+
+impl RefreshTokenRepository {
+    pub fn new(db: Arc<DatabaseConnection>) -> Self {
+        Self { db }
+    }
+
+    pub async fn find_by_hash(&self, token_hash: &str) -> Result<Option<RefreshTokenModel>, DbErr> {
+        RefreshTokenEntity::find()
+            .filter(RefreshTokenColumn::TokenHash.eq(token_hash))
+            .one(self.db.as_ref())
+            .await
+    }
+
+    pub async fn create(&self, model: RefreshTokenActiveModel) -> Result<RefreshTokenModel, DbErr> {
+        model.insert(self.db.as_ref()).await
+    }
+
+    /// Revokes `current` and stores `next` as its replacement in one transaction.
+    ///
+    /// Returns `None` when `current` was already revoked, which means another
+    /// request rotated it first and the caller is presenting a reused token.
+    pub async fn rotate(
+        &self,
+        current: &RefreshTokenModel,
+        next: RefreshTokenActiveModel,
+        now: DateTime,
+    ) -> Result<Option<RefreshTokenModel>, DbErr> {
+        let txn = self.db.begin().await?;
+
+        let revoked = RefreshTokenEntity::update_many()
+            .col_expr(RefreshTokenColumn::RevokedOn, Expr::value(now))
+            .filter(RefreshTokenColumn::Id.eq(current.id))
+            .filter(RefreshTokenColumn::RevokedOn.is_null())
+            .exec(&txn)
+            .await?;
+
+        if revoked.rows_affected == 0 {
+            txn.rollback().await?;
+            return Ok(None);
+        }
+
+        let next = next.insert(&txn).await?;
+
+        RefreshTokenEntity::update_many()
+            .col_expr(RefreshTokenColumn::ReplacedBy, Expr::value(next.id))
+            .filter(RefreshTokenColumn::Id.eq(current.id))
+            .exec(&txn)
+            .await?;
+
+        txn.commit().await?;
+        Ok(Some(next))
+    }
+
+    pub async fn revoke_family(&self, family_id: Uuid, now: DateTime) -> Result<u64, DbErr> {
+        let result = RefreshTokenEntity::update_many()
+            .col_expr(RefreshTokenColumn::RevokedOn, Expr::value(now))
+            .filter(RefreshTokenColumn::FamilyId.eq(family_id))
+            .filter(RefreshTokenColumn::RevokedOn.is_null())
+            .exec(self.db.as_ref())
+            .await?;
+
+        Ok(result.rows_affected)
+    }
+}
@@ -0,0 +1,162 @@
+use crate::db::models::{
+    RoleColumn, RoleEntity, RoleModel, UserRoleActiveModel, UserRoleColumn, UserRoleEntity,
+};
+use sea_orm::prelude::*;
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend, DbErr, EntityTrait,
+    PaginatorTrait, QueryFilter, QueryOrder, Set, Statement,
+};
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+/// Role names and effective permissions held by a user.
+#[derive(Debug, Clone, Default)]
+pub struct UserGrants {
+    pub roles: BTreeSet<String>,
+    pub permissions: BTreeSet<String>,
+}
+
+pub struct RoleWithPermissions {
+    pub role: RoleModel,
+    pub permissions: Vec<String>,
+}
+
+const USER_GRANTS_SQL: &str = r#"
+SELECT r.name AS role, p.name AS permission
+FROM tbl_user_roles ur
+JOIN tbl_roles r ON r.id = ur.role_id
+LEFT JOIN tbl_role_permissions rp ON rp.role_id = r.id
+LEFT JOIN tbl_permissions p ON p.id = rp.permission_id
+WHERE ur.user_id = $1
+"#;
+
+const ROLE_PERMISSIONS_SQL: &str = r#"
+SELECT rp.role_id, p.name AS permission
+FROM tbl_role_permissions rp
+JOIN tbl_permissions p ON p.id = rp.permission_id
+ORDER BY p.name
+"#;
+
+pub struct RoleRepository {
+    db: Arc<DatabaseConnection>,
+}
+
+impl RoleRepository {
+    pub fn new(db: Arc<DatabaseConnection>) -> Self {
+        Self { db }
+    }
+
+    pub async fn find_all_with_permissions(&self) -> Result<Vec<RoleWithPermissions>, DbErr> {
+        let roles = RoleEntity::find()
+            .order_by_asc(RoleColumn::Name)
+            .all(self.db.as_ref())
+            .await?;
+
+        let rows = self
+            .db
+            .query_all(Statement::from_string(
+                DbBackend::Postgres,
+                ROLE_PERMISSIONS_SQL,
+            ))
+            .await?;
+
+        let mut grants: Vec<(i32, String)> = Vec::with_capacity(rows.len());
+        for row in rows {
+            grants.push((row.try_get("", "role_id")?, row.try_get("", "permission")?));
+        }
+
+        Ok(roles
+            .into_iter()
+            .map(|role| RoleWithPermissions {
+                permissions: grants
+                    .iter()
+                    .filter(|(role_id, _)| *role_id == role.id)
+                    .map(|(_, permission)| permission.clone())
+                    .collect(),
+                role,
+            })
+            .collect())
+    }
+
+    pub async fn find_by_name(&self, name: &str) -> Result<Option<RoleModel>, DbErr> {
+        RoleEntity::find()
+            .filter(RoleColumn::Name.eq(name))
+            .one(self.db.as_ref())
+            .await
+    }
+
+    pub async fn find_for_user(&self, user_id: i32) -> Result<Vec<RoleModel>, DbErr> {
+        let role_ids: Vec<i32> = UserRoleEntity::find()
+            .filter(UserRoleColumn::UserId.eq(user_id))
+            .all(self.db.as_ref())
+            .await?
+            .into_iter()
+            .map(|user_role| user_role.role_id)
+            .collect();
+
+        RoleEntity::find()
+            .filter(RoleColumn::Id.is_in(role_ids))
+            .order_by_asc(RoleColumn::Name)
+            .all(self.db.as_ref())
+            .await
+    }
+
+    pub async fn find_grants(&self, user_id: i32) -> Result<UserGrants, DbErr> {
+        let rows = self
+            .db
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                USER_GRANTS_SQL,
+                [user_id.into()],
+            ))
+            .await?;
+
+        let mut grants = UserGrants::default();
+        for row in rows {
+            grants.roles.insert(row.try_get("", "role")?);
+            if let Some(permission) = row.try_get::<Option<String>>("", "permission")? {
+                grants.permissions.insert(permission);
+            }
+        }
+
+        Ok(grants)
+    }
+
+    /// Whether anyone holds the role.
+    pub async fn has_members(&self, role_id: i32) -> Result<bool, DbErr> {
+        let members = UserRoleEntity::find()
+            .filter(UserRoleColumn::RoleId.eq(role_id))
+            .count(self.db.as_ref())
+            .await?;
+
+        Ok(members > 0)
+    }
+
+    /// Returns `false` when the user already had the role.
+    pub async fn assign(&self, user_id: i32, role_id: i32, now: DateTime) -> Result<bool, DbErr> {
+        let result = UserRoleEntity::insert(UserRoleActiveModel {
+            user_id: Set(user_id),
+            role_id: Set(role_id),
+            assigned_on: Set(now),
+        })
+        .on_conflict(
+            OnConflict::columns([UserRoleColumn::UserId, UserRoleColumn::RoleId])
+                .do_nothing()
+                .to_owned(),
+        )
+        .exec_without_returning(self.db.as_ref())
+        .await?;
+
+        Ok(result > 0)
+    }
+
+    /// Returns `false` when the user did not have the role.
+    pub async fn revoke(&self, user_id: i32, role_id: i32) -> Result<bool, DbErr> {
+        let result = UserRoleEntity::delete_by_id((user_id, role_id))
+            .exec(self.db.as_ref())
+            .await?;
+
+        Ok(result.rows_affected > 0)
+    }
+}
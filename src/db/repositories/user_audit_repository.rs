@@ -0,0 +1,182 @@
+use sea_orm::prelude::*;
+use sea_orm::sqlx::types::chrono::Local;
+use sea_orm::{
+    ActiveModelTrait, ConnectionTrait, DatabaseConnection, DbBackend, DbErr, EntityTrait, Order,
+    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Set, Statement,
+};
+use std::sync::Arc;
+
+use crate::audit::{self, AuditAction, AuditContext, chain};
+use crate::db::models::{
+    UserAuditActiveModel, UserAuditColumn, UserAuditEntity, UserAuditModel, UserModel,
+};
+use crate::db::{RowCount, StatementKind, instrumented};
+
+/// `repository` label of the query metrics and spans.
+const REPOSITORY: &str = "user_audit";
+
+/// Transaction-level advisory lock serializing writers, so each entry links
+/// to the one committed before it.
+const CHAIN_LOCK_KEY: i64 = 0x7573_6572_6175_6469;
+
+#[derive(Debug, Clone, Default)]
+pub struct UserAuditQuery {
+    pub user_id: Option<i32>,
+    pub actor_id: Option<i32>,
+    pub action: Option<AuditAction>,
+    pub request_id: Option<String>,
+    pub from: Option<DateTime>,
+    pub to: Option<DateTime>,
+    pub limit: u64,
+    pub offset: u64,
+}
+
+pub struct UserAuditPage {
+    pub entries: Vec<UserAuditModel>,
+    pub total: u64,
+}
+
+impl RowCount for UserAuditPage {
+    fn row_count(&self) -> u64 {
+        self.entries.len() as u64
+    }
+}
+
+pub struct UserAuditRepository {
+    db: Arc<DatabaseConnection>,
+}
+
+impl UserAuditRepository {
+    pub fn new(db: Arc<DatabaseConnection>) -> Self {
+        Self { db }
+    }
+
+    /// Records a change to a user, chained to the newest entry; called by
+    /// [`super::UserRepository`] on the transaction making the change.
+    ///
+    /// The hash is computed over the row as stored, so Postgres rounding
+    /// timestamps or normalizing JSON cannot break the chain.
+    pub(super) async fn record<C: ConnectionTrait>(
+        conn: &C,
+        action: AuditAction,
+        user_id: i32,
+        context: &AuditContext,
+        before: Option<&UserModel>,
+        after: Option<&UserModel>,
+    ) -> Result<UserAuditModel, DbErr> {
+        let (before, after) = audit::diff(before, after);
+
+        conn.execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT pg_advisory_xact_lock($1)",
+            [CHAIN_LOCK_KEY.into()],
+        ))
+        .await?;
+        let prev_hash = Self::latest_link_on(conn)
+            .await?
+            .map(|(_, hash)| hash)
+            .unwrap_or_else(|| chain::GENESIS_HASH.to_string());
+
+        let entry = UserAuditActiveModel {
+            user_id: Set(user_id),
+            action: Set(action.as_str().to_string()),
+            actor_id: Set(context.actor_id),
+            request_id: Set(context.request_id.clone()),
+            client_ip: Set(context.client_ip.clone()),
+            before: Set(before),
+            after: Set(after),
+            created_on: Set(Local::now().naive_local()),
+            prev_hash: Set(prev_hash.clone()),
+            hash: Set(String::new()),
+            ..Default::default()
+        }
+        .insert(conn)
+        .await?;
+
+        let hash = chain::entry_hash(&prev_hash, &entry);
+        let mut entry: UserAuditActiveModel = entry.into();
+        entry.hash = Set(hash);
+        entry.update(conn).await
+    }
+
+    /// Id and hash of the newest entry.
+    pub async fn latest_link(&self) -> Result<Option<(i64, String)>, DbErr> {
+        instrumented(
+            REPOSITORY,
+            "latest_link",
+            StatementKind::Select,
+            Self::latest_link_on(self.db.as_ref()),
+        )
+        .await
+    }
+
+    async fn latest_link_on<C: ConnectionTrait>(conn: &C) -> Result<Option<(i64, String)>, DbErr> {
+        UserAuditEntity::find()
+            .select_only()
+            .column(UserAuditColumn::Id)
+            .column(UserAuditColumn::Hash)
+            .order_by(UserAuditColumn::Id, Order::Desc)
+            .into_tuple()
+            .one(conn)
+            .await
+    }
+
+    /// Up to `limit` entries after `after_id` in chain order.
+    pub async fn find_chain_batch(
+        &self,
+        after_id: i64,
+        limit: u64,
+    ) -> Result<Vec<UserAuditModel>, DbErr> {
+        instrumented(
+            REPOSITORY,
+            "find_chain_batch",
+            StatementKind::Select,
+            async {
+                UserAuditEntity::find()
+                    .filter(UserAuditColumn::Id.gt(after_id))
+                    .order_by(UserAuditColumn::Id, Order::Asc)
+                    .limit(limit)
+                    .all(self.db.as_ref())
+                    .await
+            },
+        )
+        .await
+    }
+
+    /// Entries matching `query`, newest first.
+    pub async fn find_page(&self, query: &UserAuditQuery) -> Result<UserAuditPage, DbErr> {
+        instrumented(REPOSITORY, "find_page", StatementKind::Select, async {
+            let mut select = UserAuditEntity::find();
+
+            if let Some(user_id) = query.user_id {
+                select = select.filter(UserAuditColumn::UserId.eq(user_id));
+            }
+            if let Some(actor_id) = query.actor_id {
+                select = select.filter(UserAuditColumn::ActorId.eq(actor_id));
+            }
+            if let Some(action) = query.action {
+                select = select.filter(UserAuditColumn::Action.eq(action.as_str()));
+            }
+            if let Some(request_id) = &query.request_id {
+                select = select.filter(UserAuditColumn::RequestId.eq(request_id.as_str()));
+            }
+            if let Some(from) = query.from {
+                select = select.filter(UserAuditColumn::CreatedOn.gte(from));
+            }
+            if let Some(to) = query.to {
+                select = select.filter(UserAuditColumn::CreatedOn.lt(to));
+            }
+
+            let total = select.clone().count(self.db.as_ref()).await?;
+            let entries = select
+                .order_by(UserAuditColumn::Id, Order::Desc)
+                .offset(query.offset)
+                .limit(query.limit)
+                .all(self.db.as_ref())
+                .await?;
+
+            Ok(UserAuditPage { entries, total })
+        })
+        .await
+    }
+}
@@ -0,0 +1,148 @@
+use crate::db::models::UserColumn;
+use sea_orm::prelude::*;
+use sea_orm::sea_query::SimpleExpr;
+use sea_orm::sea_query::extension::postgres::PgExpr;
+use sea_orm::{Order, Value};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    Integer,
+    Text,
+    Timestamp,
+}
+
+/// A `tbl_users` column that clients may filter and sort on.
+#[derive(Debug)]
+pub struct UserField {
+    pub name: &'static str,
+    pub column: UserColumn,
+    pub kind: FieldKind,
+    pub nullable: bool,
+}
+
+pub const USER_FIELDS: &[UserField] = &[
+    UserField {
+        name: "id",
+        column: UserColumn::Id,
+        kind: FieldKind::Integer,
+        nullable: false,
+    },
+    UserField {
+        name: "username",
+        column: UserColumn::Username,
+        kind: FieldKind::Text,
+        nullable: false,
+    },
+    UserField {
+        name: "first_name",
+        column: UserColumn::FirstName,
+        kind: FieldKind::Text,
+        nullable: true,
+    },
+    UserField {
+        name: "last_name",
+        column: UserColumn::LastName,
+        kind: FieldKind::Text,
+        nullable: true,
+    },
+    UserField {
+        name: "email",
+        column: UserColumn::Email,
+        kind: FieldKind::Text,
+        nullable: false,
+    },
+    UserField {
+        name: "phone",
+        column: UserColumn::Phone,
+        kind: FieldKind::Text,
+        nullable: true,
+    },
+    UserField {
+        name: "created_on",
+        column: UserColumn::CreatedOn,
+        kind: FieldKind::Timestamp,
+        nullable: false,
+    },
+    UserField {
+        name: "updated_on",
+        column: UserColumn::UpdatedOn,
+        kind: FieldKind::Timestamp,
+        nullable: false,
+    },
+    UserField {
+        name: "deleted_on",
+        column: UserColumn::DeletedOn,
+        kind: FieldKind::Timestamp,
+        nullable: true,
+    },
+];
+
+pub fn user_field(name: &str) -> Option<&'static UserField> {
+    USER_FIELDS.iter().find(|field| field.name == name)
+}
+
+#[derive(Debug, Clone)]
+pub enum FilterValue {
+    Integer(i32),
+    Text(String),
+    Timestamp(DateTime),
+}
+
+impl From<FilterValue> for Value {
+    fn from(value: FilterValue) -> Self {
+        match value {
+            FilterValue::Integer(v) => v.into(),
+            FilterValue::Text(v) => v.into(),
+            FilterValue::Timestamp(v) => v.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum FilterOp {
+    Eq(FilterValue),
+    Prefix(String),
+    ILike(String),
+    Gt(FilterValue),
+    Gte(FilterValue),
+    Lt(FilterValue),
+    Lte(FilterValue),
+    IsNull(bool),
+}
+
+#[derive(Debug, Clone)]
+pub struct UserFilter {
+    pub column: UserColumn,
+    pub op: FilterOp,
+}
+
+impl UserFilter {
+    pub fn condition(&self) -> SimpleExpr {
+        let column = self.column;
+        match &self.op {
+            FilterOp::Eq(v) => column.eq(Value::from(v.clone())),
+            FilterOp::Prefix(s) => Expr::col(column).like(format!("{}%", escape(s))),
+            FilterOp::ILike(s) => Expr::col(column).ilike(format!("%{}%", escape(s))),
+            FilterOp::Gt(v) => column.gt(Value::from(v.clone())),
+            FilterOp::Gte(v) => column.gte(Value::from(v.clone())),
+            FilterOp::Lt(v) => column.lt(Value::from(v.clone())),
+            FilterOp::Lte(v) => column.lte(Value::from(v.clone())),
+            FilterOp::IsNull(true) => column.is_null(),
+            FilterOp::IsNull(false) => column.is_not_null(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct UserSort {
+    pub column: UserColumn,
+    pub order: Order,
+}
+
+/// Escapes LIKE wildcards; backslash is Postgres' default LIKE escape character.
+fn escape(input: &str) -> String {
+    input
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
@@ -1,10 +1,102 @@
+use super::user_filter::{UserFilter, UserSort};
+use super::{CredentialRepository, OutboxRepository, UserAuditRepository};
+use crate::audit::{AuditAction, AuditContext};
 use crate::db::models::{UserActiveModel, UserColumn, UserEntity, UserModel};
+use crate::db::{RowCount, StatementKind, instrumented};
+use crate::events::DomainEvent;
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, Set,
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, DbBackend,
+    DbErr, EntityTrait, FromQueryResult, Order, PaginatorTrait, QueryFilter, QueryOrder,
+    QuerySelect, Set, Statement, TransactionTrait,
 };
 use sea_orm::{DeleteResult, prelude::*};
 use std::sync::Arc;
 
+/// `repository` label of the query metrics and spans.
+const REPOSITORY: &str = "user";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserSortKey {
+    Id,
+    CreatedOn,
+}
+
+/// Position of the last row of a page, used to resume a keyset scan.
+#[derive(Debug, Clone)]
+pub struct UserKeyset {
+    pub id: i32,
+    pub created_on: DateTime,
+}
+
+#[derive(Debug, Clone)]
+pub struct UserPageQuery {
+    pub include_deleted: bool,
+    pub filters: Vec<UserFilter>,
+    /// Explicit sort columns; when empty the page is ordered by `sort_key`.
+    pub sort: Vec<UserSort>,
+    pub sort_key: UserSortKey,
+    pub limit: u64,
+    pub offset: u64,
+    pub after: Option<UserKeyset>,
+}
+
+pub struct UserPage {
+    pub users: Vec<UserModel>,
+    pub total: u64,
+    pub has_more: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct UserSearchQuery {
+    /// Raw search text, compared by trigram similarity.
+    pub text: String,
+    /// Prefix `tsquery` built from the search terms, e.g. `john:* & smi:*`.
+    pub ts_query: String,
+    /// Digits of the search text, matched against phone numbers when non-empty.
+    pub phone_digits: String,
+    pub include_deleted: bool,
+    pub limit: u64,
+}
+
+pub struct UserSearchHit {
+    pub user: UserModel,
+    pub rank: f64,
+}
+
+const SEARCH_SQL: &str = r#"
+WITH params AS (SELECT to_tsquery('simple', $1) AS tsq)
+SELECT u.id, u.username, u.first_name, u.last_name, u.email, u.phone,
+       u.created_on, u.updated_on, u.deleted_on, u.version,
+       (ts_rank(u.search_vector, p.tsq) + greatest(
+            similarity(u.username, $2),
+            similarity(coalesce(u.first_name, ''), $2),
+            similarity(coalesce(u.last_name, ''), $2),
+            similarity(u.email, $2)
+       ))::float8 AS rank
+FROM tbl_users u, params p
+WHERE (u.search_vector @@ p.tsq
+       OR u.username % $2
+       OR u.first_name % $2
+       OR u.last_name % $2
+       OR u.email % $2
+       OR ($3 <> '' AND regexp_replace(coalesce(u.phone, ''), '\D', '', 'g') LIKE '%' || $3 || '%'))
+  AND ($4 OR u.deleted_on IS NULL)
+ORDER BY rank DESC, u.id
+LIMIT $5
+"#;
+
+impl RowCount for UserPage {
+    fn row_count(&self) -> u64 {
+        self.users.len() as u64
+    }
+}
+
+impl RowCount for UserModel {
+    fn row_count(&self) -> u64 {
+        1
+    }
+}
+
 pub struct UserRepository {
     db: Arc<DatabaseConnection>,
 }
@@ -14,71 +106,276 @@ impl UserRepository {
         Self { db }
     }
 
-    pub async fn find_all(&self, include_deleted: bool) -> Result<Vec<UserModel>, DbErr> {
-        let mut query = UserEntity::find();
+    pub async fn find_page(&self, page: &UserPageQuery) -> Result<UserPage, DbErr> {
+        instrumented(REPOSITORY, "find_page", StatementKind::Select, async {
+            let mut query = UserEntity::find();
+
+            if !page.include_deleted {
+                query = query.filter(UserColumn::DeletedOn.is_null());
+            }
+
+            for filter in &page.filters {
+                query = query.filter(filter.condition());
+            }
+
+            let total = query.clone().count(self.db.as_ref()).await?;
+
+            if let Some(after) = &page.after {
+                query = query.filter(match page.sort_key {
+                    UserSortKey::Id => Condition::all().add(UserColumn::Id.gt(after.id)),
+                    UserSortKey::CreatedOn => Condition::any()
+                        .add(UserColumn::CreatedOn.gt(after.created_on))
+                        .add(
+                            Condition::all()
+                                .add(UserColumn::CreatedOn.eq(after.created_on))
+                                .add(UserColumn::Id.gt(after.id)),
+                        ),
+                });
+            }
 
-        if !include_deleted {
-            query = query.filter(UserColumn::DeletedOn.is_null());
-        }
+            if page.sort.is_empty() && page.sort_key == UserSortKey::CreatedOn {
+                query = query.order_by(UserColumn::CreatedOn, Order::Asc);
+            }
 
-        query.all(self.db.as_ref()).await
+            for sort in &page.sort {
+                query = query.order_by(sort.column, sort.order.clone());
+            }
+
+            // Always finish on the primary key so pages are stable.
+            if !page
+                .sort
+                .iter()
+                .any(|sort| matches!(sort.column, UserColumn::Id))
+            {
+                query = query.order_by(UserColumn::Id, Order::Asc);
+            }
+
+            // Fetch one extra row so we know whether another page follows.
+            let mut users = query
+                .offset(page.offset)
+                .limit(page.limit + 1)
+                .all(self.db.as_ref())
+                .await?;
+
+            let has_more = users.len() as u64 > page.limit;
+            users.truncate(page.limit as usize);
+
+            Ok(UserPage {
+                users,
+                total,
+                has_more,
+            })
+        })
+        .await
+    }
+
+    pub async fn search(&self, query: &UserSearchQuery) -> Result<Vec<UserSearchHit>, DbErr> {
+        instrumented(REPOSITORY, "search", StatementKind::Select, async {
+            let statement = Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                SEARCH_SQL,
+                [
+                    query.ts_query.clone().into(),
+                    query.text.clone().into(),
+                    query.phone_digits.clone().into(),
+                    query.include_deleted.into(),
+                    (query.limit as i64).into(),
+                ],
+            );
+
+            let rows = self.db.query_all(statement).await?;
+
+            rows.iter()
+                .map(|row| {
+                    Ok(UserSearchHit {
+                        user: UserModel::from_query_result(row, "")?,
+                        rank: row.try_get("", "rank")?,
+                    })
+                })
+                .collect()
+        })
+        .await
     }
 
     pub async fn find_by_id(&self, id: i32) -> Result<Option<UserModel>, DbErr> {
-        UserEntity::find_by_id(id).one(self.db.as_ref()).await
+        instrumented(REPOSITORY, "find_by_id", StatementKind::Select, async {
+            UserEntity::find_by_id(id).one(self.db.as_ref()).await
+        })
+        .await
     }
 
     pub async fn find_by_username(&self, username: &str) -> Result<Option<UserModel>, DbErr> {
-        UserEntity::find()
-            .filter(UserColumn::Username.eq(username))
-            .one(self.db.as_ref())
-            .await
+        instrumented(
+            REPOSITORY,
+            "find_by_username",
+            StatementKind::Select,
+            async {
+                UserEntity::find()
+                    .filter(UserColumn::Username.eq(username))
+                    .one(self.db.as_ref())
+                    .await
+            },
+        )
+        .await
     }
 
     pub async fn find_by_email(&self, email: &str) -> Result<Option<UserModel>, DbErr> {
-        UserEntity::find()
-            .filter(UserColumn::Email.eq(email))
-            .one(self.db.as_ref())
-            .await
+        instrumented(REPOSITORY, "find_by_email", StatementKind::Select, async {
+            UserEntity::find()
+                .filter(UserColumn::Email.eq(email))
+                .one(self.db.as_ref())
+                .await
+        })
+        .await
     }
 
-    pub async fn create(&self, model: UserActiveModel) -> Result<UserModel, DbErr> {
-        model.insert(self.db.as_ref()).await
+    /// Inserts the user together with its password credential, if any, so
+    /// a failure cannot leave a user without the password it was created with.
+    pub async fn create(
+        &self,
+        model: UserActiveModel,
+        password_hash: Option<String>,
+        audit: &AuditContext,
+    ) -> Result<UserModel, DbErr> {
+        instrumented(REPOSITORY, "create", StatementKind::Insert, async {
+            let txn = self.db.begin().await?;
+            let user = model.insert(&txn).await?;
+            if let Some(password_hash) = password_hash {
+                CredentialRepository::insert(&txn, user.id, password_hash, user.created_on).await?;
+            }
+            UserAuditRepository::record(
+                &txn,
+                AuditAction::Created,
+                user.id,
+                audit,
+                None,
+                Some(&user),
+            )
+            .await?;
+            let events = DomainEvent::for_change(AuditAction::Created, None, Some(&user));
+            OutboxRepository::append(&txn, events, audit).await?;
+            txn.commit().await?;
+            Ok(user)
+        })
+        .await
     }
 
-    pub async fn update(&self, model: UserActiveModel) -> Result<UserModel, DbErr> {
-        model.update(self.db.as_ref()).await
+    /// Writes `model` over `user` only if the stored row is still at
+    /// `user`'s version, bumping the version. Returns `None` when another
+    /// write got there first.
+    pub async fn update(
+        &self,
+        user: &UserModel,
+        model: UserActiveModel,
+        audit: &AuditContext,
+    ) -> Result<Option<UserModel>, DbErr> {
+        instrumented(
+            REPOSITORY,
+            "update",
+            StatementKind::Update,
+            self.update_audited(AuditAction::Updated, user, model, audit),
+        )
+        .await
     }
 
-    pub async fn delete(&self, id: i32) -> Result<DeleteResult, DbErr> {
-        UserEntity::delete_by_id(id).exec(self.db.as_ref()).await
+    /// Deletes the row permanently; `rows_affected` is 0 if it was gone.
+    pub async fn delete(&self, id: i32, audit: &AuditContext) -> Result<DeleteResult, DbErr> {
+        instrumented(REPOSITORY, "delete", StatementKind::Delete, async {
+            let txn = self.db.begin().await?;
+            let Some(user) = UserEntity::find_by_id(id)
+                .lock_exclusive()
+                .one(&txn)
+                .await?
+            else {
+                return Ok(DeleteResult { rows_affected: 0 });
+            };
+
+            let result = UserEntity::delete_by_id(id).exec(&txn).await?;
+            UserAuditRepository::record(&txn, AuditAction::Deleted, id, audit, Some(&user), None)
+                .await?;
+            let events = DomainEvent::for_change(AuditAction::Deleted, Some(&user), None);
+            OutboxRepository::append(&txn, events, audit).await?;
+            txn.commit().await?;
+            Ok(result)
+        })
+        .await
     }
 
-    pub async fn soft_delete(&self, id: i32, now: DateTime) -> Result<Option<UserModel>, DbErr> {
-        let user = self.find_by_id(id).await?;
+    pub async fn soft_delete(
+        &self,
+        user: UserModel,
+        now: DateTime,
+        audit: &AuditContext,
+    ) -> Result<Option<UserModel>, DbErr> {
+        let mut active_model: UserActiveModel = user.clone().into();
+        active_model.deleted_on = Set(Some(now));
+        active_model.updated_on = Set(now);
 
-        if let Some(user) = user {
-            let mut active_model: UserActiveModel = user.into();
-            active_model.deleted_on = Set(Some(now));
-            active_model.updated_on = Set(now);
+        instrumented(
+            REPOSITORY,
+            "soft_delete",
+            StatementKind::Update,
+            self.update_audited(AuditAction::SoftDeleted, &user, active_model, audit),
+        )
+        .await
+    }
 
-            Ok(Some(active_model.update(self.db.as_ref()).await?))
-        } else {
-            Ok(None)
-        }
+    pub async fn restore(
+        &self,
+        user: UserModel,
+        now: DateTime,
+        audit: &AuditContext,
+    ) -> Result<Option<UserModel>, DbErr> {
+        let mut active_model: UserActiveModel = user.clone().into();
+        active_model.deleted_on = Set(None);
+        active_model.updated_on = Set(now);
+
+        instrumented(
+            REPOSITORY,
+            "restore",
+            StatementKind::Update,
+            self.update_audited(AuditAction::Restored, &user, active_model, audit),
+        )
+        .await
     }
 
-    pub async fn restore(&self, id: i32, now: DateTime) -> Result<Option<UserModel>, DbErr> {
-        let user = self.find_by_id(id).await?;
+    /// Applies a versioned update, records it and queues its events in one
+    /// transaction.
+    async fn update_audited(
+        &self,
+        action: AuditAction,
+        user: &UserModel,
+        model: UserActiveModel,
+        audit: &AuditContext,
+    ) -> Result<Option<UserModel>, DbErr> {
+        let txn = self.db.begin().await?;
+        let Some(updated) = update_versioned(&txn, model, user.version).await? else {
+            return Ok(None);
+        };
+
+        UserAuditRepository::record(&txn, action, user.id, audit, Some(user), Some(&updated))
+            .await?;
+        let events = DomainEvent::for_change(action, Some(user), Some(&updated));
+        OutboxRepository::append(&txn, events, audit).await?;
+        txn.commit().await?;
+        Ok(Some(updated))
+    }
+}
 
-        if let Some(user) = user {
-            let mut active_model: UserActiveModel = user.into();
-            active_model.deleted_on = Set(None);
-            active_model.updated_on = Set(now);
+async fn update_versioned<C: ConnectionTrait>(
+    conn: &C,
+    mut model: UserActiveModel,
+    expected_version: i32,
+) -> Result<Option<UserModel>, DbErr> {
+    model.version = Set(expected_version + 1);
 
-            Ok(Some(active_model.update(self.db.as_ref()).await?))
-        } else {
-            Ok(None)
-        }
+    match UserEntity::update(model)
+        .filter(UserColumn::Version.eq(expected_version))
+        .exec(conn)
+        .await
+    {
+        Ok(user) => Ok(Some(user)),
+        Err(DbErr::RecordNotUpdated) => Ok(None),
+        Err(err) => Err(err),
     }
 }
@@ -0,0 +1,253 @@
+use sea_orm::prelude::*;
+use sea_orm::sea_query::{Expr, OnConflict};
+use sea_orm::sqlx::types::chrono::Local;
+use sea_orm::{
+    ActiveModelTrait, DatabaseConnection, DbBackend, DbErr, EntityTrait, Order, PaginatorTrait,
+    QueryFilter, QueryOrder, QuerySelect, Set, Statement, TransactionTrait, UpdateResult,
+};
+use std::sync::Arc;
+
+use crate::db::models::{
+    WebhookAttemptActiveModel, WebhookAttemptColumn, WebhookAttemptEntity, WebhookAttemptModel,
+    WebhookDeliveryActiveModel, WebhookDeliveryColumn, WebhookDeliveryEntity, WebhookDeliveryModel,
+    WebhookSubscriptionModel,
+};
+use crate::db::{RowCount, StatementKind, instrumented};
+use crate::webhooks::DeliveryStatus;
+
+/// `repository` label of the query metrics and spans.
+const REPOSITORY: &str = "webhook_delivery";
+
+/// Leases due deliveries like the outbox relay does; see
+/// [`super::OutboxRepository::claim_due`].
+const CLAIM_SQL: &str = r#"
+UPDATE tbl_webhook_deliveries
+SET attempts = attempts + 1,
+    next_attempt_on = LOCALTIMESTAMP + make_interval(secs => $1)
+WHERE id IN (
+    SELECT id FROM tbl_webhook_deliveries
+    WHERE status = 'pending' AND next_attempt_on <= LOCALTIMESTAMP
+    ORDER BY id
+    LIMIT $2
+    FOR UPDATE SKIP LOCKED
+)
+RETURNING *
+"#;
+
+/// Where a delivery stands after an attempt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryOutcome {
+    Delivered,
+    Retry(DateTime),
+    /// Out of attempts; only a manual redelivery sends it again.
+    Dead,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct WebhookDeliveryQuery {
+    pub subscription_id: i32,
+    pub status: Option<DeliveryStatus>,
+    pub limit: u64,
+    pub offset: u64,
+}
+
+pub struct WebhookDeliveryPage {
+    pub deliveries: Vec<WebhookDeliveryModel>,
+    pub total: u64,
+}
+
+impl RowCount for WebhookDeliveryPage {
+    fn row_count(&self) -> u64 {
+        self.deliveries.len() as u64
+    }
+}
+
+impl RowCount for WebhookDeliveryModel {
+    fn row_count(&self) -> u64 {
+        1
+    }
+}
+
+pub struct WebhookDeliveryRepository {
+    db: Arc<DatabaseConnection>,
+}
+
+impl WebhookDeliveryRepository {
+    pub fn new(db: Arc<DatabaseConnection>) -> Self {
+        Self { db }
+    }
+
+    /// Queues `body` for each subscription. An event already queued for a
+    /// subscription is skipped, so relaying an event twice is harmless.
+    pub async fn enqueue(
+        &self,
+        subscriptions: &[WebhookSubscriptionModel],
+        event_id: Uuid,
+        event_type: &str,
+        body: &str,
+    ) -> Result<u64, DbErr> {
+        instrumented(REPOSITORY, "enqueue", StatementKind::Insert, async {
+            if subscriptions.is_empty() {
+                return Ok(0);
+            }
+
+            let now = Local::now().naive_local();
+            let rows = subscriptions
+                .iter()
+                .map(|subscription| WebhookDeliveryActiveModel {
+                    subscription_id: Set(subscription.id),
+                    event_id: Set(event_id),
+                    event_type: Set(event_type.to_string()),
+                    body: Set(body.to_string()),
+                    next_attempt_on: Set(now),
+                    created_on: Set(now),
+                    ..Default::default()
+                });
+            WebhookDeliveryEntity::insert_many(rows)
+                .on_conflict(
+                    OnConflict::columns([
+                        WebhookDeliveryColumn::SubscriptionId,
+                        WebhookDeliveryColumn::EventId,
+                    ])
+                    .do_nothing()
+                    .to_owned(),
+                )
+                .exec_without_returning(self.db.as_ref())
+                .await
+        })
+        .await
+    }
+
+    /// Claims up to `limit` due deliveries, oldest first, for `lease_secs`.
+    pub async fn claim_due(
+        &self,
+        limit: u64,
+        lease_secs: u64,
+    ) -> Result<Vec<WebhookDeliveryModel>, DbErr> {
+        instrumented(REPOSITORY, "claim_due", StatementKind::Update, async {
+            let mut deliveries = WebhookDeliveryEntity::find()
+                .from_raw_sql(Statement::from_sql_and_values(
+                    DbBackend::Postgres,
+                    CLAIM_SQL,
+                    [(lease_secs as f64).into(), (limit as i64).into()],
+                ))
+                .all(self.db.as_ref())
+                .await?;
+            deliveries.sort_by_key(|delivery| delivery.id);
+            Ok(deliveries)
+        })
+        .await
+    }
+
+    /// Stores an attempt and moves the delivery on to `outcome`.
+    pub async fn record_attempt(
+        &self,
+        attempt: WebhookAttemptActiveModel,
+        outcome: DeliveryOutcome,
+    ) -> Result<WebhookDeliveryModel, DbErr> {
+        instrumented(REPOSITORY, "record_attempt", StatementKind::Update, async {
+            let txn = self.db.begin().await?;
+            let attempt = attempt.insert(&txn).await?;
+
+            let mut delivery: WebhookDeliveryActiveModel =
+                WebhookDeliveryEntity::find_by_id(attempt.delivery_id)
+                    .one(&txn)
+                    .await?
+                    .ok_or_else(|| DbErr::RecordNotFound("webhook delivery".into()))?
+                    .into();
+            delivery.last_status_code = Set(attempt.status_code);
+            delivery.last_error = Set(attempt.error.clone());
+            match outcome {
+                DeliveryOutcome::Delivered => {
+                    delivery.status = Set(DeliveryStatus::Delivered.as_str().to_string());
+                    delivery.delivered_on = Set(Some(attempt.attempted_on));
+                }
+                DeliveryOutcome::Retry(retry_on) => delivery.next_attempt_on = Set(retry_on),
+                DeliveryOutcome::Dead => {
+                    delivery.status = Set(DeliveryStatus::Dead.as_str().to_string())
+                }
+            }
+            let delivery = delivery.update(&txn).await?;
+
+            txn.commit().await?;
+            Ok(delivery)
+        })
+        .await
+    }
+
+    /// Deliveries for a subscription, newest first.
+    pub async fn find_page(
+        &self,
+        query: &WebhookDeliveryQuery,
+    ) -> Result<WebhookDeliveryPage, DbErr> {
+        instrumented(REPOSITORY, "find_page", StatementKind::Select, async {
+            let mut select = WebhookDeliveryEntity::find()
+                .filter(WebhookDeliveryColumn::SubscriptionId.eq(query.subscription_id));
+            if let Some(status) = query.status {
+                select = select.filter(WebhookDeliveryColumn::Status.eq(status.as_str()));
+            }
+
+            let total = select.clone().count(self.db.as_ref()).await?;
+            let deliveries = select
+                .order_by(WebhookDeliveryColumn::Id, Order::Desc)
+                .offset(query.offset)
+                .limit(query.limit)
+                .all(self.db.as_ref())
+                .await?;
+
+            Ok(WebhookDeliveryPage { deliveries, total })
+        })
+        .await
+    }
+
+    pub async fn find_by_id(
+        &self,
+        subscription_id: i32,
+        id: i64,
+    ) -> Result<Option<WebhookDeliveryModel>, DbErr> {
+        instrumented(REPOSITORY, "find_by_id", StatementKind::Select, async {
+            WebhookDeliveryEntity::find_by_id(id)
+                .filter(WebhookDeliveryColumn::SubscriptionId.eq(subscription_id))
+                .one(self.db.as_ref())
+                .await
+        })
+        .await
+    }
+
+    /// Attempts at a delivery, oldest first.
+    pub async fn find_attempts(&self, delivery_id: i64) -> Result<Vec<WebhookAttemptModel>, DbErr> {
+        instrumented(REPOSITORY, "find_attempts", StatementKind::Select, async {
+            WebhookAttemptEntity::find()
+                .filter(WebhookAttemptColumn::DeliveryId.eq(delivery_id))
+                .order_by(WebhookAttemptColumn::Id, Order::Asc)
+                .all(self.db.as_ref())
+                .await
+        })
+        .await
+    }
+
+    /// Makes a delivery due now with a fresh set of attempts, whatever its
+    /// status. Earlier attempts stay in the history.
+    pub async fn redeliver(&self, id: i64) -> Result<UpdateResult, DbErr> {
+        instrumented(REPOSITORY, "redeliver", StatementKind::Update, async {
+            WebhookDeliveryEntity::update_many()
+                .col_expr(
+                    WebhookDeliveryColumn::Status,
+                    Expr::value(DeliveryStatus::Pending.as_str()),
+                )
+                .col_expr(WebhookDeliveryColumn::Attempts, Expr::value(0))
+                .col_expr(
+                    WebhookDeliveryColumn::NextAttemptOn,
+                    Expr::value(Local::now().naive_local()),
+                )
+                .col_expr(
+                    WebhookDeliveryColumn::DeliveredOn,
+                    Expr::value(Option::<DateTime>::None),
+                )
+                .filter(WebhookDeliveryColumn::Id.eq(id))
+                .exec(self.db.as_ref())
+                .await
+        })
+        .await
+    }
+}
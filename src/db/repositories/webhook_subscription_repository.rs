@@ -0,0 +1,100 @@
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, DeleteResult, EntityTrait, Order,
+    QueryFilter, QueryOrder,
+};
+use std::sync::Arc;
+
+use crate::db::models::{
+    WebhookSubscriptionActiveModel, WebhookSubscriptionColumn, WebhookSubscriptionEntity,
+    WebhookSubscriptionModel,
+};
+use crate::db::{RowCount, StatementKind, instrumented};
+
+/// `repository` label of the query metrics and spans.
+const REPOSITORY: &str = "webhook_subscription";
+
+impl RowCount for WebhookSubscriptionModel {
+    fn row_count(&self) -> u64 {
+        1
+    }
+}
+
+pub struct WebhookSubscriptionRepository {
+    db: Arc<DatabaseConnection>,
+}
+
+impl WebhookSubscriptionRepository {
+    pub fn new(db: Arc<DatabaseConnection>) -> Self {
+        Self { db }
+    }
+
+    pub async fn find_all(&self) -> Result<Vec<WebhookSubscriptionModel>, DbErr> {
+        instrumented(REPOSITORY, "find_all", StatementKind::Select, async {
+            WebhookSubscriptionEntity::find()
+                .order_by(WebhookSubscriptionColumn::Id, Order::Asc)
+                .all(self.db.as_ref())
+                .await
+        })
+        .await
+    }
+
+    pub async fn find_active(&self) -> Result<Vec<WebhookSubscriptionModel>, DbErr> {
+        instrumented(REPOSITORY, "find_active", StatementKind::Select, async {
+            WebhookSubscriptionEntity::find()
+                .filter(WebhookSubscriptionColumn::Active.eq(true))
+                .order_by(WebhookSubscriptionColumn::Id, Order::Asc)
+                .all(self.db.as_ref())
+                .await
+        })
+        .await
+    }
+
+    pub async fn find_by_ids(&self, ids: Vec<i32>) -> Result<Vec<WebhookSubscriptionModel>, DbErr> {
+        instrumented(REPOSITORY, "find_by_ids", StatementKind::Select, async {
+            WebhookSubscriptionEntity::find()
+                .filter(WebhookSubscriptionColumn::Id.is_in(ids))
+                .all(self.db.as_ref())
+                .await
+        })
+        .await
+    }
+
+    pub async fn find_by_id(&self, id: i32) -> Result<Option<WebhookSubscriptionModel>, DbErr> {
+        instrumented(REPOSITORY, "find_by_id", StatementKind::Select, async {
+            WebhookSubscriptionEntity::find_by_id(id)
+                .one(self.db.as_ref())
+                .await
+        })
+        .await
+    }
+
+    pub async fn create(
+        &self,
+        model: WebhookSubscriptionActiveModel,
+    ) -> Result<WebhookSubscriptionModel, DbErr> {
+        instrumented(REPOSITORY, "create", StatementKind::Insert, async {
+            model.insert(self.db.as_ref()).await
+        })
+        .await
+    }
+
+    pub async fn update(
+        &self,
+        model: WebhookSubscriptionActiveModel,
+    ) -> Result<WebhookSubscriptionModel, DbErr> {
+        instrumented(REPOSITORY, "update", StatementKind::Update, async {
+            model.update(self.db.as_ref()).await
+        })
+        .await
+    }
+
+    /// Deletes the subscription with its delivery history.
+    pub async fn delete(&self, id: i32) -> Result<DeleteResult, DbErr> {
+        instrumented(REPOSITORY, "delete", StatementKind::Delete, async {
+            WebhookSubscriptionEntity::delete_by_id(id)
+                .exec(self.db.as_ref())
+                .await
+        })
+        .await
+    }
+}
@@ -1,19 +1,51 @@
 use serde::{Deserialize, Serialize};
-use validator::Validate;
+use std::fmt;
+use validator::{Validate, ValidationError, ValidationErrors};
 
-#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+use crate::redaction::Redacted;
+
+pub const USERNAME_MIN_LENGTH: u64 = 3;
+pub const USERNAME_MAX_LENGTH: u64 = 50;
+pub const NAME_MAX_LENGTH: u64 = 100;
+pub const EMAIL_MAX_LENGTH: u64 = 254;
+
+#[derive(Clone, Serialize, Deserialize, Validate)]
 pub struct User {
     pub id: Option<i32>,
 
-    #[validate(length(min = 3, message = "Username must be at least 3 characters"))]
+    #[validate(length(
+        min = "USERNAME_MIN_LENGTH",
+        max = "USERNAME_MAX_LENGTH",
+        code = "length",
+        message = "Username must be between 3 and 50 characters"
+    ))]
     pub username: String,
 
+    #[validate(length(
+        max = "NAME_MAX_LENGTH",
+        code = "length",
+        message = "First name must be at most 100 characters"
+    ))]
     pub first_name: Option<String>,
+
+    #[validate(length(
+        max = "NAME_MAX_LENGTH",
+        code = "length",
+        message = "Last name must be at most 100 characters"
+    ))]
     pub last_name: Option<String>,
 
-    #[validate(email(message = "Invalid email format"))]
+    #[validate(
+        email(code = "email_format", message = "Invalid email format"),
+        length(
+            max = "EMAIL_MAX_LENGTH",
+            code = "length",
+            message = "Email must be at most 254 characters"
+        )
+    )]
     pub email: String,
 
+    #[validate(custom(function = "validate_phone"))]
     pub phone: Option<String>,
 }
 
@@ -38,11 +70,184 @@ impl User {
         }
     }
 
-    pub fn validate_business_rules(&self) -> Result<(), String> {
-        if self.username.contains(' ') {
-            return Err("Username cannot contain spaces".to_string());
+    pub fn validate_business_rules(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        self.check_business_rules(&mut errors);
+        into_result(errors)
+    }
+
+    /// Runs the field validators and the business rules, collecting every
+    /// failure instead of stopping at the first one.
+    pub fn validate_all(&self) -> Result<(), ValidationErrors> {
+        let mut errors = self.validate().err().unwrap_or_default();
+        self.check_business_rules(&mut errors);
+        into_result(errors)
+    }
+
+    fn check_business_rules(&self, errors: &mut ValidationErrors) {
+        if self.username.chars().any(char::is_whitespace) {
+            errors.add(
+                "username",
+                ValidationError::new("username_whitespace")
+                    .with_message("Username cannot contain spaces".into()),
+            );
+        }
+        // Login treats an identifier containing `@` as an email address.
+        if self.username.contains('@') {
+            errors.add(
+                "username",
+                ValidationError::new("username_at_sign")
+                    .with_message("Username cannot contain @".into()),
+            );
         }
+    }
+}
 
+impl fmt::Debug for User {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("User")
+            .field("id", &self.id)
+            .field("username", &Redacted::identifier(&self.username))
+            .field("first_name", &self.first_name.as_ref().map(Redacted::name))
+            .field("last_name", &self.last_name.as_ref().map(Redacted::name))
+            .field("email", &Redacted::email(&self.email))
+            .field("phone", &self.phone.as_ref().map(Redacted::phone))
+            .finish()
+    }
+}
+
+fn into_result(errors: ValidationErrors) -> Result<(), ValidationErrors> {
+    if errors.is_empty() {
         Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Accepts international formats such as `+1 (555) 123-4567`: an optional
+/// leading `+`, then digits with spaces, dots, dashes or parentheses, and
+/// 7 to 15 digits in total (E.164).
+fn validate_phone(phone: &str) -> Result<(), ValidationError> {
+    let body = phone.strip_prefix('+').unwrap_or(phone);
+    let digits = body.chars().filter(char::is_ascii_digit).count();
+    let allowed = body
+        .chars()
+        .all(|c| c.is_ascii_digit() || matches!(c, ' ' | '-' | '.' | '(' | ')'));
+
+    if allowed && (7..=15).contains(&digits) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("phone_format")
+            .with_message("Phone must contain 7 to 15 digits and only + ( ) - . or spaces".into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn valid() -> User {
+        User::new("jdoe".into(), "jdoe@example.com".into())
+    }
+
+    fn codes(errors: &ValidationErrors) -> Vec<(String, String)> {
+        let mut codes: Vec<(String, String)> = errors
+            .field_errors()
+            .into_iter()
+            .flat_map(|(field, errors)| {
+                errors
+                    .iter()
+                    .map(move |error| (field.to_string(), error.code.to_string()))
+            })
+            .collect();
+        codes.sort();
+        codes
+    }
+
+    #[test]
+    fn validate_all_reports_every_failure_together() {
+        let user = User {
+            username: "j d@".into(),
+            first_name: Some("a".repeat(101)),
+            email: "not-an-email".into(),
+            phone: Some("12".into()),
+            ..valid()
+        };
+
+        let errors = user.validate_all().unwrap_err();
+
+        assert_eq!(
+            codes(&errors),
+            [
+                ("email", "email_format"),
+                ("first_name", "length"),
+                ("phone", "phone_format"),
+                ("username", "username_at_sign"),
+                ("username", "username_whitespace"),
+            ]
+            .map(|(field, code)| (field.to_string(), code.to_string()))
+        );
+    }
+
+    /// A valid address of `len` characters, padded in the domain, as the
+    /// local part is limited to 64.
+    fn email(len: usize) -> String {
+        let labels = vec!["a".repeat(60); 3].join(".");
+        format!("jdoe@{}.{}.com", "a".repeat(len - 192), labels)
+    }
+
+    #[test]
+    fn length_limits() {
+        for (username, accepted) in [
+            ("ab", false),
+            ("abc", true),
+            (&"a".repeat(50), true),
+            (&"a".repeat(51), false),
+        ] {
+            let user = User {
+                username: username.to_string(),
+                ..valid()
+            };
+            assert_eq!(user.validate_all().is_ok(), accepted, "{}", username.len());
+        }
+
+        let user = User {
+            last_name: Some("a".repeat(100)),
+            email: email(254),
+            ..valid()
+        };
+        assert!(user.validate_all().is_ok());
+        let user = User {
+            last_name: Some("a".repeat(101)),
+            email: email(255),
+            ..valid()
+        };
+        assert_eq!(
+            codes(&user.validate_all().unwrap_err()),
+            [("email", "length"), ("last_name", "length")]
+                .map(|(field, code)| (field.to_string(), code.to_string()))
+        );
+    }
+
+    #[test]
+    fn phone_rules() {
+        for phone in [
+            "+1 (555) 123-4567",
+            "555.123.4567",
+            "1234567",
+            "+123456789012345",
+        ] {
+            assert!(validate_phone(phone).is_ok(), "{}", phone);
+        }
+        for phone in [
+            "123456",
+            "+1234567890123456",
+            "555-CALL-NOW",
+            "++1234567",
+            "1234567 ext 8",
+            "",
+        ] {
+            assert!(validate_phone(phone).is_err(), "{}", phone);
+        }
     }
 }
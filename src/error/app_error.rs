@@ -1,11 +1,38 @@
+use actix_web::http::{StatusCode, header};
 use actix_web::{HttpResponse, ResponseError};
 use sea_orm::DbErr;
 use serde::Serialize;
 use std::fmt;
+use utoipa::ToSchema;
+use validator::ValidationErrors;
 
-#[derive(Serialize)]
+use super::{ErrorCode, constraint};
+
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// RFC 7807 problem details document returned for every error.
+#[derive(Debug, Clone, Serialize, ToSchema)]
 pub struct ErrorResponse {
-    pub status: String,
+    #[serde(rename = "type")]
+    #[schema(example = "/problems/user-not-found")]
+    pub type_uri: String,
+    pub title: String,
+    pub status: u16,
+    pub detail: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    pub code: ErrorCode,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub errors: Option<Vec<FieldError>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+/// A single failing field of a request body.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct FieldError {
+    pub field: String,
+    pub code: String,
     pub message: String,
 }
 
@@ -13,8 +40,17 @@ pub struct ErrorResponse {
 pub enum AppError {
     Database(DbErr),
     Validation(String),
+    InvalidFields(Vec<FieldError>),
     NotFound(String),
+    UserNotFound(i32),
+    Conflict(FieldError),
+    PreconditionFailed(String),
+    PreconditionRequired(String),
+    UnsupportedMediaType(String),
+    /// Carries the seconds until the client may retry.
+    TooManyRequests(u64),
     Unauthorized(String),
+    Forbidden(String),
     InternalServerError,
 }
 
@@ -23,47 +59,173 @@ impl fmt::Display for AppError {
         match self {
             Self::Database(err) => write!(f, "Database error: {}", err),
             Self::Validation(msg) => write!(f, "Validation error: {}", msg),
+            Self::InvalidFields(errors) => write!(f, "{} invalid field(s)", errors.len()),
             Self::NotFound(msg) => write!(f, "Not found: {}", msg),
+            Self::UserNotFound(id) => write!(f, "User with ID {} not found", id),
+            Self::Conflict(error) => write!(f, "Conflict on {}: {}", error.field, error.message),
+            Self::PreconditionFailed(msg) => write!(f, "Precondition failed: {}", msg),
+            Self::PreconditionRequired(msg) => write!(f, "Precondition required: {}", msg),
+            Self::UnsupportedMediaType(msg) => write!(f, "Unsupported media type: {}", msg),
+            Self::TooManyRequests(secs) => write!(f, "Too many requests, retry in {} s", secs),
             Self::Unauthorized(msg) => write!(f, "Unauthorized: {}", msg),
+            Self::Forbidden(msg) => write!(f, "Forbidden: {}", msg),
             Self::InternalServerError => write!(f, "Internal server error"),
         }
     }
 }
 
-impl ResponseError for AppError {
-    fn error_response(&self) -> HttpResponse {
+impl AppError {
+    /// A uniqueness conflict detected before hitting the database.
+    pub fn already_exists(field: &str, value: &str) -> Self {
+        AppError::Conflict(FieldError {
+            field: field.into(),
+            code: "unique".into(),
+            message: format!("{} {} already exists", capitalize(field), value),
+        })
+    }
+
+    pub fn code(&self) -> ErrorCode {
         match self {
-            AppError::Database(err) => {
-                log::error!("Database error: {}", err);
-                HttpResponse::InternalServerError().json(ErrorResponse {
-                    status: "error".into(),
-                    message: "An internal error occurred".into(),
-                })
+            Self::Database(_) | Self::InternalServerError => ErrorCode::InternalError,
+            Self::Validation(_) => ErrorCode::InvalidRequest,
+            Self::InvalidFields(_) => ErrorCode::ValidationFailed,
+            Self::NotFound(_) => ErrorCode::NotFound,
+            Self::UserNotFound(_) => ErrorCode::UserNotFound,
+            Self::Conflict(error) => match (error.code.as_str(), error.field.as_str()) {
+                ("unique", "username") => ErrorCode::UsernameTaken,
+                ("unique", "email") => ErrorCode::EmailTaken,
+                _ => ErrorCode::Conflict,
+            },
+            Self::PreconditionFailed(_) => ErrorCode::PreconditionFailed,
+            Self::PreconditionRequired(_) => ErrorCode::PreconditionRequired,
+            Self::UnsupportedMediaType(_) => ErrorCode::UnsupportedMediaType,
+            Self::TooManyRequests(_) => ErrorCode::RateLimited,
+            Self::Unauthorized(_) => ErrorCode::Unauthorized,
+            Self::Forbidden(_) => ErrorCode::Forbidden,
+        }
+    }
+
+    /// Builds the problem document without request context; internal
+    /// details of database errors are never exposed.
+    pub fn to_problem(&self) -> ErrorResponse {
+        let code = self.code();
+        let detail = match self {
+            Self::Database(_) | Self::InternalServerError => "An internal error occurred".into(),
+            Self::Validation(msg)
+            | Self::NotFound(msg)
+            | Self::Unauthorized(msg)
+            | Self::Forbidden(msg)
+            | Self::PreconditionFailed(msg)
+            | Self::PreconditionRequired(msg)
+            | Self::UnsupportedMediaType(msg) => msg.clone(),
+            Self::InvalidFields(_) => "One or more fields are invalid".into(),
+            Self::UserNotFound(id) => format!("User with ID {} not found", id),
+            Self::TooManyRequests(secs) => {
+                format!("Rate limit exceeded; retry in {} second(s)", secs)
             }
-            AppError::Validation(msg) => HttpResponse::BadRequest().json(ErrorResponse {
-                status: "error".into(),
-                message: msg.clone(),
-            }),
-            AppError::NotFound(msg) => HttpResponse::NotFound().json(ErrorResponse {
-                status: "error".into(),
-                message: msg.clone(),
-            }),
-            AppError::Unauthorized(msg) => HttpResponse::Unauthorized().json(ErrorResponse {
-                status: "error".into(),
-                message: msg.clone(),
-            }),
-            AppError::InternalServerError => {
-                HttpResponse::InternalServerError().json(ErrorResponse {
-                    status: "error".into(),
-                    message: "An internal error occurred".into(),
-                })
+            Self::Conflict(error) => error.message.clone(),
+        };
+
+        ErrorResponse {
+            type_uri: code.type_uri(),
+            title: code.title().into(),
+            status: self.status_code().as_u16(),
+            detail,
+            instance: None,
+            code,
+            errors: match self {
+                Self::InvalidFields(errors) => Some(errors.clone()),
+                Self::Conflict(error) => Some(vec![error.clone()]),
+                _ => None,
+            },
+            request_id: None,
+        }
+    }
+}
+
+impl ErrorResponse {
+    pub fn into_response(self) -> HttpResponse {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let mut builder = HttpResponse::build(status);
+        builder.content_type(PROBLEM_JSON);
+
+        if status == StatusCode::UNAUTHORIZED {
+            builder.insert_header((header::WWW_AUTHENTICATE, "Bearer"));
+        }
+
+        builder.body(serde_json::to_string(&self).unwrap_or_default())
+    }
+}
+
+impl ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            AppError::Database(_) | AppError::InternalServerError => {
+                StatusCode::INTERNAL_SERVER_ERROR
             }
+            AppError::Validation(_) => StatusCode::BAD_REQUEST,
+            AppError::Conflict(_) => StatusCode::CONFLICT,
+            AppError::PreconditionFailed(_) => StatusCode::PRECONDITION_FAILED,
+            AppError::PreconditionRequired(_) => StatusCode::PRECONDITION_REQUIRED,
+            AppError::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            AppError::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
+            AppError::InvalidFields(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            AppError::NotFound(_) | AppError::UserNotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        if let AppError::Database(err) = self {
+            log::error!("Database error: {}", err);
+        }
+
+        let mut res = self.to_problem().into_response();
+        if let AppError::TooManyRequests(secs) = self {
+            res.headers_mut()
+                .insert(header::RETRY_AFTER, header::HeaderValue::from(*secs));
         }
+        res
+    }
+}
+
+pub(super) fn capitalize(value: &str) -> String {
+    let mut chars = value.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
     }
 }
 
 impl From<DbErr> for AppError {
     fn from(err: DbErr) -> Self {
-        AppError::Database(err)
+        match constraint::violation(&err) {
+            Some(error) => AppError::Conflict(error),
+            None => AppError::Database(err),
+        }
+    }
+}
+
+impl From<ValidationErrors> for AppError {
+    fn from(errors: ValidationErrors) -> Self {
+        let mut fields: Vec<FieldError> = errors
+            .field_errors()
+            .into_iter()
+            .flat_map(|(field, errors)| {
+                errors.iter().map(move |error| FieldError {
+                    field: field.to_string(),
+                    code: error.code.to_string(),
+                    message: error
+                        .message
+                        .as_ref()
+                        .map(|message| message.to_string())
+                        .unwrap_or_else(|| format!("Invalid {}", field)),
+                })
+            })
+            .collect();
+
+        fields.sort_by(|a, b| a.field.cmp(&b.field).then(a.code.cmp(&b.code)));
+        AppError::InvalidFields(fields)
     }
 }
@@ -0,0 +1,197 @@
+use sea_orm::sqlx::Error as SqlxError;
+use sea_orm::{DbErr, RuntimeErr};
+
+use super::FieldError;
+use super::app_error::capitalize;
+
+/// Constraint names from the migrations mapped to the field they guard.
+///
+/// Constraints not listed here, such as primary keys, are reported with
+/// the constraint name as the field; add new migrations' constraints so
+/// clients see a field name instead.
+const CONSTRAINT_FIELDS: &[(&str, &str)] = &[
+    ("idx_username", "username"),
+    ("idx_email", "email"),
+    ("idx_role_name", "name"),
+    ("idx_permission_name", "name"),
+    ("idx_refresh_token_hash", "token_hash"),
+    ("fk_user_credentials_user_id", "user_id"),
+    ("fk_refresh_tokens_user_id", "user_id"),
+    ("fk_user_roles_user_id", "user_id"),
+    ("fk_user_roles_role_id", "role_id"),
+    ("fk_role_permissions_role_id", "role_id"),
+    ("fk_role_permissions_permission_id", "permission_id"),
+    ("idx_user_audit_prev_hash", "prev_hash"),
+    ("tbl_outbox_events_event_id_key", "event_id"),
+    ("idx_webhook_deliveries_subscription_event", "event_id"),
+    ("fk_webhook_deliveries_subscription_id", "subscription_id"),
+    ("fk_webhook_attempts_delivery_id", "delivery_id"),
+];
+
+/// Recognizes Postgres unique (23505), foreign-key (23503) and check (23514)
+/// violations and describes the offending field.
+pub fn violation(err: &DbErr) -> Option<FieldError> {
+    let db_err = match err {
+        DbErr::Query(RuntimeErr::SqlxError(SqlxError::Database(db_err)))
+        | DbErr::Exec(RuntimeErr::SqlxError(SqlxError::Database(db_err))) => db_err,
+        _ => return None,
+    };
+
+    let kind = match db_err.code().as_deref() {
+        Some("23505") => "unique",
+        Some("23503") => "foreign_key",
+        Some("23514") => "check",
+        _ => return None,
+    };
+
+    let constraint = db_err.constraint().unwrap_or("unknown");
+    let field = CONSTRAINT_FIELDS
+        .iter()
+        .find(|(name, _)| *name == constraint)
+        .map(|(_, field)| field.to_string())
+        .unwrap_or_else(|| constraint.to_string());
+
+    let message = match kind {
+        "unique" => format!("{} already exists", capitalize(&field)),
+        "foreign_key" => format!(
+            "{} references a row that does not exist or is still referenced",
+            capitalize(&field)
+        ),
+        _ => format!("{} violates constraint {}", capitalize(&field), constraint),
+    };
+
+    Some(FieldError {
+        field,
+        code: kind.into(),
+        message,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::{AppError, ErrorCode};
+    use actix_web::ResponseError;
+    use actix_web::http::StatusCode;
+    use sea_orm::sqlx::error::{DatabaseError, ErrorKind};
+    use std::borrow::Cow;
+    use std::error::Error;
+    use std::fmt;
+
+    /// What the Postgres driver reports for a failed statement.
+    #[derive(Debug)]
+    struct PgError {
+        code: &'static str,
+        constraint: Option<&'static str>,
+        message: &'static str,
+    }
+
+    impl fmt::Display for PgError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(self.message)
+        }
+    }
+
+    impl Error for PgError {}
+
+    impl DatabaseError for PgError {
+        fn message(&self) -> &str {
+            self.message
+        }
+
+        fn code(&self) -> Option<Cow<'_, str>> {
+            Some(self.code.into())
+        }
+
+        fn constraint(&self) -> Option<&str> {
+            self.constraint
+        }
+
+        fn as_error(&self) -> &(dyn Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn as_error_mut(&mut self) -> &mut (dyn Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn into_error(self: Box<Self>) -> Box<dyn Error + Send + Sync + 'static> {
+            self
+        }
+
+        fn kind(&self) -> ErrorKind {
+            ErrorKind::Other
+        }
+    }
+
+    fn db_err(code: &'static str, constraint: Option<&'static str>) -> DbErr {
+        DbErr::Exec(RuntimeErr::SqlxError(SqlxError::Database(Box::new(
+            PgError {
+                code,
+                constraint,
+                message: "Key (email)=(jdoe@example.com) already exists in tbl_users",
+            },
+        ))))
+    }
+
+    #[test]
+    fn known_constraints_map_to_their_field_with_409() {
+        let cases = [
+            (
+                "23505",
+                "idx_username",
+                "username",
+                "unique",
+                ErrorCode::UsernameTaken,
+            ),
+            (
+                "23505",
+                "idx_email",
+                "email",
+                "unique",
+                ErrorCode::EmailTaken,
+            ),
+            (
+                "23503",
+                "fk_user_roles_role_id",
+                "role_id",
+                "foreign_key",
+                ErrorCode::Conflict,
+            ),
+            (
+                "23514",
+                "chk_anything",
+                "chk_anything",
+                "check",
+                ErrorCode::Conflict,
+            ),
+        ];
+
+        for (code, constraint, field, kind, error_code) in cases {
+            let err = AppError::from(db_err(code, Some(constraint)));
+
+            assert_eq!(err.status_code(), StatusCode::CONFLICT, "{}", constraint);
+            assert_eq!(err.code(), error_code, "{}", constraint);
+            let AppError::Conflict(error) = err else {
+                unreachable!()
+            };
+            assert_eq!((error.field.as_str(), error.code.as_str()), (field, kind));
+        }
+    }
+
+    #[test]
+    fn other_database_errors_hide_their_details() {
+        for err in [
+            db_err("57014", None),
+            DbErr::Custom("relation \"tbl_users\" does not exist".into()),
+        ] {
+            assert!(violation(&err).is_none());
+
+            let problem = AppError::from(err).to_problem();
+
+            assert_eq!(problem.status, 500);
+            assert_eq!(problem.detail, "An internal error occurred");
+            assert!(problem.errors.is_none());
+        }
+    }
+}
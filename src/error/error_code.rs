@@ -0,0 +1,72 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// Stable, machine-readable error identifiers exposed to API clients.
+///
+/// Variants may be added but never renamed; clients switch on these values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    InvalidRequest,
+    ValidationFailed,
+    Unauthorized,
+    Forbidden,
+    NotFound,
+    UserNotFound,
+    UsernameTaken,
+    EmailTaken,
+    Conflict,
+    PreconditionFailed,
+    PreconditionRequired,
+    UnsupportedMediaType,
+    RateLimited,
+    InternalError,
+}
+
+impl ErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::InvalidRequest => "INVALID_REQUEST",
+            Self::ValidationFailed => "VALIDATION_FAILED",
+            Self::Unauthorized => "UNAUTHORIZED",
+            Self::Forbidden => "FORBIDDEN",
+            Self::NotFound => "NOT_FOUND",
+            Self::UserNotFound => "USER_NOT_FOUND",
+            Self::UsernameTaken => "USERNAME_TAKEN",
+            Self::EmailTaken => "EMAIL_TAKEN",
+            Self::Conflict => "CONFLICT",
+            Self::PreconditionFailed => "PRECONDITION_FAILED",
+            Self::PreconditionRequired => "PRECONDITION_REQUIRED",
+            Self::UnsupportedMediaType => "UNSUPPORTED_MEDIA_TYPE",
+            Self::RateLimited => "RATE_LIMITED",
+            Self::InternalError => "INTERNAL_ERROR",
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Self::InvalidRequest => "Invalid request",
+            Self::ValidationFailed => "Validation failed",
+            Self::Unauthorized => "Authentication required",
+            Self::Forbidden => "Permission denied",
+            Self::NotFound => "Resource not found",
+            Self::UserNotFound => "User not found",
+            Self::UsernameTaken => "Username already taken",
+            Self::EmailTaken => "Email already taken",
+            Self::Conflict => "Conflict with existing data",
+            Self::PreconditionFailed => "Precondition failed",
+            Self::PreconditionRequired => "Precondition required",
+            Self::UnsupportedMediaType => "Unsupported media type",
+            Self::RateLimited => "Too many requests",
+            Self::InternalError => "Internal server error",
+        }
+    }
+
+    /// Problem type URI, e.g. `/problems/user-not-found`.
+    pub fn type_uri(&self) -> String {
+        format!(
+            "/problems/{}",
+            self.as_str().to_ascii_lowercase().replace('_', "-")
+        )
+    }
+}
@@ -1,3 +1,6 @@
 mod app_error;
+mod constraint;
+mod error_code;
 
-pub use app_error::{AppError, ErrorResponse};
+pub use app_error::{AppError, ErrorResponse, FieldError, PROBLEM_JSON};
+pub use error_code::ErrorCode;
@@ -0,0 +1,232 @@
+mod relay;
+mod sink;
+
+pub use relay::{Relay, retry_delay};
+pub use sink::{EventSink, LogSink};
+
+use sea_orm::sqlx::types::chrono::NaiveDateTime;
+use serde::Serialize;
+
+use crate::audit::{self, AuditAction};
+use crate::db::models::UserModel;
+
+/// `aggregate_type` of user events.
+pub const USER_AGGREGATE: &str = "user";
+
+/// Something that happened to a user, published through the outbox.
+///
+/// Serialized with its variant name as `type`, e.g.
+/// `{"type": "UserEmailChanged", "user_id": 7, ...}`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type")]
+pub enum DomainEvent {
+    UserCreated {
+        user: UserModel,
+    },
+    /// Any change through `PUT` or `PATCH`; `changes` names the fields.
+    UserUpdated {
+        user_id: i32,
+        changes: Vec<String>,
+        user: UserModel,
+    },
+    /// Raised alongside `UserUpdated` when the email changes.
+    UserEmailChanged {
+        user_id: i32,
+        old_email: String,
+        new_email: String,
+    },
+    UserSoftDeleted {
+        user_id: i32,
+        deleted_on: NaiveDateTime,
+    },
+    UserRestored {
+        user_id: i32,
+    },
+    /// The row was deleted permanently.
+    UserPurged {
+        user_id: i32,
+    },
+}
+
+impl DomainEvent {
+    /// Events for a change to a user, from its before and after images.
+    /// An update that changes nothing raises none.
+    pub fn for_change(
+        action: AuditAction,
+        before: Option<&UserModel>,
+        after: Option<&UserModel>,
+    ) -> Vec<DomainEvent> {
+        match (action, before, after) {
+            (AuditAction::Created, _, Some(user)) => {
+                vec![DomainEvent::UserCreated { user: user.clone() }]
+            }
+            (AuditAction::Updated, Some(before), Some(after)) => {
+                let changes: Vec<String> = match audit::diff(Some(before), Some(after)) {
+                    (_, Some(serde_json::Value::Object(fields))) => {
+                        fields.keys().cloned().collect()
+                    }
+                    _ => Vec::new(),
+                };
+                if changes.is_empty() {
+                    return Vec::new();
+                }
+
+                let mut events = vec![DomainEvent::UserUpdated {
+                    user_id: after.id,
+                    changes,
+                    user: after.clone(),
+                }];
+                if before.email != after.email {
+                    events.push(DomainEvent::UserEmailChanged {
+                        user_id: after.id,
+                        old_email: before.email.clone(),
+                        new_email: after.email.clone(),
+                    });
+                }
+                events
+            }
+            (AuditAction::SoftDeleted, _, Some(user)) => user
+                .deleted_on
+                .map(|deleted_on| DomainEvent::UserSoftDeleted {
+                    user_id: user.id,
+                    deleted_on,
+                })
+                .into_iter()
+                .collect(),
+            (AuditAction::Restored, _, Some(user)) => {
+                vec![DomainEvent::UserRestored { user_id: user.id }]
+            }
+            (AuditAction::Deleted, Some(user), _) => {
+                vec![DomainEvent::UserPurged { user_id: user.id }]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            DomainEvent::UserCreated { .. } => "UserCreated",
+            DomainEvent::UserUpdated { .. } => "UserUpdated",
+            DomainEvent::UserEmailChanged { .. } => "UserEmailChanged",
+            DomainEvent::UserSoftDeleted { .. } => "UserSoftDeleted",
+            DomainEvent::UserRestored { .. } => "UserRestored",
+            DomainEvent::UserPurged { .. } => "UserPurged",
+        }
+    }
+
+    pub fn user_id(&self) -> i32 {
+        match self {
+            DomainEvent::UserCreated { user } => user.id,
+            DomainEvent::UserUpdated { user_id, .. }
+            | DomainEvent::UserEmailChanged { user_id, .. }
+            | DomainEvent::UserSoftDeleted { user_id, .. }
+            | DomainEvent::UserRestored { user_id }
+            | DomainEvent::UserPurged { user_id } => *user_id,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::sqlx::types::chrono::NaiveDate;
+
+    fn user() -> UserModel {
+        let now = NaiveDate::from_ymd_opt(2026, 10, 18)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+        UserModel {
+            id: 7,
+            username: "jdoe".into(),
+            first_name: None,
+            last_name: None,
+            email: "jdoe@example.com".into(),
+            phone: None,
+            created_on: now,
+            updated_on: now,
+            deleted_on: None,
+            version: 1,
+        }
+    }
+
+    fn types(events: &[DomainEvent]) -> Vec<&'static str> {
+        events.iter().map(DomainEvent::event_type).collect()
+    }
+
+    #[test]
+    fn update_changing_nothing_raises_no_events() {
+        let before = user();
+        let after = UserModel {
+            version: 2,
+            updated_on: before.updated_on + std::time::Duration::from_secs(1),
+            ..user()
+        };
+
+        assert!(
+            DomainEvent::for_change(AuditAction::Updated, Some(&before), Some(&after)).is_empty()
+        );
+    }
+
+    #[test]
+    fn email_change_raises_update_and_email_events() {
+        let before = user();
+        let after = UserModel {
+            email: "john@example.com".into(),
+            phone: Some("+1 555 123 4567".into()),
+            ..user()
+        };
+
+        let events = DomainEvent::for_change(AuditAction::Updated, Some(&before), Some(&after));
+
+        assert_eq!(types(&events), ["UserUpdated", "UserEmailChanged"]);
+        let DomainEvent::UserUpdated { changes, .. } = &events[0] else {
+            unreachable!()
+        };
+        assert_eq!(changes, &["email", "phone"]);
+        let DomainEvent::UserEmailChanged {
+            old_email,
+            new_email,
+            ..
+        } = &events[1]
+        else {
+            unreachable!()
+        };
+        assert_eq!(
+            (old_email.as_str(), new_email.as_str()),
+            ("jdoe@example.com", "john@example.com")
+        );
+    }
+
+    #[test]
+    fn lifecycle_actions_map_to_their_events() {
+        let live = user();
+        let deleted = UserModel {
+            deleted_on: Some(live.created_on),
+            ..user()
+        };
+
+        let cases = [
+            (AuditAction::Created, None, Some(&live), "UserCreated"),
+            (
+                AuditAction::SoftDeleted,
+                Some(&live),
+                Some(&deleted),
+                "UserSoftDeleted",
+            ),
+            (
+                AuditAction::Restored,
+                Some(&deleted),
+                Some(&live),
+                "UserRestored",
+            ),
+            (AuditAction::Deleted, Some(&live), None, "UserPurged"),
+        ];
+        for (action, before, after, event_type) in cases {
+            let events = DomainEvent::for_change(action, before, after);
+
+            assert_eq!(types(&events), [event_type]);
+            assert_eq!(events[0].user_id(), 7);
+        }
+    }
+}
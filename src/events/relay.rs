@@ -0,0 +1,263 @@
+use sea_orm::prelude::DateTime;
+use sea_orm::sqlx::types::chrono::Local;
+use sea_orm::{DbConn, DbErr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::{EventSink, LogSink};
+use crate::config::{OutboxConfig, OutboxSinkKind};
+use crate::db::models::OutboxEventModel;
+use crate::db::repositories::OutboxRepository;
+use crate::metrics::metrics;
+use crate::shutdown::Shutdown;
+use crate::webhooks::WebhookSink;
+
+/// Moves committed events from the outbox to the configured sinks.
+pub struct Relay {
+    outbox: OutboxRepository,
+    sinks: Vec<Arc<dyn EventSink>>,
+    config: OutboxConfig,
+}
+
+impl Relay {
+    pub fn new(config: &OutboxConfig, db: &DbConn) -> Self {
+        let sinks = config
+            .sinks
+            .iter()
+            .map(|kind| match kind {
+                OutboxSinkKind::Log => Arc::new(LogSink) as Arc<dyn EventSink>,
+                OutboxSinkKind::Webhooks => Arc::new(WebhookSink::new(db)),
+            })
+            .collect();
+
+        Self {
+            outbox: OutboxRepository::new(Arc::new(db.clone())),
+            sinks,
+            config: config.clone(),
+        }
+    }
+
+    /// Delivers one batch of due events, returning how many were claimed.
+    pub async fn run_once(&self) -> Result<usize, DbErr> {
+        let events = self
+            .outbox
+            .claim_due(self.config.batch_size, self.config.lease_secs)
+            .await?;
+
+        for event in &events {
+            if !self
+                .outbox
+                .renew_lease(event, self.config.lease_secs)
+                .await?
+            {
+                log::warn!(
+                    "Lease on event {} ran out before its delivery; leaving it to the next claim",
+                    event.event_id
+                );
+                continue;
+            }
+
+            match self.deliver(event).await {
+                Ok(()) => {
+                    self.outbox.mark_delivered(event.id).await?;
+                    metrics()
+                        .outbox_deliveries
+                        .with_label_values(&["delivered"])
+                        .inc();
+                }
+                Err(err) => {
+                    let retry_on = self.retry_on(event.attempts);
+                    self.outbox.mark_failed(event.id, &err, retry_on).await?;
+                    if retry_on.is_some() {
+                        log::warn!(
+                            "Delivering event {} failed (attempt {}), will retry: {}",
+                            event.event_id,
+                            event.attempts,
+                            err
+                        );
+                        metrics()
+                            .outbox_deliveries
+                            .with_label_values(&["retried"])
+                            .inc();
+                    } else {
+                        log::error!(
+                            "Giving up on event {} after {} attempt(s): {}",
+                            event.event_id,
+                            event.attempts,
+                            err
+                        );
+                        metrics()
+                            .outbox_deliveries
+                            .with_label_values(&["failed"])
+                            .inc();
+                    }
+                }
+            }
+        }
+
+        Ok(events.len())
+    }
+
+    /// Hands the event to every sink, stopping at the first failure.
+    async fn deliver(&self, event: &OutboxEventModel) -> Result<(), String> {
+        for sink in &self.sinks {
+            sink.deliver(event)
+                .await
+                .map_err(|err| format!("{}: {}", sink.name(), err))?;
+        }
+        Ok(())
+    }
+
+    /// When to retry after `attempts` failed attempts, or `None` once
+    /// `max_attempts` is reached.
+    fn retry_on(&self, attempts: i32) -> Option<DateTime> {
+        let attempts = u32::try_from(attempts).unwrap_or(0);
+        if attempts >= self.config.max_attempts {
+            return None;
+        }
+
+        let delay = retry_delay(
+            self.config.initial_backoff_ms,
+            self.config.max_backoff_ms,
+            attempts,
+        );
+        Some(Local::now().naive_local() + delay)
+    }
+
+    /// Polls every `poll_interval_ms`, draining full batches back to back,
+    /// until shutdown. A batch in progress is finished first.
+    pub fn spawn(self: Arc<Self>, shutdown: &Shutdown) {
+        let interval = Duration::from_millis(self.config.poll_interval_ms);
+        let signal = shutdown.clone();
+
+        shutdown.spawn(async move {
+            let mut ticks = tokio::time::interval(interval);
+            loop {
+                tokio::select! {
+                    _ = signal.signalled() => break,
+                    _ = ticks.tick() => {}
+                }
+                loop {
+                    match self.run_once().await {
+                        Ok(claimed) if claimed as u64 == self.config.batch_size => {
+                            if signal.is_shutting_down() {
+                                break;
+                            }
+                        }
+                        Ok(_) => break,
+                        Err(err) => {
+                            log::warn!("Failed to relay outbox events: {}", err);
+                            break;
+                        }
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// Backoff after `attempts` failed attempts: `initial_backoff_ms`, doubling
+/// with each further failure up to `max_backoff_ms`.
+pub fn retry_delay(initial_backoff_ms: u64, max_backoff_ms: u64, attempts: u32) -> Duration {
+    let backoff_ms = initial_backoff_ms
+        .saturating_mul(2u64.saturating_pow(attempts.saturating_sub(1)))
+        .min(max_backoff_ms);
+    Duration::from_millis(backoff_ms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn relay(max_attempts: u32) -> Relay {
+        let config = OutboxConfig {
+            enabled: true,
+            sinks: vec![OutboxSinkKind::Log],
+            poll_interval_ms: 1000,
+            batch_size: 100,
+            lease_secs: 60,
+            max_attempts,
+            initial_backoff_ms: 1000,
+            max_backoff_ms: 10_000,
+        };
+        Relay::new(&config, &DbConn::Disconnected)
+    }
+
+    #[test]
+    fn retry_delay_doubles_up_to_the_cap() {
+        let delays: Vec<u128> = (1..=6)
+            .map(|attempts| retry_delay(1000, 10_000, attempts).as_millis())
+            .collect();
+
+        assert_eq!(delays, [1000, 2000, 4000, 8000, 10_000, 10_000]);
+        assert_eq!(retry_delay(1000, 10_000, u32::MAX), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn retry_on_gives_up_at_max_attempts() {
+        let relay = relay(3);
+        let before = Local::now().naive_local();
+
+        let retry_on = relay.retry_on(2).unwrap();
+
+        assert!(retry_on >= before + Duration::from_secs(2));
+        assert!(retry_on <= Local::now().naive_local() + Duration::from_secs(2));
+        assert_eq!(relay.retry_on(3), None);
+        assert_eq!(relay.retry_on(4), None);
+    }
+
+    /// Runs against the database in `TEST_DATABASE_URL`, which it migrates:
+    /// `TEST_DATABASE_URL=postgres://... cargo test -- --ignored`.
+    #[actix_web::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn lease_is_renewed_only_while_it_is_held() {
+        use sea_orm::ActiveValue::Set;
+        use sea_orm::prelude::Uuid;
+        use sea_orm::{ActiveModelTrait, EntityTrait};
+        use sea_orm_migration::MigratorTrait;
+
+        use crate::db::migrations::Migrator;
+        use crate::db::models::{OutboxEventActiveModel, OutboxEventEntity};
+
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL is not set");
+        let db = Arc::new(sea_orm::Database::connect(url).await.unwrap());
+        Migrator::up(db.as_ref(), None).await.unwrap();
+        let outbox = OutboxRepository::new(db.clone());
+        let now = Local::now().naive_local();
+        let id = OutboxEventActiveModel {
+            event_id: Set(Uuid::new_v4()),
+            event_type: Set("UserRestored".into()),
+            aggregate_type: Set("user".into()),
+            aggregate_id: Set("7".into()),
+            payload: Set(serde_json::json!({})),
+            occurred_on: Set(now),
+            next_attempt_on: Set(now),
+            ..Default::default()
+        }
+        .insert(db.as_ref())
+        .await
+        .unwrap()
+        .id;
+        let claim = || async {
+            let events = outbox.claim_due(1000, 60).await.unwrap();
+            events.into_iter().find(|event| event.id == id).unwrap()
+        };
+
+        let first = claim().await;
+        assert!(outbox.renew_lease(&first, 60).await.unwrap());
+
+        // Let the lease run out and another relay claim the event.
+        let mut expired: OutboxEventActiveModel = first.clone().into();
+        expired.next_attempt_on = Set(now);
+        expired.update(db.as_ref()).await.unwrap();
+        assert!(!outbox.renew_lease(&first, 60).await.unwrap());
+        let second = claim().await;
+        assert!(!outbox.renew_lease(&first, 60).await.unwrap());
+        assert!(outbox.renew_lease(&second, 60).await.unwrap());
+
+        OutboxEventEntity::delete_by_id(id)
+            .exec(db.as_ref())
+            .await
+            .unwrap();
+    }
+}
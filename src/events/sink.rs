@@ -0,0 +1,39 @@
+use crate::db::models::OutboxEventModel;
+
+/// A destination for outbox events.
+///
+/// Delivery is at least once: an event is retried until every sink has
+/// accepted it in the same attempt, so a sink may see an event again after
+/// it or another sink failed. Sinks drop duplicates by `event_id`.
+#[async_trait::async_trait]
+pub trait EventSink: Send + Sync {
+    /// Name used in logs and recorded errors.
+    fn name(&self) -> &'static str;
+
+    /// Delivers one event; an error schedules a retry.
+    async fn deliver(&self, event: &OutboxEventModel) -> Result<(), String>;
+}
+
+/// Writes each event to the `events` log target. The payload is left out,
+/// as it holds personal data.
+pub struct LogSink;
+
+#[async_trait::async_trait]
+impl EventSink for LogSink {
+    fn name(&self) -> &'static str {
+        "log"
+    }
+
+    async fn deliver(&self, event: &OutboxEventModel) -> Result<(), String> {
+        log::info!(
+            target: "events",
+            event_id = event.event_id.to_string().as_str(),
+            attempts = event.attempts;
+            "{} for {} {}",
+            event.event_type,
+            event.aggregate_type,
+            event.aggregate_id
+        );
+        Ok(())
+    }
+}
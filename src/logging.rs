@@ -0,0 +1,202 @@
+use env_logger::fmt::Formatter;
+use log::Record;
+use log::kv::{self, Key, Value, VisitSource};
+use serde_json::{Map, json};
+use std::borrow::Cow;
+use std::io::{self, Write};
+
+use crate::config::{LogFormat, LoggingConfig};
+use crate::redaction;
+
+/// Request-scoped fields attached to every log record emitted while the
+/// request is handled.
+#[derive(Debug, Clone)]
+pub struct RequestContext {
+    pub request_id: String,
+    pub method: String,
+    pub route: String,
+}
+
+tokio::task_local! {
+    static REQUEST_CONTEXT: RequestContext;
+}
+
+impl RequestContext {
+    /// Runs `fut` with this context visible to the logger.
+    pub async fn scope<F: Future>(self, fut: F) -> F::Output {
+        REQUEST_CONTEXT.scope(self, fut).await
+    }
+
+    /// Context of the request being handled on this task, if any.
+    pub fn current() -> Option<RequestContext> {
+        REQUEST_CONTEXT.try_with(RequestContext::clone).ok()
+    }
+}
+
+pub fn init(config: &LoggingConfig) {
+    redaction::init(&config.redaction);
+
+    let mut builder = env_logger::Builder::new();
+    builder.parse_filters(&config.level);
+
+    match config.format {
+        LogFormat::Text => builder.format(write_text),
+        LogFormat::Json => builder.format(write_json),
+    };
+
+    builder.init();
+}
+
+fn write_text(buf: &mut Formatter, record: &Record) -> io::Result<()> {
+    let timestamp = buf.timestamp_millis();
+    write!(
+        buf,
+        "[{} {:<5} {}]",
+        timestamp,
+        record.level(),
+        record.target()
+    )?;
+    if let Some(context) = RequestContext::current() {
+        write!(buf, " [{}]", context.request_id)?;
+    }
+    write!(buf, " {}", redaction::scrub(&record.args().to_string()))?;
+
+    let mut fields = TextFields(buf);
+    record
+        .key_values()
+        .visit(&mut fields)
+        .map_err(io::Error::other)?;
+    writeln!(buf)
+}
+
+fn write_json(buf: &mut Formatter, record: &Record) -> io::Result<()> {
+    let mut line = Map::new();
+    line.insert(
+        "timestamp".into(),
+        json!(buf.timestamp_millis().to_string()),
+    );
+    line.insert("level".into(), json!(record.level().as_str()));
+    line.insert("target".into(), json!(record.target()));
+    if let Some(context) = RequestContext::current() {
+        line.insert("request_id".into(), json!(context.request_id));
+        line.insert("method".into(), json!(context.method));
+        line.insert("route".into(), json!(context.route));
+    }
+    line.insert(
+        "message".into(),
+        json!(redaction::scrub(&record.args().to_string())),
+    );
+
+    let mut fields = JsonFields(&mut line);
+    record
+        .key_values()
+        .visit(&mut fields)
+        .map_err(io::Error::other)?;
+
+    serde_json::to_writer(&mut *buf, &line)?;
+    writeln!(buf)
+}
+
+/// Writes key-value pairs as ` key=value`, scrubbed like the message.
+struct TextFields<'a, W: Write>(&'a mut W);
+
+impl<'kvs, W: Write> VisitSource<'kvs> for TextFields<'_, W> {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), kv::Error> {
+        write!(self.0, " {}={}", key, redaction::scrub(&value.to_string()))?;
+        Ok(())
+    }
+}
+
+/// Adds key-value pairs to a JSON line, scrubbing every string in them.
+struct JsonFields<'a>(&'a mut Map<String, serde_json::Value>);
+
+impl<'kvs> VisitSource<'kvs> for JsonFields<'_> {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), kv::Error> {
+        let mut value = serde_json::to_value(&value).map_err(kv::Error::boxed)?;
+        scrub_json(&mut value);
+        self.0.insert(key.to_string(), value);
+        Ok(())
+    }
+}
+
+fn scrub_json(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::String(text) => {
+            if let Cow::Owned(scrubbed) = redaction::scrub(text) {
+                *text = scrubbed;
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(scrub_json),
+        serde_json::Value::Object(fields) => fields.values_mut().for_each(scrub_json),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The default rules mask emails and drop phone numbers.
+
+    fn fields_of(record: &Record) -> (String, Map<String, serde_json::Value>) {
+        let mut text = Vec::new();
+        record
+            .key_values()
+            .visit(&mut TextFields(&mut text))
+            .unwrap();
+        let mut json = Map::new();
+        record
+            .key_values()
+            .visit(&mut JsonFields(&mut json))
+            .unwrap();
+        (String::from_utf8(text).unwrap(), json)
+    }
+
+    #[test]
+    fn field_values_are_scrubbed() {
+        let pairs: &[(&str, Value)] = &[
+            ("to", Value::from("jane.doe@example.com")),
+            ("phone", Value::from("+1 (555) 123-4567")),
+            ("attempts", Value::from(3)),
+            (
+                "event_id",
+                Value::from("12345678-4235-9446-2ba5-555123456789"),
+            ),
+        ];
+        let record = Record::builder()
+            .args(format_args!("sent"))
+            .key_values(&pairs)
+            .build();
+
+        let (text, json) = fields_of(&record);
+
+        assert_eq!(
+            text,
+            concat!(
+                " to=j***@example.com phone=[REDACTED] attempts=3",
+                " event_id=12345678-4235-9446-2ba5-555123456789"
+            )
+        );
+        assert_eq!(
+            serde_json::Value::Object(json),
+            json!({
+                "to": "j***@example.com",
+                "phone": "[REDACTED]",
+                "attempts": 3,
+                "event_id": "12345678-4235-9446-2ba5-555123456789",
+            })
+        );
+    }
+
+    #[test]
+    fn nested_strings_are_scrubbed() {
+        let mut value = json!({ "recipients": ["jane@example.com", "ok"], "count": 1 });
+
+        scrub_json(&mut value);
+
+        assert_eq!(
+            value,
+            json!({ "recipients": ["j***@example.com", "ok"], "count": 1 })
+        );
+    }
+}
@@ -1,35 +1,95 @@
 pub mod api;
+pub mod audit;
+pub mod auth;
 pub mod config;
 pub mod db;
 pub mod domain;
 pub mod error;
+pub mod events;
+pub mod logging;
+pub mod metrics;
+pub mod middleware;
+pub mod rate_limit;
+pub mod redaction;
+pub mod shutdown;
+pub mod telemetry;
+pub mod webhooks;
 
-use actix_web::{App, HttpServer, middleware::Logger, web};
+use actix_web::middleware::from_fn;
+use actix_web::{App, HttpServer, web};
+use clap::Parser;
 use dotenv::dotenv;
-use sea_orm::{Database, DbConn};
+use sea_orm::DbConn;
 use sea_orm_migration::MigratorTrait;
 use std::io;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use crate::config::AppConfig;
+use crate::auth::{BearerTokenExtractor, JwtKeys, PrincipalExtractor};
+use crate::config::{AppConfig, Cli, Command};
 use crate::db::migrations::Migrator;
+use crate::events::Relay;
+use crate::metrics::metrics;
+use crate::rate_limit::RateLimiter;
+use crate::shutdown::Shutdown;
+use crate::webhooks::Dispatcher;
 
 #[tokio::main]
 async fn main() -> io::Result<()> {
     dotenv().ok();
 
-    env_logger::init_from_env(env_logger::Env::new().default_filter_or("info"));
+    let cli = Cli::parse();
+    let app_config = match AppConfig::load(&cli) {
+        Ok(app_config) => app_config,
+        Err(err) => {
+            eprint!("{}", err);
+            std::process::exit(2);
+        }
+    };
 
-    let app_config = AppConfig::from_env();
+    logging::init(&app_config.logging);
 
-    log::info!(
-        "Starting server at {}:{}",
-        app_config.server.host,
-        app_config.server.port
-    );
+    log::info!("Loaded configuration profile '{}'", app_config.profile);
 
-    let db: DbConn = Database::connect(&app_config.database.url)
-        .await
-        .expect("Error connecting to the database");
+    let tracer_provider = match telemetry::init(&app_config.tracing) {
+        Ok(provider) => provider,
+        Err(err) => {
+            log::error!("Could not initialise tracing: {}", err);
+            std::process::exit(1);
+        }
+    };
+
+    if cli.command.is_none() {
+        log::info!(
+            "Starting server at {}:{}",
+            app_config.server.host,
+            app_config.server.port
+        );
+    }
+
+    let db: DbConn = match db::connect(&app_config.database).await {
+        Ok(db) => db,
+        Err(err) => {
+            log::error!("Could not connect to the database: {}", err);
+            std::process::exit(1);
+        }
+    };
+
+    if let Some(Command::VerifyAudit) = cli.command {
+        let key = audit::checkpoint::verifying_key(&app_config.audit);
+        let report = match audit::verify::verify(&db, key.as_ref()).await {
+            Ok(report) => report,
+            Err(err) => {
+                log::error!("Could not verify the audit chain: {}", err);
+                std::process::exit(1);
+            }
+        };
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).expect("the report always serializes")
+        );
+        std::process::exit(if report.intact { 0 } else { 3 });
+    }
 
     log::info!("Running database migrations...");
     Migrator::up(&db, None)
@@ -37,16 +97,151 @@ async fn main() -> io::Result<()> {
         .expect("Failed to run migrations");
     log::info!("Database migrations completed successfully");
 
-    HttpServer::new(move || {
-        App::new()
+    let auth_config = &app_config.auth;
+    if let (Some(username), Some(email), Some(password)) = (
+        &auth_config.bootstrap_admin,
+        &auth_config.bootstrap_admin_email,
+        &auth_config.bootstrap_admin_password,
+    ) {
+        auth::bootstrap::ensure_admin(&db, username, email, password.expose())
+            .await
+            .expect("Failed to bootstrap admin user");
+    }
+
+    let shutdown = Shutdown::new();
+    let shutdown_data = web::Data::new(shutdown.clone());
+    let config_data = web::Data::new(app_config.clone());
+    let jwt_keys = web::Data::new(JwtKeys::new(&app_config.auth));
+    let principal_extractor: web::Data<dyn PrincipalExtractor> =
+        web::Data::from(Arc::new(BearerTokenExtractor) as Arc<dyn PrincipalExtractor>);
+    if let Some(key) = audit::checkpoint::signing_key(&app_config.audit) {
+        audit::checkpoint::spawn_checkpointer(
+            key,
+            db.clone(),
+            Duration::from_secs(app_config.audit.checkpoint_interval_secs),
+            &shutdown,
+        );
+    }
+    if app_config.outbox.enabled {
+        Arc::new(Relay::new(&app_config.outbox, &db)).spawn(&shutdown);
+    }
+    if app_config.webhooks.enabled {
+        Arc::new(Dispatcher::new(&app_config.webhooks, &db)).spawn(&shutdown);
+    }
+    let cors_policy = web::Data::new(middleware::CorsPolicy::new(&app_config.cors));
+    let security_headers = app_config.security_headers.enabled.then(|| {
+        web::Data::new(middleware::SecurityHeaders::new(
+            &app_config.security_headers,
+        ))
+    });
+    let rate_limiter = app_config.rate_limit.enabled.then(|| {
+        let limiter = Arc::new(RateLimiter::new(&app_config.rate_limit, &db));
+        limiter.clone().spawn_purger(&shutdown);
+        web::Data::from(limiter)
+    });
+
+    let app_db = db.clone();
+    let server = HttpServer::new(move || {
+        let db = app_db.clone();
+        let app = App::new()
             .app_data(web::Data::new(db.clone()))
-            .configure(|config| api::configure_routes(config, db.clone()))
-            .wrap(Logger::default())
-    })
-    .bind(format!(
-        "{}:{}",
-        app_config.server.host, app_config.server.port
-    ))?
-    .run()
-    .await
+            .app_data(shutdown_data.clone())
+            .app_data(config_data.clone())
+            .app_data(jwt_keys.clone())
+            .app_data(principal_extractor.clone())
+            .app_data(cors_policy.clone());
+        let app = match &rate_limiter {
+            Some(limiter) => app.app_data(limiter.clone()),
+            None => app,
+        };
+        let app = match &security_headers {
+            Some(headers) => app.app_data(headers.clone()),
+            None => app,
+        };
+        app.configure(|config| api::configure_routes(config, db.clone()))
+            .wrap(from_fn(middleware::rate_limit))
+            .wrap(from_fn(middleware::problem_details))
+            .wrap(from_fn(middleware::security_headers))
+            .wrap(from_fn(middleware::cors))
+            .wrap(from_fn(middleware::track_requests))
+            .wrap(from_fn(middleware::trace_requests))
+            .wrap(from_fn(middleware::access_log))
+            .wrap(from_fn(middleware::request_id))
+    });
+
+    let server = match app_config.server.workers {
+        Some(workers) => server.workers(workers),
+        None => server,
+    };
+
+    // Signals are handled below so readiness can fail before the
+    // listeners close.
+    let server = server
+        .disable_signals()
+        .shutdown_timeout(app_config.shutdown.timeout_secs)
+        .bind(format!(
+            "{}:{}",
+            app_config.server.host, app_config.server.port
+        ))?
+        .run();
+    let handle = server.handle();
+    tokio::pin!(server);
+
+    let signal = tokio::select! {
+        result = &mut server => {
+            log::error!("Server stopped unexpectedly");
+            return result;
+        }
+        signal = shutdown::signal() => signal,
+    };
+
+    let started = Instant::now();
+    let timeout = Duration::from_secs(app_config.shutdown.timeout_secs);
+    shutdown.trigger();
+    log::info!(
+        "Received {}, shutting down: readiness now fails, {} request(s) in flight",
+        signal,
+        metrics().http_in_flight.get()
+    );
+
+    tokio::time::sleep(Duration::from_secs(app_config.shutdown.drain_delay_secs)).await;
+    log::info!("Closing listeners and draining in-flight requests");
+    let draining = Instant::now();
+    let (_, result) = tokio::join!(handle.stop(true), server);
+    let drained_in = draining.elapsed();
+
+    let running_tasks = shutdown.running_tasks();
+    let tasks_finished = shutdown
+        .wait_for_tasks(timeout.saturating_sub(started.elapsed()))
+        .await;
+    if !tasks_finished {
+        log::warn!(
+            "{} background task(s) still running after the shutdown timeout",
+            shutdown.running_tasks()
+        );
+    }
+
+    let db_closed = match db.close().await {
+        Ok(()) => true,
+        Err(err) => {
+            log::warn!("Failed to close the database pool: {}", err);
+            false
+        }
+    };
+
+    if let Err(err) = tracer_provider.shutdown() {
+        log::warn!("Failed to flush pending spans: {}", err);
+    }
+
+    log::info!(
+        "Shutdown complete in {} ms: {} request(s) served, requests drained in {} ms, {} of {} background task(s) finished, database pool {}",
+        started.elapsed().as_millis(),
+        metrics().requests_served(),
+        drained_in.as_millis(),
+        running_tasks - shutdown.running_tasks(),
+        running_tasks,
+        if db_closed { "closed" } else { "not closed" }
+    );
+
+    result
 }
@@ -0,0 +1,236 @@
+use prometheus::core::Collector;
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry,
+    TextEncoder,
+};
+use sea_orm::DbConn;
+use std::future::Future;
+use std::sync::LazyLock;
+use std::time::Instant;
+
+const QUERY_BUCKETS: &[f64] = &[
+    0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5,
+];
+
+/// Process-wide Prometheus collectors.
+pub struct Metrics {
+    registry: Registry,
+    pub http_requests: IntCounterVec,
+    pub http_request_duration: HistogramVec,
+    pub http_in_flight: IntGauge,
+    pub db_pool_connections: IntGaugeVec,
+    pub db_pool_max: IntGauge,
+    pub db_pool_waiting: IntGauge,
+    pub db_queries_in_flight: IntGauge,
+    pub db_query_duration: HistogramVec,
+    pub outbox_deliveries: IntCounterVec,
+    pub webhook_attempts: IntCounterVec,
+}
+
+static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+pub fn metrics() -> &'static Metrics {
+    &METRICS
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new();
+
+        let http_requests = IntCounterVec::new(
+            Opts::new(
+                "http_requests_total",
+                "HTTP requests by route and status class",
+            ),
+            &["method", "route", "status"],
+        )
+        .expect("valid http_requests_total");
+        let http_request_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "HTTP request latency by route and status class",
+            ),
+            &["method", "route", "status"],
+        )
+        .expect("valid http_request_duration_seconds");
+        let http_in_flight = IntGauge::new("http_requests_in_flight", "HTTP requests in progress")
+            .expect("valid http_requests_in_flight");
+        let db_pool_connections = IntGaugeVec::new(
+            Opts::new(
+                "db_pool_connections",
+                "Pooled database connections by state",
+            ),
+            &["state"],
+        )
+        .expect("valid db_pool_connections");
+        let db_pool_max = IntGauge::new("db_pool_max_connections", "Configured pool size limit")
+            .expect("valid db_pool_max_connections");
+        let db_pool_waiting = IntGauge::new(
+            "db_pool_waiting",
+            "Estimated repository calls waiting for a pooled connection",
+        )
+        .expect("valid db_pool_waiting");
+        let db_queries_in_flight =
+            IntGauge::new("db_queries_in_flight", "Repository calls in progress")
+                .expect("valid db_queries_in_flight");
+        let db_query_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "db_query_duration_seconds",
+                "Repository call latency by method and outcome",
+            )
+            .buckets(QUERY_BUCKETS.to_vec()),
+            &["repository", "method", "outcome"],
+        )
+        .expect("valid db_query_duration_seconds");
+        let outbox_deliveries = IntCounterVec::new(
+            Opts::new(
+                "outbox_deliveries_total",
+                "Outbox event delivery attempts by outcome",
+            ),
+            &["outcome"],
+        )
+        .expect("valid outbox_deliveries_total");
+        let webhook_attempts = IntCounterVec::new(
+            Opts::new(
+                "webhook_attempts_total",
+                "Webhook delivery attempts by resulting state",
+            ),
+            &["outcome"],
+        )
+        .expect("valid webhook_attempts_total");
+
+        for collector in [
+            Box::new(http_requests.clone()) as Box<dyn prometheus::core::Collector>,
+            Box::new(http_request_duration.clone()),
+            Box::new(http_in_flight.clone()),
+            Box::new(db_pool_connections.clone()),
+            Box::new(db_pool_max.clone()),
+            Box::new(db_pool_waiting.clone()),
+            Box::new(db_queries_in_flight.clone()),
+            Box::new(db_query_duration.clone()),
+            Box::new(outbox_deliveries.clone()),
+            Box::new(webhook_attempts.clone()),
+        ] {
+            registry
+                .register(collector)
+                .expect("metric registered once");
+        }
+
+        Metrics {
+            registry,
+            http_requests,
+            http_request_duration,
+            http_in_flight,
+            db_pool_connections,
+            db_pool_max,
+            db_pool_waiting,
+            db_queries_in_flight,
+            db_query_duration,
+            outbox_deliveries,
+            webhook_attempts,
+        }
+    }
+
+    /// Samples the pool gauges and encodes every metric in the Prometheus
+    /// text exposition format.
+    pub fn render(&self, db: &DbConn, max_connections: u32) -> String {
+        let pool = db.get_postgres_connection_pool();
+        let size = i64::from(pool.size());
+        let idle = pool.num_idle() as i64;
+        let in_use = size - idle;
+
+        self.db_pool_connections
+            .with_label_values(&["idle"])
+            .set(idle);
+        self.db_pool_connections
+            .with_label_values(&["in_use"])
+            .set(in_use);
+        self.db_pool_max.set(i64::from(max_connections));
+        // sqlx does not expose its wait queue; calls in flight beyond the
+        // connections in use are waiting to acquire one.
+        self.db_pool_waiting
+            .set((self.db_queries_in_flight.get() - in_use).max(0));
+
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .expect("text encoding never fails");
+        String::from_utf8(buffer).unwrap_or_default()
+    }
+
+    /// Requests completed since startup, across every route and status.
+    pub fn requests_served(&self) -> u64 {
+        self.http_requests
+            .collect()
+            .iter()
+            .flat_map(|family| family.get_metric())
+            .map(|metric| metric.get_counter().get_value() as u64)
+            .sum()
+    }
+}
+
+/// Raises a gauge for as long as it lives. The decrement happens on drop,
+/// so a future cancelled mid-await still brings the gauge back down.
+pub struct InFlight(IntGauge);
+
+impl InFlight {
+    pub fn new(gauge: &IntGauge) -> Self {
+        gauge.inc();
+        Self(gauge.clone())
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.dec();
+    }
+}
+
+/// Times a repository call under `repository` and `method`.
+pub async fn observe_query<T, E>(
+    repository: &'static str,
+    method: &'static str,
+    query: impl Future<Output = Result<T, E>>,
+) -> Result<T, E> {
+    let metrics = metrics();
+    let in_flight = InFlight::new(&metrics.db_queries_in_flight);
+    let started = Instant::now();
+
+    let result = query.await;
+
+    drop(in_flight);
+    let outcome = if result.is_ok() { "ok" } else { "error" };
+    metrics
+        .db_query_duration
+        .with_label_values(&[repository, method, outcome])
+        .observe(started.elapsed().as_secs_f64());
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::FutureExt;
+
+    #[test]
+    fn in_flight_guard_decrements_on_drop() {
+        let gauge = IntGauge::new("test_in_flight", "test").unwrap();
+
+        let guard = InFlight::new(&gauge);
+        assert_eq!(gauge.get(), 1);
+        drop(guard);
+        assert_eq!(gauge.get(), 0);
+    }
+
+    #[test]
+    fn cancelled_query_is_not_left_in_flight() {
+        let gauge = &metrics().db_queries_in_flight;
+        let before = gauge.get();
+
+        let query = observe_query("test", "pending", std::future::pending::<Result<(), ()>>());
+        assert!(query.now_or_never().is_none());
+
+        assert_eq!(gauge.get(), before);
+    }
+}
@@ -0,0 +1,38 @@
+use actix_web::Error;
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use std::time::Instant;
+
+use super::client_ip;
+
+/// Logs one line per completed request; method, route and request id come
+/// from the request context.
+pub async fn access_log(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let method = req.method().to_string();
+    let path = req.path().to_owned();
+    let client_ip = client_ip(req.request()).unwrap_or_else(|| "-".to_owned());
+
+    let started = Instant::now();
+    let result = next.call(req).await;
+    let latency_ms = started.elapsed().as_secs_f64() * 1000.0;
+
+    let status = match &result {
+        Ok(res) => res.status(),
+        Err(err) => err.as_response_error().status_code(),
+    };
+    log::info!(
+        target: "access",
+        status = status.as_u16(),
+        latency_ms = (latency_ms * 1000.0).round() / 1000.0,
+        client_ip = client_ip.as_str();
+        "{} {}",
+        method,
+        path
+    );
+
+    result
+}
@@ -0,0 +1,53 @@
+use actix_web::dev::ConnectionInfo;
+use actix_web::{HttpRequest, web};
+
+use crate::config::AppConfig;
+
+/// The client address recorded in audit entries and access logs.
+///
+/// `Forwarded`/`X-Forwarded-For` can be set by any caller, so they are only
+/// honoured when `rate_limit.trust_forwarded_for` says a proxy sets them;
+/// otherwise this is the peer address.
+pub fn client_ip(req: &HttpRequest) -> Option<String> {
+    let trust_forwarded_for = req
+        .app_data::<web::Data<AppConfig>>()
+        .is_some_and(|config| config.rate_limit.trust_forwarded_for);
+    resolve(&req.connection_info(), trust_forwarded_for)
+}
+
+fn resolve(info: &ConnectionInfo, trust_forwarded_for: bool) -> Option<String> {
+    if trust_forwarded_for {
+        info.realip_remote_addr()
+    } else {
+        info.peer_addr()
+    }
+    .map(str::to_owned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    fn request() -> HttpRequest {
+        TestRequest::default()
+            .insert_header(("x-forwarded-for", "198.51.100.9"))
+            .peer_addr("203.0.113.7:40000".parse().unwrap())
+            .to_http_request()
+    }
+
+    #[test]
+    fn forwarded_for_is_ignored_unless_trusted() {
+        let req = request();
+
+        assert_eq!(
+            resolve(&req.connection_info(), false).as_deref(),
+            Some("203.0.113.7")
+        );
+        assert_eq!(
+            resolve(&req.connection_info(), true).as_deref(),
+            Some("198.51.100.9")
+        );
+        assert_eq!(client_ip(&req).as_deref(), Some("203.0.113.7"));
+    }
+}
@@ -0,0 +1,204 @@
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::Method;
+use actix_web::http::header::{
+    ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS,
+    ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_EXPOSE_HEADERS, ACCESS_CONTROL_MAX_AGE,
+    ACCESS_CONTROL_REQUEST_METHOD, HeaderMap, HeaderValue, ORIGIN, VARY,
+};
+use actix_web::middleware::Next;
+use actix_web::{Error, HttpResponse, web};
+
+use super::extra_headers::with_headers;
+use crate::config::CorsConfig;
+
+/// The CORS policy from [`CorsConfig`], with header values prepared once.
+pub struct CorsPolicy {
+    origins: Vec<String>,
+    any_origin: bool,
+    allow_credentials: bool,
+    allowed_methods: HeaderValue,
+    allowed_headers: HeaderValue,
+    exposed_headers: HeaderValue,
+    max_age: HeaderValue,
+}
+
+impl CorsPolicy {
+    pub fn new(config: &CorsConfig) -> Self {
+        let list = |values: &[String]| {
+            HeaderValue::from_str(&values.join(", ")).unwrap_or(HeaderValue::from_static(""))
+        };
+        Self {
+            origins: config.allowed_origins.clone(),
+            any_origin: config.allowed_origins.iter().any(|origin| origin == "*"),
+            allow_credentials: config.allow_credentials,
+            allowed_methods: list(&config.allowed_methods),
+            allowed_headers: list(&config.allowed_headers),
+            exposed_headers: list(&config.exposed_headers),
+            max_age: HeaderValue::from(config.max_age_secs),
+        }
+    }
+
+    fn allows(&self, origin: &str) -> bool {
+        self.any_origin
+            || self
+                .origins
+                .iter()
+                .any(|pattern| origin_matches(pattern, origin))
+    }
+
+    /// Headers shared by preflight and actual responses.
+    fn origin_headers(&self, origin: &HeaderValue) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if self.any_origin {
+            headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, HeaderValue::from_static("*"));
+        } else {
+            headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, origin.clone());
+            headers.insert(VARY, HeaderValue::from_static("Origin"));
+        }
+        if self.allow_credentials {
+            headers.insert(
+                ACCESS_CONTROL_ALLOW_CREDENTIALS,
+                HeaderValue::from_static("true"),
+            );
+        }
+        headers
+    }
+}
+
+/// Matches an origin against a configured pattern, where one `*` stands for
+/// host labels or a port but never crosses into the scheme or a path.
+fn origin_matches(pattern: &str, origin: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern.eq_ignore_ascii_case(origin),
+        Some((prefix, suffix)) => {
+            let origin = origin.to_ascii_lowercase();
+            origin.len() > prefix.len() + suffix.len()
+                && origin.starts_with(&prefix.to_ascii_lowercase())
+                && origin.ends_with(&suffix.to_ascii_lowercase())
+                && origin[prefix.len()..origin.len() - suffix.len()]
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.'))
+        }
+    }
+}
+
+/// Applies the registered [`CorsPolicy`], if any. Preflight requests from an
+/// allowed origin are answered here; requests from other origins pass
+/// through without CORS headers, so browsers block them.
+pub async fn cors(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, Error> {
+    let policy = req.app_data::<web::Data<CorsPolicy>>().cloned();
+    let origin = req.headers().get(ORIGIN).cloned();
+
+    let (policy, origin) = match (policy, origin) {
+        (Some(policy), Some(origin))
+            if origin.to_str().is_ok_and(|origin| policy.allows(origin)) =>
+        {
+            (policy, origin)
+        }
+        _ => {
+            return next
+                .call(req)
+                .await
+                .map(ServiceResponse::map_into_boxed_body);
+        }
+    };
+
+    let mut headers = policy.origin_headers(&origin);
+
+    if req.method() == Method::OPTIONS && req.headers().contains_key(ACCESS_CONTROL_REQUEST_METHOD)
+    {
+        headers.insert(ACCESS_CONTROL_ALLOW_METHODS, policy.allowed_methods.clone());
+        headers.insert(ACCESS_CONTROL_ALLOW_HEADERS, policy.allowed_headers.clone());
+        headers.insert(ACCESS_CONTROL_MAX_AGE, policy.max_age.clone());
+
+        let mut res = HttpResponse::NoContent().finish();
+        for (name, value) in headers {
+            res.headers_mut().insert(name, value);
+        }
+        return Ok(req.into_response(res));
+    }
+
+    headers.insert(
+        ACCESS_CONTROL_EXPOSE_HEADERS,
+        policy.exposed_headers.clone(),
+    );
+    with_headers(
+        next.call(req)
+            .await
+            .map(ServiceResponse::map_into_boxed_body),
+        headers,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact_patterns_ignore_case() {
+        assert!(origin_matches(
+            "https://app.example.com",
+            "https://app.example.com"
+        ));
+        assert!(origin_matches(
+            "https://app.example.com",
+            "HTTPS://App.Example.com"
+        ));
+        assert!(!origin_matches(
+            "https://app.example.com",
+            "http://app.example.com"
+        ));
+        assert!(!origin_matches(
+            "https://app.example.com",
+            "https://app.example.com.evil.io"
+        ));
+    }
+
+    #[test]
+    fn wildcard_matches_host_labels_and_ports() {
+        assert!(origin_matches(
+            "https://*.example.com",
+            "https://app.example.com"
+        ));
+        assert!(origin_matches(
+            "https://*.example.com",
+            "https://a.b.example.com"
+        ));
+        assert!(origin_matches(
+            "http://localhost:*",
+            "http://localhost:5173"
+        ));
+    }
+
+    #[test]
+    fn wildcard_does_not_cross_into_scheme_or_path() {
+        assert!(!origin_matches(
+            "https://*.example.com",
+            "https://.example.com"
+        ));
+        assert!(!origin_matches(
+            "https://*.example.com",
+            "https://example.com"
+        ));
+        assert!(!origin_matches(
+            "https://*.example.com",
+            "http://app.example.com"
+        ));
+        assert!(!origin_matches(
+            "https://*.example.com",
+            "https://evil.io/x.example.com"
+        ));
+        assert!(!origin_matches(
+            "https://*.example.com",
+            "https://evil.io?.example.com"
+        ));
+        assert!(!origin_matches(
+            "https://*.example.com",
+            "https://evil.io#.example.com"
+        ));
+    }
+}
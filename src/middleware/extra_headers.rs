@@ -0,0 +1,61 @@
+use actix_web::body::MessageBody;
+use actix_web::dev::ServiceResponse;
+use actix_web::http::StatusCode;
+use actix_web::http::header::HeaderMap;
+use actix_web::{Error, HttpResponse, ResponseError};
+use std::fmt;
+
+/// Adds `headers` the response does not already set. Errors raised by inner
+/// middleware have no response yet, so they are wrapped to add the headers
+/// once rendered.
+pub(super) fn with_headers<B: MessageBody>(
+    result: Result<ServiceResponse<B>, Error>,
+    headers: HeaderMap,
+) -> Result<ServiceResponse<B>, Error> {
+    match result {
+        Ok(mut res) => {
+            insert_missing(res.headers_mut(), &headers);
+            Ok(res)
+        }
+        Err(error) => Err(WithHeaders { error, headers }.into()),
+    }
+}
+
+fn insert_missing(target: &mut HeaderMap, headers: &HeaderMap) {
+    for name in headers.keys() {
+        if !target.contains_key(name) {
+            for value in headers.get_all(name) {
+                target.append(name.clone(), value.clone());
+            }
+        }
+    }
+}
+
+struct WithHeaders {
+    error: Error,
+    headers: HeaderMap,
+}
+
+impl fmt::Debug for WithHeaders {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.error, f)
+    }
+}
+
+impl fmt::Display for WithHeaders {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.error, f)
+    }
+}
+
+impl ResponseError for WithHeaders {
+    fn status_code(&self) -> StatusCode {
+        self.error.as_response_error().status_code()
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let mut res = self.error.error_response();
+        insert_missing(res.headers_mut(), &self.headers);
+        res
+    }
+}
@@ -0,0 +1,40 @@
+use actix_web::Error;
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use std::time::Instant;
+
+use crate::metrics::{InFlight, metrics};
+
+/// Records request count and latency per matched route pattern, e.g.
+/// `/api/users/{id}`, so ids do not explode label cardinality.
+pub async fn track_requests(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let metrics = metrics();
+    let method = req.method().to_string();
+    let route = req
+        .match_pattern()
+        .unwrap_or_else(|| "unmatched".to_string());
+
+    let in_flight = InFlight::new(&metrics.http_in_flight);
+    let started = Instant::now();
+    let result = next.call(req).await;
+    drop(in_flight);
+
+    let status = match &result {
+        Ok(res) => res.status(),
+        Err(err) => err.as_response_error().status_code(),
+    };
+    let status_class = format!("{}xx", status.as_u16() / 100);
+    let labels = [method.as_str(), route.as_str(), status_class.as_str()];
+
+    metrics.http_requests.with_label_values(&labels).inc();
+    metrics
+        .http_request_duration
+        .with_label_values(&labels)
+        .observe(started.elapsed().as_secs_f64());
+
+    result
+}
@@ -0,0 +1,20 @@
+mod access_log;
+mod client_ip;
+mod cors;
+mod extra_headers;
+mod metrics;
+mod problem_details;
+mod rate_limit;
+mod request_id;
+mod security_headers;
+mod tracing;
+
+pub use access_log::access_log;
+pub use client_ip::client_ip;
+pub use cors::{CorsPolicy, cors};
+pub use metrics::track_requests;
+pub use problem_details::problem_details;
+pub use rate_limit::rate_limit;
+pub use request_id::{REQUEST_ID_HEADER, RequestId, request_id};
+pub use security_headers::{SecurityHeaders, security_headers};
+pub use tracing::trace_requests;
@@ -0,0 +1,114 @@
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::StatusCode;
+use actix_web::http::header::{CONTENT_LENGTH, CONTENT_TYPE, HeaderValue};
+use actix_web::middleware::Next;
+use actix_web::{Error, HttpMessage, HttpResponse, ResponseError};
+use std::fmt;
+
+use super::request_id::{REQUEST_ID_HEADER, RequestId};
+use crate::error::{AppError, ErrorCode, ErrorResponse};
+
+/// Rewrites every error response into a problem document carrying the
+/// request path as `instance` and the request id for log correlation.
+pub async fn problem_details(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, Error> {
+    let request_id = req.extensions().get::<RequestId>().map(|id| id.0.clone());
+    let instance = req.path().to_owned();
+
+    let problem_for = |err: &Error, status: StatusCode| {
+        let mut problem = match err.as_error::<AppError>() {
+            Some(app_error) => app_error.to_problem(),
+            None => generic_problem(err, status.as_u16()),
+        };
+        problem.instance = Some(instance.clone());
+        problem.request_id = request_id.clone();
+        problem
+    };
+
+    // Errors returned by inner middleware never reach a handler; they are
+    // re-raised as a problem so the outer layers render it unchanged.
+    let res = match next.call(req).await {
+        Ok(res) => res.map_into_boxed_body(),
+        Err(err) => {
+            let status = err.as_response_error().status_code();
+            return Err(Problem(problem_for(&err, status)).into());
+        }
+    };
+
+    let problem = res
+        .response()
+        .error()
+        .map(|err| problem_for(err, res.status()));
+
+    Ok(match problem {
+        Some(problem) => {
+            // Headers set alongside the error, e.g. `Retry-After`, are kept.
+            let mut rebuilt = problem.into_response();
+            let own = rebuilt.headers().clone();
+            for (name, value) in res.headers() {
+                if !own.contains_key(name) && name != CONTENT_TYPE && name != CONTENT_LENGTH {
+                    rebuilt.headers_mut().append(name.clone(), value.clone());
+                }
+            }
+            res.into_response(rebuilt)
+        }
+        None => res,
+    })
+}
+
+#[derive(Debug)]
+struct Problem(ErrorResponse);
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.detail)
+    }
+}
+
+impl ResponseError for Problem {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::from_u16(self.0.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let mut res = self.0.clone().into_response();
+        if let Some(value) = self
+            .0
+            .request_id
+            .as_deref()
+            .and_then(|id| HeaderValue::from_str(id).ok())
+        {
+            res.headers_mut().insert(REQUEST_ID_HEADER, value);
+        }
+        res
+    }
+}
+
+/// Problem document for errors raised outside our handlers, e.g. by actix
+/// itself; only client errors keep their message.
+fn generic_problem(err: &Error, status: u16) -> ErrorResponse {
+    let (code, detail) = match status {
+        401 => (ErrorCode::Unauthorized, err.to_string()),
+        403 => (ErrorCode::Forbidden, err.to_string()),
+        404 => (ErrorCode::NotFound, err.to_string()),
+        400..=499 => (ErrorCode::InvalidRequest, err.to_string()),
+        _ => (
+            ErrorCode::InternalError,
+            "An internal error occurred".into(),
+        ),
+    };
+
+    ErrorResponse {
+        type_uri: code.type_uri(),
+        title: code.title().into(),
+        status,
+        detail,
+        instance: None,
+        code,
+        errors: None,
+        request_id: None,
+    }
+}
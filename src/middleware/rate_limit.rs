@@ -0,0 +1,174 @@
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{AUTHORIZATION, HeaderMap, HeaderName, HeaderValue};
+use actix_web::middleware::Next;
+use actix_web::{Error, web};
+
+use crate::auth::AuthenticatedUser;
+use crate::error::AppError;
+use crate::rate_limit::{Decision, RateLimiter};
+
+/// Applies the registered [`RateLimiter`], if any, answering `429` once the
+/// client's bucket is empty. Every response carries `RateLimit-*` headers.
+pub async fn rate_limit(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, Error> {
+    let Some(limiter) = req.app_data::<web::Data<RateLimiter>>().cloned() else {
+        return next
+            .call(req)
+            .await
+            .map(ServiceResponse::map_into_boxed_body);
+    };
+
+    let (scope, limit) = limiter.limit_for(req.method(), req.match_pattern().as_deref());
+    let client = client_key(&req, &limiter).await;
+    let decision = limiter.check(&scope, &client, limit).await;
+
+    if !decision.allowed {
+        log::info!(
+            "Rate limited {} in scope '{}', retry in {} second(s)",
+            client,
+            scope,
+            decision.retry_after_secs
+        );
+        let mut res = req.error_response(AppError::TooManyRequests(decision.retry_after_secs));
+        insert_headers(res.headers_mut(), &decision);
+        return Ok(res);
+    }
+
+    let mut res = next.call(req).await?.map_into_boxed_body();
+    insert_headers(res.headers_mut(), &decision);
+    Ok(res)
+}
+
+/// Identifies the client by authenticated user, then known API key, then IP.
+async fn client_key(req: &ServiceRequest, limiter: &RateLimiter) -> String {
+    if req.headers().contains_key(AUTHORIZATION)
+        && let Ok(user) = AuthenticatedUser::identify(req.request()).await
+    {
+        return format!("user:{}", user.id);
+    }
+
+    if let Some(client) = limiter.api_key_client(req.headers()) {
+        return client;
+    }
+
+    let info = req.connection_info();
+    let ip = if limiter.trust_forwarded_for {
+        info.realip_remote_addr()
+    } else {
+        info.peer_addr()
+    };
+    format!("ip:{}", ip.unwrap_or("unknown"))
+}
+
+fn insert_headers(headers: &mut HeaderMap, decision: &Decision) {
+    let values = [
+        ("ratelimit-limit", decision.limit.burst.to_string()),
+        ("ratelimit-remaining", decision.remaining.to_string()),
+        ("ratelimit-reset", decision.reset_secs.to_string()),
+        ("ratelimit-policy", decision.policy()),
+    ];
+    for (name, value) in values {
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            headers.insert(HeaderName::from_static(name), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{RateLimitBackend, RateLimitConfig};
+    use crate::redaction::Redacted;
+    use actix_web::http::StatusCode;
+    use actix_web::http::header::RETRY_AFTER;
+    use actix_web::middleware::from_fn;
+    use actix_web::{App, HttpResponse, test};
+    use sea_orm::DbConn;
+
+    fn limiter() -> web::Data<RateLimiter> {
+        web::Data::new(RateLimiter::new(
+            &RateLimitConfig {
+                enabled: true,
+                backend: RateLimitBackend::Memory,
+                requests_per_minute: 1,
+                burst: 1,
+                routes: Vec::new(),
+                api_key_header: Some("x-api-key".into()),
+                api_keys: vec![Redacted::new("known-key".into())],
+                trust_forwarded_for: false,
+            },
+            &DbConn::Disconnected,
+        ))
+    }
+
+    fn request(api_key: Option<&str>) -> test::TestRequest {
+        let mut req = test::TestRequest::get()
+            .uri("/")
+            .peer_addr("203.0.113.7:40000".parse().unwrap());
+        if let Some(key) = api_key {
+            req = req.insert_header(("x-api-key", key));
+        }
+        req
+    }
+
+    #[actix_web::test]
+    async fn empty_bucket_answers_429_with_retry_after() {
+        let app = test::init_service(
+            App::new()
+                .app_data(limiter())
+                .wrap(from_fn(rate_limit))
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let res = test::call_service(&app, request(None).to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers().get("ratelimit-remaining").unwrap(), "0");
+
+        let res = test::call_service(&app, request(None).to_request()).await;
+        assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(res.headers().get(RETRY_AFTER).unwrap(), "60");
+        assert_eq!(res.headers().get("ratelimit-reset").unwrap(), "60");
+    }
+
+    #[actix_web::test]
+    async fn unknown_api_keys_share_the_ip_bucket() {
+        let app = test::init_service(
+            App::new()
+                .app_data(limiter())
+                .wrap(from_fn(rate_limit))
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let res = test::call_service(&app, request(Some("random-1")).to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        for key in ["random-2", "random-3"] {
+            let res = test::call_service(&app, request(Some(key)).to_request()).await;
+            assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+        }
+        let res = test::call_service(&app, request(None).to_request()).await;
+        assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[actix_web::test]
+    async fn known_api_key_has_its_own_bucket() {
+        let app = test::init_service(
+            App::new()
+                .app_data(limiter())
+                .wrap(from_fn(rate_limit))
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let res = test::call_service(&app, request(None).to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = test::call_service(&app, request(Some("known-key")).to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+}
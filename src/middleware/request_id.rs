@@ -0,0 +1,48 @@
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::middleware::Next;
+use actix_web::{Error, HttpMessage};
+use sea_orm::prelude::Uuid;
+
+use crate::logging::RequestContext;
+
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Longest client-supplied id we accept; longer ones are replaced.
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+/// Id of the current request, stored in the request extensions.
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+/// Accepts the caller's `X-Request-Id` or generates one, makes it available
+/// to handlers and log records, and echoes it in the response.
+pub async fn request_id(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let request_id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty() && value.len() <= MAX_REQUEST_ID_LENGTH)
+        .map(str::to_owned)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    req.extensions_mut().insert(RequestId(request_id.clone()));
+    let context = RequestContext {
+        request_id: request_id.clone(),
+        method: req.method().to_string(),
+        route: req
+            .match_pattern()
+            .unwrap_or_else(|| "unmatched".to_string()),
+    };
+
+    let mut res = context.scope(next.call(req)).await?;
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        res.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+
+    Ok(res)
+}
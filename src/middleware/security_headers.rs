@@ -0,0 +1,148 @@
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{
+    CONTENT_SECURITY_POLICY, CONTENT_TYPE, HeaderMap, HeaderValue, REFERRER_POLICY,
+    STRICT_TRANSPORT_SECURITY, X_CONTENT_TYPE_OPTIONS,
+};
+use actix_web::middleware::Next;
+use actix_web::{Error, web};
+
+use super::extra_headers::with_headers;
+use crate::config::{SecurityHeadersConfig, SecurityHeadersOverride};
+
+/// Headers for one path scope.
+struct Policy {
+    path: String,
+    referrer_policy: String,
+    content_security_policy: String,
+    frame_ancestors: String,
+}
+
+/// The security headers from [`SecurityHeadersConfig`], resolved per scope.
+pub struct SecurityHeaders {
+    hsts: Option<HeaderValue>,
+    default: Policy,
+    /// Longest path first, so the most specific scope wins.
+    scopes: Vec<Policy>,
+}
+
+impl SecurityHeaders {
+    pub fn new(config: &SecurityHeadersConfig) -> Self {
+        let default = Policy {
+            path: "/".into(),
+            referrer_policy: config.referrer_policy.clone(),
+            content_security_policy: config.content_security_policy.clone(),
+            frame_ancestors: config.frame_ancestors.clone(),
+        };
+
+        let mut scopes: Vec<Policy> = config
+            .overrides
+            .iter()
+            .map(|scope: &SecurityHeadersOverride| Policy {
+                path: scope.path.trim_end_matches('/').to_string(),
+                referrer_policy: scope
+                    .referrer_policy
+                    .clone()
+                    .unwrap_or_else(|| default.referrer_policy.clone()),
+                content_security_policy: scope
+                    .content_security_policy
+                    .clone()
+                    .unwrap_or_else(|| default.content_security_policy.clone()),
+                frame_ancestors: scope
+                    .frame_ancestors
+                    .clone()
+                    .unwrap_or_else(|| default.frame_ancestors.clone()),
+            })
+            .collect();
+        scopes.sort_by_key(|scope| std::cmp::Reverse(scope.path.len()));
+
+        let hsts = (config.hsts_max_age_secs > 0).then(|| {
+            let mut value = format!("max-age={}", config.hsts_max_age_secs);
+            if config.hsts_include_subdomains {
+                value.push_str("; includeSubDomains");
+            }
+            HeaderValue::from_str(&value).expect("HSTS value is ASCII")
+        });
+
+        Self {
+            hsts,
+            default,
+            scopes,
+        }
+    }
+
+    fn policy_for(&self, path: &str) -> &Policy {
+        self.scopes
+            .iter()
+            .find(|scope| {
+                path.strip_prefix(&scope.path)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+            })
+            .unwrap_or(&self.default)
+    }
+}
+
+/// Adds the registered [`SecurityHeaders`], if any, to every response.
+/// HTML responses get the full content security policy; others only
+/// `frame-ancestors`. Headers a handler sets itself are kept, and a policy
+/// without `frame-ancestors` gets the configured one appended.
+pub async fn security_headers(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, Error> {
+    let Some(config) = req.app_data::<web::Data<SecurityHeaders>>().cloned() else {
+        return next
+            .call(req)
+            .await
+            .map(ServiceResponse::map_into_boxed_body);
+    };
+    let policy = config.policy_for(req.path());
+
+    let mut headers = HeaderMap::new();
+    headers.insert(X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff"));
+    if let Ok(value) = HeaderValue::from_str(&policy.referrer_policy) {
+        headers.insert(REFERRER_POLICY, value);
+    }
+    if let Some(hsts) = &config.hsts {
+        headers.insert(STRICT_TRANSPORT_SECURITY, hsts.clone());
+    }
+
+    let mut result = next
+        .call(req)
+        .await
+        .map(ServiceResponse::map_into_boxed_body);
+
+    let csp = match &result {
+        Ok(res) => {
+            let is_html = res
+                .headers()
+                .get(CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .is_some_and(|value| value.starts_with("text/html"));
+            match res.headers().get(CONTENT_SECURITY_POLICY) {
+                Some(own) => own.to_str().ok().and_then(|own| {
+                    (!own.contains("frame-ancestors"))
+                        .then(|| format!("{}; frame-ancestors {}", own, policy.frame_ancestors))
+                }),
+                None if is_html => Some(format!(
+                    "{}; frame-ancestors {}",
+                    policy.content_security_policy, policy.frame_ancestors
+                )),
+                None => Some(format!("frame-ancestors {}", policy.frame_ancestors)),
+            }
+        }
+        Err(_) => Some(format!("frame-ancestors {}", policy.frame_ancestors)),
+    };
+    if let Some(value) = csp.and_then(|csp| HeaderValue::from_str(&csp).ok()) {
+        match &mut result {
+            Ok(res) => {
+                res.headers_mut().insert(CONTENT_SECURITY_POLICY, value);
+            }
+            Err(_) => {
+                headers.insert(CONTENT_SECURITY_POLICY, value);
+            }
+        }
+    }
+
+    with_headers(result, headers)
+}
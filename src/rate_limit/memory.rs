@@ -0,0 +1,52 @@
+use sea_orm::DbErr;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::{Limit, RateLimitStore};
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Buckets held by this process; each replica limits independently.
+#[derive(Default)]
+pub struct MemoryStore {
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait::async_trait]
+impl RateLimitStore for MemoryStore {
+    async fn take(&self, key: &str, limit: Limit) -> Result<(f64, bool), DbErr> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets.entry(key.to_string()).or_insert(Bucket {
+            tokens: limit.capacity(),
+            updated: now,
+        });
+
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * limit.refill_per_sec()).min(limit.capacity());
+        bucket.updated = now;
+
+        let allowed = bucket.tokens >= 1.0;
+        if allowed {
+            bucket.tokens -= 1.0;
+        }
+        Ok((bucket.tokens, allowed))
+    }
+
+    async fn purge_idle(&self, idle: Duration) -> Result<u64, DbErr> {
+        let mut buckets = self.buckets.lock().unwrap();
+        let before = buckets.len();
+        buckets.retain(|_, bucket| bucket.updated.elapsed() < idle);
+        Ok((before - buckets.len()) as u64)
+    }
+}
@@ -0,0 +1,356 @@
+mod memory;
+mod postgres;
+
+pub use memory::MemoryStore;
+pub use postgres::PostgresStore;
+
+use actix_web::http::Method;
+use actix_web::http::header::{HeaderMap, HeaderName};
+use sea_orm::{DbConn, DbErr};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::{RateLimitBackend, RateLimitConfig};
+use crate::shutdown::Shutdown;
+
+/// How often buckets that have refilled completely are dropped.
+const PURGE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Hex characters of the API key digest used in bucket keys.
+const API_KEY_HASH_LENGTH: usize = 12;
+
+/// A token bucket: `capacity` requests at once, refilled at
+/// `requests_per_minute`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Limit {
+    pub requests_per_minute: u32,
+    pub burst: u32,
+}
+
+impl Limit {
+    pub fn capacity(&self) -> f64 {
+        f64::from(self.burst)
+    }
+
+    pub fn refill_per_sec(&self) -> f64 {
+        f64::from(self.requests_per_minute) / 60.0
+    }
+
+    /// Seconds for an empty bucket to fill up again.
+    fn refill_secs(&self) -> f64 {
+        self.capacity() / self.refill_per_sec()
+    }
+}
+
+/// Stores buckets; implementations refill and take a token atomically.
+#[async_trait::async_trait]
+pub trait RateLimitStore: Send + Sync {
+    /// Takes a token from bucket `key`, returning the tokens left and
+    /// whether one was taken.
+    async fn take(&self, key: &str, limit: Limit) -> Result<(f64, bool), DbErr>;
+
+    /// Drops buckets untouched for `idle`, returning how many were dropped.
+    async fn purge_idle(&self, idle: Duration) -> Result<u64, DbErr>;
+}
+
+/// Outcome of a rate limit check, rendered as `RateLimit-*` headers.
+#[derive(Debug, Clone, Copy)]
+pub struct Decision {
+    pub allowed: bool,
+    pub limit: Limit,
+    pub remaining: u32,
+    /// Seconds until the bucket is full again.
+    pub reset_secs: u64,
+    /// Seconds until the next token is available.
+    pub retry_after_secs: u64,
+}
+
+impl Decision {
+    fn new(limit: Limit, tokens: f64, allowed: bool) -> Self {
+        let tokens = tokens.clamp(0.0, limit.capacity());
+        let rate = limit.refill_per_sec();
+        Self {
+            allowed,
+            limit,
+            remaining: tokens.floor() as u32,
+            reset_secs: ((limit.capacity() - tokens) / rate).ceil() as u64,
+            retry_after_secs: ((1.0 - tokens) / rate).ceil().max(1.0) as u64,
+        }
+    }
+
+    /// Value of the `RateLimit-Policy` header.
+    pub fn policy(&self) -> String {
+        format!(
+            "{};w=60;burst={}",
+            self.limit.requests_per_minute, self.limit.burst
+        )
+    }
+}
+
+struct RouteLimit {
+    path: String,
+    method: Option<Method>,
+    limit: Limit,
+}
+
+/// Per-client token buckets, with limits chosen by the matched route.
+pub struct RateLimiter {
+    store: Arc<dyn RateLimitStore>,
+    default: Limit,
+    routes: Vec<RouteLimit>,
+    api_key_header: Option<HeaderName>,
+    /// SHA-256 digests of the configured API keys.
+    api_keys: HashSet<[u8; 32]>,
+    pub trust_forwarded_for: bool,
+}
+
+impl RateLimiter {
+    pub fn new(config: &RateLimitConfig, db: &DbConn) -> Self {
+        let store: Arc<dyn RateLimitStore> = match config.backend {
+            RateLimitBackend::Memory => Arc::new(MemoryStore::new()),
+            RateLimitBackend::Postgres => Arc::new(PostgresStore::new(db.clone())),
+        };
+
+        let routes = config
+            .routes
+            .iter()
+            .map(|route| RouteLimit {
+                path: route.path.clone(),
+                method: route
+                    .method
+                    .as_deref()
+                    .and_then(|method| Method::from_str(&method.to_uppercase()).ok()),
+                limit: Limit {
+                    requests_per_minute: route.requests_per_minute,
+                    burst: route.burst,
+                },
+            })
+            .collect();
+
+        Self {
+            store,
+            default: Limit {
+                requests_per_minute: config.requests_per_minute,
+                burst: config.burst,
+            },
+            routes,
+            api_key_header: config
+                .api_key_header
+                .as_deref()
+                .and_then(|header| HeaderName::from_str(header).ok()),
+            api_keys: config
+                .api_keys
+                .iter()
+                .map(|key| Sha256::digest(key.expose().as_bytes()).into())
+                .collect(),
+            trust_forwarded_for: config.trust_forwarded_for,
+        }
+    }
+
+    /// Bucket client for the request's API key, if it carries one of the
+    /// configured keys. Unknown keys are ignored, so inventing a new key
+    /// per request cannot buy a fresh bucket.
+    pub fn api_key_client(&self, headers: &HeaderMap) -> Option<String> {
+        let key = headers.get(self.api_key_header.as_ref()?)?;
+        let digest: [u8; 32] = Sha256::digest(key.as_bytes()).into();
+        if !self.api_keys.contains(&digest) {
+            return None;
+        }
+
+        let hex: String = digest.iter().map(|byte| format!("{:02x}", byte)).collect();
+        Some(format!("key:{}", &hex[..API_KEY_HASH_LENGTH]))
+    }
+
+    /// Returns the bucket scope and limit for a request; routes without a
+    /// configured limit share the `default` scope.
+    pub fn limit_for(&self, method: &Method, route: Option<&str>) -> (String, Limit) {
+        let matched = route.and_then(|route| {
+            self.routes.iter().find(|limit| {
+                limit.path == route && limit.method.as_ref().is_none_or(|m| m == method)
+            })
+        });
+
+        match matched {
+            Some(RouteLimit {
+                path,
+                method: Some(method),
+                limit,
+            }) => (format!("{} {}", method, path), *limit),
+            Some(RouteLimit { path, limit, .. }) => (format!("* {}", path), *limit),
+            None => ("default".to_string(), self.default),
+        }
+    }
+
+    /// Takes a token for `client` in `scope`. Store failures let the
+    /// request through rather than failing the API with the database.
+    pub async fn check(&self, scope: &str, client: &str, limit: Limit) -> Decision {
+        let key = format!("{}|{}", scope, client);
+        match self.store.take(&key, limit).await {
+            Ok((tokens, allowed)) => Decision::new(limit, tokens, allowed),
+            Err(err) => {
+                log::warn!("Rate limit check failed, allowing request: {}", err);
+                Decision::new(limit, limit.capacity(), true)
+            }
+        }
+    }
+
+    /// Periodically drops buckets that have refilled completely, until
+    /// shutdown.
+    pub fn spawn_purger(self: Arc<Self>, shutdown: &Shutdown) {
+        let idle = self
+            .routes
+            .iter()
+            .map(|route| route.limit.refill_secs())
+            .fold(self.default.refill_secs(), f64::max);
+        let idle = Duration::from_secs_f64(idle.ceil());
+        let signal = shutdown.clone();
+
+        shutdown.spawn(async move {
+            let mut interval = tokio::time::interval(PURGE_INTERVAL);
+            interval.tick().await;
+            loop {
+                tokio::select! {
+                    _ = signal.signalled() => break,
+                    _ = interval.tick() => {}
+                }
+                match self.store.purge_idle(idle).await {
+                    Ok(0) => {}
+                    Ok(purged) => log::debug!("Purged {} idle rate limit bucket(s)", purged),
+                    Err(err) => log::warn!("Failed to purge rate limit buckets: {}", err),
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RouteRateLimit;
+    use crate::redaction::Redacted;
+    use actix_web::http::header::HeaderValue;
+
+    const LIMIT: Limit = Limit {
+        requests_per_minute: 6,
+        burst: 10,
+    };
+
+    fn limiter(api_key_header: Option<&str>, api_keys: &[&str]) -> RateLimiter {
+        RateLimiter::new(
+            &RateLimitConfig {
+                enabled: true,
+                backend: RateLimitBackend::Memory,
+                requests_per_minute: LIMIT.requests_per_minute,
+                burst: LIMIT.burst,
+                routes: vec![
+                    RouteRateLimit {
+                        path: "/api/auth/login".into(),
+                        method: Some("post".into()),
+                        requests_per_minute: 5,
+                        burst: 5,
+                    },
+                    RouteRateLimit {
+                        path: "/api/users".into(),
+                        method: None,
+                        requests_per_minute: 60,
+                        burst: 20,
+                    },
+                ],
+                api_key_header: api_key_header.map(str::to_string),
+                api_keys: api_keys
+                    .iter()
+                    .map(|key| Redacted::new(key.to_string()))
+                    .collect(),
+                trust_forwarded_for: false,
+            },
+            &DbConn::Disconnected,
+        )
+    }
+
+    #[test]
+    fn decision_reports_remaining_and_reset() {
+        let decision = Decision::new(LIMIT, 3.5, true);
+
+        assert_eq!(decision.remaining, 3);
+        // 6.5 tokens missing at 0.1 per second.
+        assert_eq!(decision.reset_secs, 65);
+        assert_eq!(decision.retry_after_secs, 1);
+        assert_eq!(decision.policy(), "6;w=60;burst=10");
+    }
+
+    #[test]
+    fn retry_after_waits_for_the_next_token() {
+        let decision = Decision::new(LIMIT, 0.25, false);
+
+        assert!(!decision.allowed);
+        assert_eq!(decision.remaining, 0);
+        assert_eq!(decision.retry_after_secs, 8);
+        assert_eq!(decision.reset_secs, 98);
+    }
+
+    #[test]
+    fn decision_clamps_tokens() {
+        let full = Decision::new(LIMIT, 12.0, true);
+        assert_eq!((full.remaining, full.reset_secs), (10, 0));
+
+        let empty = Decision::new(LIMIT, -1.0, false);
+        assert_eq!((empty.remaining, empty.retry_after_secs), (0, 10));
+    }
+
+    #[actix_web::test]
+    async fn bucket_empties_and_refills() {
+        let store = MemoryStore::new();
+        let limit = Limit {
+            requests_per_minute: 60,
+            burst: 2,
+        };
+
+        assert_eq!(store.take("k", limit).await.unwrap(), (1.0, true));
+        let (tokens, allowed) = store.take("k", limit).await.unwrap();
+        assert!(allowed && tokens < 0.1);
+        assert!(!store.take("k", limit).await.unwrap().1);
+        assert!(store.take("other", limit).await.unwrap().1);
+    }
+
+    #[test]
+    fn routes_select_their_own_scope() {
+        let limiter = limiter(None, &[]);
+
+        let (scope, limit) = limiter.limit_for(&Method::POST, Some("/api/auth/login"));
+        assert_eq!((scope.as_str(), limit.burst), ("POST /api/auth/login", 5));
+
+        let (scope, limit) = limiter.limit_for(&Method::GET, Some("/api/users"));
+        assert_eq!((scope.as_str(), limit.burst), ("* /api/users", 20));
+
+        for (method, route) in [
+            (Method::GET, Some("/api/auth/login")),
+            (Method::GET, Some("/api/users/{id}")),
+            (Method::GET, None),
+        ] {
+            let (scope, limit) = limiter.limit_for(&method, route);
+            assert_eq!((scope.as_str(), limit), ("default", LIMIT));
+        }
+    }
+
+    #[test]
+    fn only_configured_api_keys_identify_a_client() {
+        let limiter = limiter(Some("x-api-key"), &["known-key"]);
+        let headers = |key: &'static str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                HeaderName::from_static("x-api-key"),
+                HeaderValue::from_static(key),
+            );
+            headers
+        };
+
+        let client = limiter.api_key_client(&headers("known-key")).unwrap();
+        assert!(client.starts_with("key:"));
+        assert_eq!(client.len(), "key:".len() + API_KEY_HASH_LENGTH);
+        assert_eq!(limiter.api_key_client(&headers("made-up-key")), None);
+        assert_eq!(limiter.api_key_client(&HeaderMap::new()), None);
+    }
+}
@@ -0,0 +1,32 @@
+use sea_orm::{DbConn, DbErr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::{Limit, RateLimitStore};
+use crate::db::repositories::RateLimitRepository;
+
+/// Buckets in `tbl_rate_limit_buckets`, shared by every replica.
+pub struct PostgresStore {
+    repository: RateLimitRepository,
+}
+
+impl PostgresStore {
+    pub fn new(db: DbConn) -> Self {
+        Self {
+            repository: RateLimitRepository::new(Arc::new(db)),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl RateLimitStore for PostgresStore {
+    async fn take(&self, key: &str, limit: Limit) -> Result<(f64, bool), DbErr> {
+        self.repository
+            .take(key, limit.capacity(), limit.refill_per_sec())
+            .await
+    }
+
+    async fn purge_idle(&self, idle: Duration) -> Result<u64, DbErr> {
+        self.repository.purge_idle(idle.as_secs_f64()).await
+    }
+}
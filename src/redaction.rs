@@ -0,0 +1,298 @@
+use regex::Regex;
+use serde::{Deserialize, Deserializer};
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::{LazyLock, OnceLock};
+
+use crate::config::{RedactionConfig, RedactionRule};
+
+const REDACTED: &str = "[REDACTED]";
+/// Hex characters of the digest kept by the `hash` rule.
+const HASH_LENGTH: usize = 12;
+
+static RULES: OnceLock<RedactionConfig> = OnceLock::new();
+
+static EMAIL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)[a-z0-9._%+-]+@[a-z0-9-]+(?:\.[a-z0-9-]+)*\.[a-z]{2,}").unwrap()
+});
+
+/// Candidate phone numbers; `scrub` keeps only those with 7 to 15 digits
+/// that do not look like a date or an IPv4 address.
+static PHONE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\+?\(?\d[\d ().-]{5,}\d").unwrap());
+
+static DATE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\d{4}-\d{2}-\d{2}").unwrap());
+
+/// Installs the rules; until then the defaults apply.
+pub fn init(config: &RedactionConfig) {
+    let _ = RULES.set(config.clone());
+}
+
+fn rules() -> &'static RedactionConfig {
+    RULES.get_or_init(RedactionConfig::default)
+}
+
+/// Kind of personal data, selecting the configured rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pii {
+    Email,
+    Phone,
+    Name,
+    Identifier,
+    /// Passwords and tokens; never rendered.
+    Secret,
+}
+
+impl Pii {
+    fn rule(self) -> RedactionRule {
+        let rules = rules();
+        if !rules.enabled && self != Pii::Secret {
+            return RedactionRule::Keep;
+        }
+        match self {
+            Pii::Email => rules.email,
+            Pii::Phone => rules.phone,
+            Pii::Name => rules.name,
+            Pii::Identifier => rules.identifier,
+            Pii::Secret => RedactionRule::Drop,
+        }
+    }
+}
+
+/// Renders `value` according to the rule configured for `kind`.
+pub fn redact(kind: Pii, value: &str) -> Cow<'_, str> {
+    match kind.rule() {
+        RedactionRule::Keep => Cow::Borrowed(value),
+        RedactionRule::Mask => Cow::Owned(mask(kind, value)),
+        RedactionRule::Hash => Cow::Owned(hash(value)),
+        RedactionRule::Drop => Cow::Borrowed(REDACTED),
+    }
+}
+
+fn mask(kind: Pii, value: &str) -> String {
+    match kind {
+        Pii::Email => match value.split_once('@') {
+            Some((local, domain)) => format!("{}***@{}", first_char(local), domain),
+            None => format!("{}***", first_char(value)),
+        },
+        Pii::Phone => {
+            let digits: Vec<char> = value.chars().filter(char::is_ascii_digit).collect();
+            let last: String = digits[digits.len().saturating_sub(2)..].iter().collect();
+            format!("***{}", last)
+        }
+        _ => format!("{}***", first_char(value)),
+    }
+}
+
+fn first_char(value: &str) -> String {
+    value.chars().next().map(String::from).unwrap_or_default()
+}
+
+fn hash(value: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(rules().hash_key.as_bytes());
+    hasher.update([0]);
+    hasher.update(value.as_bytes());
+    let digest: String = hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    format!("sha256:{}", &digest[..HASH_LENGTH])
+}
+
+/// Applies the email and phone rules to free text such as log messages.
+/// Other personal data cannot be recognised and must be wrapped in
+/// [`Redacted`] where it is logged.
+pub fn scrub(text: &str) -> Cow<'_, str> {
+    if !rules().enabled {
+        return Cow::Borrowed(text);
+    }
+
+    let text = EMAIL.replace_all(text, |caps: &regex::Captures| {
+        redact(Pii::Email, &caps[0]).into_owned()
+    });
+    if !text.bytes().any(|byte| byte.is_ascii_digit()) {
+        return text;
+    }
+
+    let source = text.as_ref();
+    let mut scrubbed = String::with_capacity(source.len());
+    let mut last = 0;
+    for candidate in PHONE.find_iter(source) {
+        if is_phone(source, candidate.start(), candidate.end()) {
+            scrubbed.push_str(&source[last..candidate.start()]);
+            scrubbed.push_str(&redact(Pii::Phone, candidate.as_str()));
+            last = candidate.end();
+        }
+    }
+    if last == 0 {
+        return text;
+    }
+    scrubbed.push_str(&source[last..]);
+    Cow::Owned(scrubbed)
+}
+
+fn is_phone(text: &str, start: usize, end: usize) -> bool {
+    let candidate = &text[start..end];
+    let digits = candidate.chars().filter(char::is_ascii_digit).count();
+    let is_word_char = |c: char| c.is_alphanumeric() || matches!(c, '_' | '-' | '/' | ':');
+    let standalone =
+        !text[..start].ends_with(is_word_char) && !text[end..].starts_with(is_word_char);
+    let is_ipv4 = candidate.split('.').count() == 4
+        && candidate
+            .split('.')
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()));
+
+    standalone && (7..=15).contains(&digits) && !is_ipv4 && !DATE.is_match(candidate)
+}
+
+/// Wraps a sensitive value so `Debug` and `Display` render it redacted;
+/// the raw value is only reachable through [`Redacted::expose`].
+/// Deserialized values are treated as secrets.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Redacted<T> {
+    value: T,
+    kind: Pii,
+}
+
+impl<T> Redacted<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            value,
+            kind: Pii::Secret,
+        }
+    }
+
+    pub fn email(value: T) -> Self {
+        Self {
+            value,
+            kind: Pii::Email,
+        }
+    }
+
+    pub fn phone(value: T) -> Self {
+        Self {
+            value,
+            kind: Pii::Phone,
+        }
+    }
+
+    pub fn name(value: T) -> Self {
+        Self {
+            value,
+            kind: Pii::Name,
+        }
+    }
+
+    pub fn identifier(value: T) -> Self {
+        Self {
+            value,
+            kind: Pii::Identifier,
+        }
+    }
+
+    pub fn expose(&self) -> &T {
+        &self.value
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T: AsRef<str>> fmt::Display for Redacted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&redact(self.kind, self.value.as_ref()))
+    }
+}
+
+impl<T: AsRef<str>> fmt::Debug for Redacted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", redact(self.kind, self.value.as_ref()))
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Redacted<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(Redacted::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Tests run with the default rules: emails and names masked, phones
+    // dropped and identifiers hashed.
+
+    #[test]
+    fn scrubs_emails_and_phones() {
+        assert_eq!(
+            scrub("Sent to jane.doe@example.com and +1 (555) 123-4567."),
+            "Sent to j***@example.com and [REDACTED]."
+        );
+    }
+
+    #[test]
+    fn leaves_dates_ips_and_ids_alone() {
+        let text = "at 2026-10-18 12:30:05 from 192.168.100.200 for user 42 in req-1234567";
+        assert_eq!(scrub(text), text);
+    }
+
+    #[test]
+    fn text_without_pii_is_borrowed() {
+        assert!(matches!(scrub("nothing to see"), Cow::Borrowed(_)));
+        assert!(matches!(scrub("user 42 updated"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn masks_by_kind() {
+        assert_eq!(mask(Pii::Email, "jane@example.com"), "j***@example.com");
+        assert_eq!(mask(Pii::Email, "not-an-email"), "n***");
+        assert_eq!(mask(Pii::Phone, "+1 555 123 4567"), "***67");
+        assert_eq!(mask(Pii::Name, "Émile"), "É***");
+        assert_eq!(mask(Pii::Name, ""), "***");
+    }
+
+    #[test]
+    fn hashes_are_stable_and_short() {
+        let hashed = hash("jdoe");
+
+        assert_eq!(hashed, hash("jdoe"));
+        assert_ne!(hashed, hash("jdoe2"));
+        assert_eq!(hashed.len(), "sha256:".len() + HASH_LENGTH);
+    }
+
+    #[test]
+    fn redacted_renders_by_kind() {
+        assert_eq!(
+            Redacted::email("jane@example.com").to_string(),
+            "j***@example.com"
+        );
+        assert_eq!(Redacted::name("Jane").to_string(), "J***");
+        assert_eq!(Redacted::phone("5551234567").to_string(), REDACTED);
+        assert!(
+            Redacted::identifier("jdoe")
+                .to_string()
+                .starts_with("sha256:")
+        );
+    }
+
+    #[test]
+    fn secrets_never_render() {
+        let secret = Redacted::new("hunter2hunter2".to_string());
+
+        assert_eq!(secret.to_string(), REDACTED);
+        assert_eq!(format!("{:?}", secret), format!("{:?}", REDACTED));
+        assert_eq!(secret.expose(), "hunter2hunter2");
+    }
+
+    #[test]
+    fn deserialized_values_are_secrets() {
+        let secret: Redacted<String> = serde_json::from_str(r#""s3cret-value""#).unwrap();
+
+        assert_eq!(format!("{:?}", secret), format!("{:?}", REDACTED));
+        assert_eq!(secret.expose(), "s3cret-value");
+    }
+}
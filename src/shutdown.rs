@@ -0,0 +1,76 @@
+use std::future::Future;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
+
+/// Shared shutdown state: readiness checks whether shutdown has begun, and
+/// background tasks are tracked so they can finish before the database
+/// pool is closed.
+#[derive(Clone, Default)]
+pub struct Shutdown {
+    token: CancellationToken,
+    tasks: TaskTracker,
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    pub fn trigger(&self) {
+        self.token.cancel();
+    }
+
+    /// Resolves once shutdown has begun.
+    pub async fn signalled(&self) {
+        self.token.cancelled().await
+    }
+
+    /// Spawns a background task that is awaited on shutdown. The task
+    /// should return promptly once [`Shutdown::signalled`] resolves.
+    pub fn spawn<F>(&self, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.tasks.spawn(task);
+    }
+
+    /// Number of background tasks still running.
+    pub fn running_tasks(&self) -> usize {
+        self.tasks.len()
+    }
+
+    /// Waits for the background tasks, returning `false` if some were still
+    /// running when `timeout` elapsed.
+    pub async fn wait_for_tasks(&self, timeout: Duration) -> bool {
+        self.tasks.close();
+        tokio::time::timeout(timeout, self.tasks.wait())
+            .await
+            .is_ok()
+    }
+}
+
+/// Resolves on SIGINT or SIGTERM, returning the signal name.
+pub async fn signal() -> &'static str {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        let mut terminate =
+            signal(SignalKind::terminate()).expect("failed to install SIGTERM handler");
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => "SIGINT",
+            _ = terminate.recv() => "SIGTERM",
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+        "SIGINT"
+    }
+}
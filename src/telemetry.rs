@@ -0,0 +1,61 @@
+use opentelemetry::global;
+use opentelemetry::trace::TracerProvider;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::{Sampler, SdkTracerProvider};
+use tracing::Level;
+use tracing_subscriber::Layer;
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+use crate::config::{TraceExporter, TracingConfig};
+
+/// Installs the W3C trace-context propagator and a `tracing` subscriber
+/// that turns spans into OpenTelemetry spans. Spans are always created so
+/// `traceparent` is propagated; they are only exported when an exporter is
+/// configured.
+pub fn init(config: &TracingConfig) -> Result<SdkTracerProvider, String> {
+    global::set_text_map_propagator(TraceContextPropagator::new());
+
+    let mut builder = SdkTracerProvider::builder()
+        .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+            config.sample_ratio,
+        ))))
+        .with_resource(
+            Resource::builder()
+                .with_service_name(config.service_name.clone())
+                .build(),
+        );
+
+    builder = match config.exporter {
+        TraceExporter::None => builder,
+        TraceExporter::Stdout => {
+            builder.with_simple_exporter(opentelemetry_stdout::SpanExporter::default())
+        }
+        TraceExporter::Otlp => {
+            let exporter = opentelemetry_otlp::SpanExporter::builder()
+                .with_http()
+                .with_endpoint(config.otlp_endpoint.clone())
+                .build()
+                .map_err(|err| format!("failed to build the OTLP exporter: {}", err))?;
+            builder.with_batch_exporter(exporter)
+        }
+    };
+
+    let provider = builder.build();
+    global::set_tracer_provider(provider.clone());
+
+    // Only this crate's spans; sqlx and SeaORM open their own per statement.
+    let spans = tracing_opentelemetry::layer()
+        .with_tracer(provider.tracer("rust-actix-seaorm"))
+        .with_filter(Targets::new().with_target(env!("CARGO_CRATE_NAME"), Level::INFO));
+
+    tracing_subscriber::registry()
+        .with(spans)
+        .try_init()
+        .map_err(|err| format!("failed to install the tracing subscriber: {}", err))?;
+
+    Ok(provider)
+}
@@ -0,0 +1,145 @@
+use reqwest::Url;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+/// Whether `ip` is reachable on the public internet, as opposed to the
+/// loopback, private, link-local and other special-purpose ranges that
+/// would let a subscription reach services behind the firewall.
+pub fn is_public(ip: IpAddr) -> bool {
+    match ip.to_canonical() {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => is_public_v6(ip),
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        || a == 0
+        // Shared address space (carrier-grade NAT).
+        || (a == 100 && (64..128).contains(&b))
+        // IETF protocol assignments.
+        || (a == 192 && b == 0 && c == 0)
+        // Benchmarking.
+        || (a == 198 && (18..20).contains(&b))
+        // Reserved.
+        || a >= 240)
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    let [first, second, ..] = ip.segments();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        || ip.is_unique_local()
+        || ip.is_unicast_link_local()
+        // Documentation.
+        || (first == 0x2001 && second == 0x0db8))
+}
+
+/// Rejects URLs whose host is a non-public IP literal or `localhost`.
+/// Other host names are checked when they are resolved for a delivery.
+pub fn check_url(url: &Url) -> Result<(), String> {
+    let host = url.host_str().unwrap_or_default();
+    let public = match host.trim_start_matches('[').trim_end_matches(']').parse() {
+        Ok(ip) => is_public(ip),
+        Err(_) => {
+            let domain = host.trim_end_matches('.').to_ascii_lowercase();
+            !domain.is_empty() && domain != "localhost" && !domain.ends_with(".localhost")
+        }
+    };
+    if public {
+        Ok(())
+    } else {
+        Err(format!("{} is not a public address", host))
+    }
+}
+
+/// Resolves receiver host names to their public addresses only, so a name
+/// pointing at an internal address fails instead of being connected to.
+pub struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let host = name.as_str();
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, 0))
+                .await?
+                .filter(|addr| is_public(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{} does not resolve to a public address", host).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn internal_addresses_are_not_public() {
+        for ip in [
+            "0.0.0.0",
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "255.255.255.255",
+            "::",
+            "::1",
+            "fc00::1",
+            "fd12:3456::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+            "::ffff:169.254.169.254",
+        ] {
+            assert!(!is_public(ip.parse().unwrap()), "{} is public", ip);
+        }
+    }
+
+    #[test]
+    fn internet_addresses_are_public() {
+        for ip in [
+            "93.184.215.14",
+            "8.8.8.8",
+            "2606:4700::1111",
+            "::ffff:1.1.1.1",
+        ] {
+            assert!(is_public(ip.parse().unwrap()), "{} is not public", ip);
+        }
+    }
+
+    #[test]
+    fn urls_with_internal_hosts_are_rejected() {
+        for url in [
+            "http://127.0.0.1:9100/hook",
+            "http://[::1]/hook",
+            "http://169.254.169.254/latest/meta-data",
+            "http://2130706433/",
+            "http://localhost:8000/",
+            "http://api.LOCALHOST./",
+        ] {
+            assert!(check_url(&Url::parse(url).unwrap()).is_err(), "{}", url);
+        }
+        assert!(check_url(&Url::parse("https://hooks.example.com/users").unwrap()).is_ok());
+        assert!(check_url(&Url::parse("https://93.184.215.14/hook").unwrap()).is_ok());
+    }
+
+    #[actix_web::test]
+    async fn resolver_refuses_names_of_internal_addresses() {
+        let name: Name = "localhost".parse().unwrap();
+
+        assert!(PublicResolver.resolve(name).await.is_err());
+    }
+}
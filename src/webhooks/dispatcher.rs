@@ -0,0 +1,643 @@
+use actix_web::http::header::{CONTENT_TYPE, USER_AGENT};
+use sea_orm::sqlx::types::chrono::{Local, NaiveDateTime};
+use sea_orm::{DbConn, DbErr, Set};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
+
+use super::address::{PublicResolver, check_url};
+use super::{EVENT_HEADER, ID_HEADER, SIGNATURE_HEADER, TIMESTAMP_HEADER};
+use crate::config::WebhooksConfig;
+use crate::db::models::{
+    WebhookAttemptActiveModel, WebhookDeliveryModel, WebhookSubscriptionModel,
+};
+use crate::db::repositories::{
+    DeliveryOutcome, WebhookDeliveryRepository, WebhookSubscriptionRepository,
+};
+use crate::events::retry_delay;
+use crate::metrics::metrics;
+use crate::shutdown::Shutdown;
+
+/// Characters of the response body kept with an attempt.
+const RESPONSE_EXCERPT_CHARS: usize = 1024;
+/// Bytes read from a response body; the rest is never downloaded.
+const RESPONSE_READ_LIMIT: usize = 4 * RESPONSE_EXCERPT_CHARS;
+
+/// What came back from one request.
+struct Response {
+    status_code: Option<u16>,
+    error: Option<String>,
+    body: Option<String>,
+}
+
+/// Sends queued deliveries, retrying failures with exponential backoff.
+pub struct Dispatcher {
+    deliveries: WebhookDeliveryRepository,
+    subscriptions: WebhookSubscriptionRepository,
+    client: reqwest::Client,
+    config: WebhooksConfig,
+}
+
+impl Dispatcher {
+    pub fn new(config: &WebhooksConfig, db: &DbConn) -> Self {
+        let db = Arc::new(db.clone());
+        // Redirects are not followed, so a receiver cannot bounce a signed
+        // request to another host.
+        let mut client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.timeout_ms))
+            .redirect(reqwest::redirect::Policy::none());
+        if !config.allow_private_networks {
+            client = client.dns_resolver(PublicResolver);
+        }
+        let client = client
+            .build()
+            .expect("the webhook HTTP client always builds");
+
+        Self {
+            deliveries: WebhookDeliveryRepository::new(db.clone()),
+            subscriptions: WebhookSubscriptionRepository::new(db),
+            client,
+            config: config.clone(),
+        }
+    }
+
+    /// Sends one batch of due deliveries concurrently, returning how many
+    /// were claimed.
+    pub async fn run_once(self: &Arc<Self>) -> Result<usize, DbErr> {
+        let deliveries = self
+            .deliveries
+            .claim_due(self.config.batch_size, self.config.lease_secs)
+            .await?;
+        if deliveries.is_empty() {
+            return Ok(0);
+        }
+
+        let mut ids: Vec<i32> = deliveries.iter().map(|d| d.subscription_id).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        let subscriptions: HashMap<i32, WebhookSubscriptionModel> = self
+            .subscriptions
+            .find_by_ids(ids)
+            .await?
+            .into_iter()
+            .map(|subscription| (subscription.id, subscription))
+            .collect();
+
+        let claimed = deliveries.len();
+        let mut attempts = JoinSet::new();
+        for delivery in deliveries {
+            // Deleting a subscription deletes its deliveries.
+            let Some(subscription) = subscriptions.get(&delivery.subscription_id).cloned() else {
+                continue;
+            };
+            let dispatcher = self.clone();
+            attempts.spawn(async move { dispatcher.attempt(delivery, subscription).await });
+        }
+        while let Some(result) = attempts.join_next().await {
+            if let Ok(Err(err)) = result {
+                log::warn!("Failed to record a webhook attempt: {}", err);
+            }
+        }
+
+        Ok(claimed)
+    }
+
+    /// Sends a delivery once and records the attempt.
+    async fn attempt(
+        &self,
+        delivery: WebhookDeliveryModel,
+        subscription: WebhookSubscriptionModel,
+    ) -> Result<(), DbErr> {
+        let attempted_on = Local::now().naive_local();
+        let started = Instant::now();
+        let response = if subscription.active {
+            self.send(&delivery, &subscription).await
+        } else {
+            Response {
+                status_code: None,
+                error: Some("subscription is inactive".to_string()),
+                body: None,
+            }
+        };
+        let duration_ms = started.elapsed().as_millis();
+
+        let outcome = self.outcome(
+            response.error.is_none(),
+            subscription.active,
+            delivery.attempts,
+            Local::now().naive_local(),
+        );
+
+        match (&outcome, &response.error) {
+            (DeliveryOutcome::Delivered, _) => {}
+            (DeliveryOutcome::Retry(_), Some(error)) => log::warn!(
+                "Webhook delivery {} to subscription {} failed (attempt {}), will retry: {}",
+                delivery.id,
+                subscription.id,
+                delivery.attempts,
+                error
+            ),
+            (_, error) => log::error!(
+                "Webhook delivery {} to subscription {} is dead after {} attempt(s): {}",
+                delivery.id,
+                subscription.id,
+                delivery.attempts,
+                error.as_deref().unwrap_or_default()
+            ),
+        }
+        let label = match outcome {
+            DeliveryOutcome::Delivered => "delivered",
+            DeliveryOutcome::Retry(_) => "retried",
+            DeliveryOutcome::Dead => "dead",
+        };
+        metrics().webhook_attempts.with_label_values(&[label]).inc();
+
+        self.deliveries
+            .record_attempt(
+                WebhookAttemptActiveModel {
+                    delivery_id: Set(delivery.id),
+                    status_code: Set(response.status_code.map(i32::from)),
+                    error: Set(response.error),
+                    response_body: Set(response.body),
+                    duration_ms: Set(i32::try_from(duration_ms).unwrap_or(i32::MAX)),
+                    attempted_on: Set(attempted_on),
+                    ..Default::default()
+                },
+                outcome,
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Where a delivery goes after its `attempts`th attempt: failures are
+    /// retried with backoff until `max_attempts`, or right away for an
+    /// inactive subscription, after which the delivery is dead.
+    fn outcome(
+        &self,
+        succeeded: bool,
+        active: bool,
+        attempts: i32,
+        now: NaiveDateTime,
+    ) -> DeliveryOutcome {
+        let attempts = u32::try_from(attempts).unwrap_or(0);
+        if succeeded {
+            DeliveryOutcome::Delivered
+        } else if !active || attempts >= self.config.max_attempts {
+            DeliveryOutcome::Dead
+        } else {
+            let delay = retry_delay(
+                self.config.initial_backoff_ms,
+                self.config.max_backoff_ms,
+                attempts,
+            );
+            DeliveryOutcome::Retry(now + delay)
+        }
+    }
+
+    /// POSTs the signed body; anything but a 2xx response is a failure.
+    async fn send(
+        &self,
+        delivery: &WebhookDeliveryModel,
+        subscription: &WebhookSubscriptionModel,
+    ) -> Response {
+        // Literal addresses skip the resolver, and the subscription may
+        // predate the current settings.
+        if !self.config.allow_private_networks
+            && let Err(error) = reqwest::Url::parse(&subscription.url)
+                .map_err(|err| err.to_string())
+                .and_then(|url| check_url(&url))
+        {
+            return Response {
+                status_code: None,
+                error: Some(error),
+                body: None,
+            };
+        }
+
+        let timestamp = Local::now().timestamp();
+        let result = self
+            .client
+            .post(&subscription.url)
+            .header(CONTENT_TYPE.as_str(), "application/json")
+            .header(USER_AGENT.as_str(), "rust-actix-seaorm-webhooks")
+            .header(ID_HEADER, delivery.event_id.to_string())
+            .header(EVENT_HEADER, &delivery.event_type)
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(
+                SIGNATURE_HEADER,
+                super::signature(&subscription.secret, timestamp, &delivery.body),
+            )
+            .body(delivery.body.clone())
+            .send()
+            .await;
+
+        let response = match result {
+            Ok(response) => response,
+            Err(err) => {
+                return Response {
+                    status_code: None,
+                    error: Some(err.to_string()),
+                    body: None,
+                };
+            }
+        };
+
+        let status = response.status();
+        let body = excerpt(response).await;
+        Response {
+            status_code: Some(status.as_u16()),
+            error: (!status.is_success()).then(|| format!("receiver answered {}", status)),
+            body,
+        }
+    }
+
+    /// Polls every `poll_interval_ms`, draining full batches back to back,
+    /// until shutdown. Requests in flight are finished first.
+    pub fn spawn(self: Arc<Self>, shutdown: &Shutdown) {
+        let interval = Duration::from_millis(self.config.poll_interval_ms);
+        let signal = shutdown.clone();
+
+        shutdown.spawn(async move {
+            let mut ticks = tokio::time::interval(interval);
+            loop {
+                tokio::select! {
+                    _ = signal.signalled() => break,
+                    _ = ticks.tick() => {}
+                }
+                loop {
+                    match self.run_once().await {
+                        Ok(claimed) if claimed as u64 == self.config.batch_size => {
+                            if signal.is_shutting_down() {
+                                break;
+                            }
+                        }
+                        Ok(_) => break,
+                        Err(err) => {
+                            log::warn!("Failed to dispatch webhooks: {}", err);
+                            break;
+                        }
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// The start of the response body, read chunk by chunk so a receiver
+/// cannot make the dispatcher buffer an unbounded body.
+async fn excerpt(mut response: reqwest::Response) -> Option<String> {
+    let mut bytes = Vec::new();
+    while bytes.len() < RESPONSE_READ_LIMIT {
+        match response.chunk().await {
+            Ok(Some(chunk)) => bytes.extend_from_slice(&chunk),
+            Ok(None) => break,
+            Err(_) if bytes.is_empty() => return None,
+            Err(_) => break,
+        }
+    }
+    bytes.truncate(RESPONSE_READ_LIMIT);
+    Some(
+        String::from_utf8_lossy(&bytes)
+            .chars()
+            .take(RESPONSE_EXCERPT_CHARS)
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::prelude::Uuid;
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicU16, Ordering};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    use crate::db::repositories::WebhookDeliveryQuery;
+    use crate::webhooks::{DeliveryStatus, signature};
+
+    const SECRET: &str = "whsec_test_secret";
+
+    fn config(allow_private_networks: bool) -> WebhooksConfig {
+        WebhooksConfig {
+            enabled: true,
+            timeout_ms: 5000,
+            poll_interval_ms: 1000,
+            batch_size: 20,
+            lease_secs: 60,
+            max_attempts: 5,
+            initial_backoff_ms: 10_000,
+            max_backoff_ms: 60_000,
+            allow_private_networks,
+        }
+    }
+
+    fn now() -> NaiveDateTime {
+        Local::now().naive_local()
+    }
+
+    fn subscription(url: &str) -> WebhookSubscriptionModel {
+        WebhookSubscriptionModel {
+            id: 1,
+            url: url.to_string(),
+            secret: SECRET.to_string(),
+            event_types: serde_json::json!([]),
+            active: true,
+            created_by: None,
+            created_on: now(),
+            updated_on: now(),
+        }
+    }
+
+    fn delivery() -> WebhookDeliveryModel {
+        WebhookDeliveryModel {
+            id: 1,
+            subscription_id: 1,
+            event_id: Uuid::new_v4(),
+            event_type: "UserCreated".into(),
+            body: r#"{"type":"UserCreated","data":{"user_id":7}}"#.into(),
+            status: "pending".into(),
+            attempts: 1,
+            next_attempt_on: now(),
+            last_status_code: None,
+            last_error: None,
+            created_on: now(),
+            delivered_on: None,
+        }
+    }
+
+    /// A request as the receiver saw it, with lowercase header names.
+    struct Received {
+        headers: HashMap<String, String>,
+        body: String,
+    }
+
+    /// A webhook receiver answering every request with `status`.
+    struct Receiver {
+        url: String,
+        status: Arc<AtomicU16>,
+        requests: Arc<Mutex<Vec<Received>>>,
+    }
+
+    impl Receiver {
+        async fn start(status: u16) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let receiver = Self {
+                url: format!("http://{}/hook", listener.local_addr().unwrap()),
+                status: Arc::new(AtomicU16::new(status)),
+                requests: Arc::default(),
+            };
+            let (status, requests) = (receiver.status.clone(), receiver.requests.clone());
+            tokio::spawn(async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    let status = status.load(Ordering::SeqCst);
+                    if let Some(request) = Self::answer(stream, status).await {
+                        requests.lock().unwrap().push(request);
+                    }
+                }
+            });
+            receiver
+        }
+
+        async fn answer(mut stream: TcpStream, status: u16) -> Option<Received> {
+            let mut bytes = Vec::new();
+            let mut buf = [0u8; 1024];
+            let head_end = loop {
+                let read = stream.read(&mut buf).await.ok().filter(|&n| n > 0)?;
+                bytes.extend_from_slice(&buf[..read]);
+                if let Some(end) = bytes.windows(4).position(|w| w == b"\r\n\r\n") {
+                    break end + 4;
+                }
+            };
+            let head = String::from_utf8_lossy(&bytes[..head_end]).into_owned();
+            let headers: HashMap<String, String> = head
+                .lines()
+                .skip(1)
+                .filter_map(|line| line.split_once(": "))
+                .map(|(name, value)| (name.to_ascii_lowercase(), value.to_string()))
+                .collect();
+            let length: usize = headers.get("content-length")?.parse().ok()?;
+            while bytes.len() < head_end + length {
+                let read = stream.read(&mut buf).await.ok().filter(|&n| n > 0)?;
+                bytes.extend_from_slice(&buf[..read]);
+            }
+            let response = format!(
+                "HTTP/1.1 {} Test\r\ncontent-length: 2\r\nconnection: close\r\n\r\nok",
+                status
+            );
+            stream.write_all(response.as_bytes()).await.ok()?;
+            Some(Received {
+                headers,
+                body: String::from_utf8_lossy(&bytes[head_end..]).into_owned(),
+            })
+        }
+
+        fn received(&self) -> usize {
+            self.requests.lock().unwrap().len()
+        }
+    }
+
+    #[actix_web::test]
+    async fn send_signs_the_request() {
+        let receiver = Receiver::start(204).await;
+        let dispatcher = Dispatcher::new(&config(true), &DbConn::Disconnected);
+        let delivery = delivery();
+
+        let response = dispatcher
+            .send(&delivery, &subscription(&receiver.url))
+            .await;
+
+        assert_eq!(response.status_code, Some(204));
+        assert_eq!(response.error, None);
+        let requests = receiver.requests.lock().unwrap();
+        let request = &requests[0];
+        let header = |name: &str| request.headers[name].as_str();
+        let timestamp: i64 = header(TIMESTAMP_HEADER).parse().unwrap();
+        assert_eq!(request.body, delivery.body);
+        assert_eq!(header(ID_HEADER), delivery.event_id.to_string());
+        assert_eq!(header(EVENT_HEADER), "UserCreated");
+        assert_eq!(
+            header(SIGNATURE_HEADER),
+            signature(SECRET, timestamp, &delivery.body)
+        );
+    }
+
+    #[actix_web::test]
+    async fn send_fails_unless_the_receiver_answers_2xx() {
+        let receiver = Receiver::start(500).await;
+        let dispatcher = Dispatcher::new(&config(true), &DbConn::Disconnected);
+
+        let response = dispatcher
+            .send(&delivery(), &subscription(&receiver.url))
+            .await;
+
+        assert_eq!(response.status_code, Some(500));
+        assert_eq!(response.body.as_deref(), Some("ok"));
+        assert!(response.error.unwrap().contains("500"));
+    }
+
+    #[actix_web::test]
+    async fn send_refuses_private_addresses_unless_allowed() {
+        let receiver = Receiver::start(204).await;
+        let dispatcher = Dispatcher::new(&config(false), &DbConn::Disconnected);
+        let by_name = receiver.url.replace("127.0.0.1", "localhost");
+
+        for url in [&receiver.url, &by_name] {
+            let response = dispatcher.send(&delivery(), &subscription(url)).await;
+
+            assert_eq!(response.status_code, None);
+            assert!(response.error.unwrap().contains("not a public address"));
+        }
+        assert_eq!(receiver.received(), 0);
+    }
+
+    #[actix_web::test]
+    async fn excerpt_stops_reading_at_the_limit() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let _ = stream.read(&mut [0u8; 1024]).await;
+            let head = "HTTP/1.1 500 Internal Server Error\r\ncontent-length: 1000000000\r\n\r\n";
+            stream.write_all(head.as_bytes()).await.unwrap();
+            while stream.write_all(&[b'x'; 8192]).await.is_ok() {}
+        });
+        let response = reqwest::get(&url).await.unwrap();
+
+        let body = tokio::time::timeout(Duration::from_secs(5), excerpt(response))
+            .await
+            .expect("the body is not read to the end")
+            .unwrap();
+
+        assert_eq!(body.len(), RESPONSE_EXCERPT_CHARS);
+    }
+
+    #[test]
+    fn failures_back_off_until_the_delivery_is_dead() {
+        let dispatcher = Dispatcher::new(&config(false), &DbConn::Disconnected);
+        let now = now();
+        let retry = |secs| DeliveryOutcome::Retry(now + Duration::from_secs(secs));
+
+        let schedule: Vec<DeliveryOutcome> = (1..=5)
+            .map(|attempts| dispatcher.outcome(false, true, attempts, now))
+            .collect();
+
+        assert_eq!(
+            schedule,
+            [
+                retry(10),
+                retry(20),
+                retry(40),
+                retry(60),
+                DeliveryOutcome::Dead
+            ]
+        );
+        // A redelivery starts again from the first attempt.
+        assert_eq!(dispatcher.outcome(false, true, 1, now), retry(10));
+    }
+
+    #[test]
+    fn success_delivers_and_inactive_subscriptions_die() {
+        let dispatcher = Dispatcher::new(&config(false), &DbConn::Disconnected);
+
+        assert_eq!(
+            dispatcher.outcome(true, true, 5, now()),
+            DeliveryOutcome::Delivered
+        );
+        assert_eq!(
+            dispatcher.outcome(false, false, 1, now()),
+            DeliveryOutcome::Dead
+        );
+    }
+
+    async fn only_delivery(dispatcher: &Dispatcher, subscription_id: i32) -> WebhookDeliveryModel {
+        let mut page = dispatcher
+            .deliveries
+            .find_page(&WebhookDeliveryQuery {
+                subscription_id,
+                limit: 2,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(page.deliveries.len(), 1);
+        page.deliveries.remove(0)
+    }
+
+    /// Runs against the database in `TEST_DATABASE_URL`, which it migrates:
+    /// `TEST_DATABASE_URL=postgres://... cargo test -- --ignored`.
+    #[actix_web::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn dead_delivery_is_redelivered_with_a_new_attempt() {
+        use sea_orm_migration::MigratorTrait;
+
+        use crate::db::migrations::Migrator;
+        use crate::db::models::WebhookSubscriptionActiveModel;
+
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL is not set");
+        let db = sea_orm::Database::connect(url).await.unwrap();
+        Migrator::up(&db, None).await.unwrap();
+        let receiver = Receiver::start(503).await;
+        let config = WebhooksConfig {
+            max_attempts: 3,
+            initial_backoff_ms: 0,
+            ..config(true)
+        };
+        let dispatcher = Arc::new(Dispatcher::new(&config, &db));
+        let subscription = dispatcher
+            .subscriptions
+            .create(WebhookSubscriptionActiveModel {
+                url: Set(receiver.url.clone()),
+                secret: Set(SECRET.to_string()),
+                event_types: Set(serde_json::json!([])),
+                active: Set(true),
+                created_on: Set(now()),
+                updated_on: Set(now()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        dispatcher
+            .deliveries
+            .enqueue(
+                std::slice::from_ref(&subscription),
+                Uuid::new_v4(),
+                "UserCreated",
+                "{}",
+            )
+            .await
+            .unwrap();
+
+        for _ in 0..4 {
+            dispatcher.run_once().await.unwrap();
+        }
+        let delivery = only_delivery(&dispatcher, subscription.id).await;
+        assert_eq!(delivery.status, DeliveryStatus::Dead.as_str());
+        assert_eq!(delivery.last_status_code, Some(503));
+        assert_eq!(receiver.received(), 3);
+
+        receiver.status.store(200, Ordering::SeqCst);
+        dispatcher.deliveries.redeliver(delivery.id).await.unwrap();
+        dispatcher.run_once().await.unwrap();
+
+        let delivery = only_delivery(&dispatcher, subscription.id).await;
+        let attempts = dispatcher
+            .deliveries
+            .find_attempts(delivery.id)
+            .await
+            .unwrap();
+        assert_eq!(delivery.status, DeliveryStatus::Delivered.as_str());
+        assert_eq!(delivery.attempts, 1);
+        assert_eq!(
+            attempts.iter().map(|a| a.status_code).collect::<Vec<_>>(),
+            [Some(503), Some(503), Some(503), Some(200)]
+        );
+
+        dispatcher
+            .subscriptions
+            .delete(subscription.id)
+            .await
+            .unwrap();
+    }
+}
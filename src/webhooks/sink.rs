@@ -0,0 +1,52 @@
+use sea_orm::DbConn;
+use std::sync::Arc;
+
+use crate::db::models::OutboxEventModel;
+use crate::db::repositories::{WebhookDeliveryRepository, WebhookSubscriptionRepository};
+use crate::events::EventSink;
+
+/// Queues a delivery of each event for every active subscription whose
+/// filter matches; the [`super::Dispatcher`] sends them.
+pub struct WebhookSink {
+    subscriptions: WebhookSubscriptionRepository,
+    deliveries: WebhookDeliveryRepository,
+}
+
+impl WebhookSink {
+    pub fn new(db: &DbConn) -> Self {
+        let db = Arc::new(db.clone());
+        Self {
+            subscriptions: WebhookSubscriptionRepository::new(db.clone()),
+            deliveries: WebhookDeliveryRepository::new(db),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl EventSink for WebhookSink {
+    fn name(&self) -> &'static str {
+        "webhooks"
+    }
+
+    async fn deliver(&self, event: &OutboxEventModel) -> Result<(), String> {
+        let subscriptions: Vec<_> = self
+            .subscriptions
+            .find_active()
+            .await
+            .map_err(|err| err.to_string())?
+            .into_iter()
+            .filter(|subscription| super::matches(subscription, &event.event_type))
+            .collect();
+
+        self.deliveries
+            .enqueue(
+                &subscriptions,
+                event.event_id,
+                &event.event_type,
+                &super::body(event),
+            )
+            .await
+            .map_err(|err| err.to_string())?;
+        Ok(())
+    }
+}